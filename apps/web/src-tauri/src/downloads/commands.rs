@@ -12,7 +12,17 @@
 #![allow(clippy::unnecessary_wraps)] // Tauri commands require Result return type
 
 use super::manager;
-use super::state::{DownloadProgressEvent, DownloadState, StorageCheckResult};
+use super::metadata::{clear_complete, is_complete, weights_path, DownloadManifest};
+use super::shards;
+use super::state::{
+    DownloadProgressEvent, DownloadRequest, DownloadState, InstalledModel, ModelChange,
+    ModelReadiness, NetworkError, OrphanedDownload, StorageCheckResult, StorageUsage,
+};
+use crate::hardware::mount_for_path;
+use crate::inference::InferenceState;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
 use sysinfo::Disks;
 use tauri::{AppHandle, State};
 
@@ -33,27 +43,84 @@ use tauri::{AppHandle, State};
 /// * `url` - The download URL for the GGUF model
 /// * `tokenizer_url` - The download URL for the tokenizer.json
 /// * `expected_hash` - Optional SHA-256 hash for verification (Story 2.5)
+/// * `tokenizer_hash` - Optional SHA-256 of tokenizer.json; a mismatch fails
+///   with `tokenizer_corrupted` set and the file deleted
+/// * `headers` - Optional extra HTTP headers (e.g. `User-Agent`, or
+///   `Authorization: Bearer <token>` for gated repositories), sent with every
+///   request and kept for resume; auth values are redacted in logs
+/// * `shard_hashes` - SHA-256 of each shard when `url` is the first shard of a
+///   split model (`*-00001-of-00003.gguf`); every shard is downloaded
+/// * `mirrors` - Fallback URLs for the same file, tried in order when `url`
+///   can't be reached or answers with an error status
 ///
 /// # Returns
 /// * `download_id` - Unique ID for tracking this download
+/// * `NetworkError { offline: true }` if the download host is unreachable
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn start_download(
     app: AppHandle,
     model_id: String,
     url: String,
     tokenizer_url: String,
     expected_hash: Option<String>,
+    tokenizer_hash: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    shard_hashes: Option<Vec<String>>,
+    mirrors: Option<Vec<String>>,
     state: State<'_, DownloadState>,
-) -> Result<String, String> {
+) -> Result<String, NetworkError> {
+    // Fail fast with a clear offline error instead of deep in the download task
+    manager::check_connectivity(&state.client(), &url).await?;
+
     manager::start_download(
         &app,
         &state,
-        &model_id,
-        &url,
-        &tokenizer_url,
-        expected_hash.as_deref(),
+        DownloadRequest {
+            model_id,
+            url,
+            tokenizer_url,
+            expected_hash,
+            tokenizer_hash,
+            resolved_url: None,
+            headers: headers.into_iter().flatten().collect(),
+            shard_hashes: shard_hashes.unwrap_or_default(),
+            mirrors: mirrors.unwrap_or_default(),
+        },
     )
     .await
+    .map_err(NetworkError::from)
+}
+
+/// Fetch the expected SHA-256 from a checksum sidecar URL
+///
+/// Many hosts publish `model.gguf.sha256` next to the weights; the returned
+/// digest can be passed straight to `start_download` as `expected_hash`.
+///
+/// # Arguments
+/// * `url` - URL of the `.sha256` file
+#[tauri::command]
+pub async fn fetch_expected_hash(
+    url: String,
+    state: State<'_, DownloadState>,
+) -> Result<String, String> {
+    manager::fetch_expected_hash(&state.client(), &url).await
+}
+
+/// Fetch a small text file (e.g. `config.json`, a model card) into memory
+///
+/// Nothing is written to disk. Files larger than 10MB are rejected.
+///
+/// # Arguments
+/// * `url` - URL of the file
+/// * `auth_token` - Optional bearer token for gated repositories
+#[tauri::command]
+pub async fn fetch_text(
+    url: String,
+    auth_token: Option<String>,
+    state: State<'_, DownloadState>,
+) -> Result<String, String> {
+    manager::fetch_text(&state.client(), &url, auth_token.as_deref()).await
 }
 
 /// Pause an active download
@@ -73,6 +140,9 @@ pub async fn pause_download(
 /// Resume a paused download
 ///
 /// Continues from where the download left off using HTTP Range headers.
+/// A download recovered after a restart that was started with credential
+/// headers (`Authorization`, ...) fails here, since those aren't saved; use
+/// `resume_from_disk` with the headers instead.
 ///
 /// # Arguments
 /// * `download_id` - The download ID to resume
@@ -85,6 +155,128 @@ pub async fn resume_download(
     manager::resume_download(&app, &state, &download_id).await
 }
 
+/// Resume a partial download left on disk without an in-memory entry
+///
+/// Used to recover orphaned `.part` files, e.g. after an app restart when
+/// `resume_download` no longer knows the download ID, and to resume a
+/// recovered download whose credential headers must be supplied again.
+///
+/// # Arguments
+/// * `model_id` - The model identifier whose `.part` file should be resumed
+/// * `url` - The download URL for the GGUF model
+/// * `tokenizer_url` - The download URL for the tokenizer.json
+/// * `expected_hash` - Optional SHA-256 hash for verification
+/// * `tokenizer_hash` - Optional SHA-256 of tokenizer.json
+/// * `headers` - Optional extra HTTP headers, as for `start_download`
+/// * `shard_hashes` - Per-shard hashes of a split model, as for `start_download`
+/// * `mirrors` - Fallback URLs, as for `start_download`
+///
+/// # Returns
+/// * `download_id` - Unique ID for tracking the resumed download
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn resume_from_disk(
+    app: AppHandle,
+    model_id: String,
+    url: String,
+    tokenizer_url: String,
+    expected_hash: Option<String>,
+    tokenizer_hash: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    shard_hashes: Option<Vec<String>>,
+    mirrors: Option<Vec<String>>,
+    state: State<'_, DownloadState>,
+) -> Result<String, String> {
+    manager::resume_from_disk(
+        &app,
+        &state,
+        DownloadRequest {
+            model_id,
+            url,
+            tokenizer_url,
+            expected_hash,
+            tokenizer_hash,
+            resolved_url: None,
+            headers: headers.into_iter().flatten().collect(),
+            shard_hashes: shard_hashes.unwrap_or_default(),
+            mirrors: mirrors.unwrap_or_default(),
+        },
+    )
+    .await
+}
+
+/// Re-download a model whose last download was quarantined as corrupted
+///
+/// Uses the URLs, hashes and headers saved with the quarantined file, then
+/// removes it. Fails if no quarantine metadata exists for the model.
+///
+/// # Arguments
+/// * `model_id` - The model identifier
+/// * `headers` - Optional extra HTTP headers, added to the saved ones;
+///   required when the original download sent credentials (`Authorization`,
+///   ...), since those are never saved
+///
+/// # Returns
+/// * `download_id` - Unique ID for tracking the new download
+#[tauri::command]
+pub async fn redownload_corrupted(
+    app: AppHandle,
+    model_id: String,
+    headers: Option<HashMap<String, String>>,
+    state: State<'_, DownloadState>,
+) -> Result<String, String> {
+    let headers = headers.into_iter().flatten().collect();
+    manager::redownload_corrupted(&app, &state, &model_id, headers).await
+}
+
+/// Retry a specific quarantined file with the source it was saved with
+///
+/// Deletes the quarantined file and its metadata once the new download has
+/// started. Fails if no metadata was saved, in which case the model must be
+/// re-downloaded manually.
+///
+/// # Arguments
+/// * `file_id` - ID from `list_quarantined_files`
+/// * `headers` - Optional extra HTTP headers, as for `redownload_corrupted`
+///
+/// # Returns
+/// * `download_id` - Unique ID for tracking the new download
+#[tauri::command]
+pub async fn requarantine_retry(
+    app: AppHandle,
+    file_id: String,
+    headers: Option<HashMap<String, String>>,
+    state: State<'_, DownloadState>,
+) -> Result<String, String> {
+    let headers = headers.into_iter().flatten().collect();
+    manager::requarantine_retry(&app, &state, &file_id, headers).await
+}
+
+/// Pause every active download
+///
+/// Partial files are preserved so each download can be resumed later.
+///
+/// # Returns
+/// * Number of downloads that were paused
+#[tauri::command]
+pub async fn pause_all_downloads(state: State<'_, DownloadState>) -> Result<usize, String> {
+    Ok(manager::pause_all_downloads(&state).await)
+}
+
+/// Resume every paused download
+///
+/// Each download continues from its .part file with its original URLs and hash.
+///
+/// # Returns
+/// * Number of downloads that were resumed
+#[tauri::command]
+pub async fn resume_all_downloads(
+    app: AppHandle,
+    state: State<'_, DownloadState>,
+) -> Result<usize, String> {
+    Ok(manager::resume_all_downloads(&app, &state).await)
+}
+
 /// Cancel a download and clean up partial files
 ///
 /// # Arguments
@@ -100,6 +292,9 @@ pub async fn cancel_download(
 
 /// Get current progress for a download
 ///
+/// Returns the same live bytes/speed/ETA the `download_progress` events carry,
+/// so polling and subscribing agree.
+///
 /// # Arguments
 /// * `download_id` - The download ID to query
 ///
@@ -114,34 +309,129 @@ pub async fn get_download_progress(
     Ok(state
         .get_download(&download_id)
         .await
-        .map(|d| d.to_progress_event(0, 0)))
+        .map(|d| d.live_progress_event()))
+}
+
+/// Set how often downloads emit `download_progress` events
+///
+/// Low-power UIs can use a longer interval. Default is 100ms.
+///
+/// # Arguments
+/// * `interval_ms` - Minimum milliseconds between progress events
+#[tauri::command]
+pub fn set_progress_interval_ms(
+    interval_ms: u64,
+    state: State<'_, DownloadState>,
+) -> Result<(), String> {
+    state.set_progress_interval_ms(interval_ms)
+}
+
+/// Cap the total size of quarantined (corrupted) downloads
+///
+/// When a new file is quarantined, the oldest entries are evicted until it
+/// fits. Default is 10GB.
+///
+/// # Arguments
+/// * `limit_mb` - Maximum quarantine size in MB, or `None` for unlimited
+#[tauri::command]
+pub fn set_quarantine_size_limit(limit_mb: Option<u64>, state: State<'_, DownloadState>) {
+    state.set_quarantine_size_limit(limit_mb);
+}
+
+/// Set how many times a dropped download is retried before it fails
+///
+/// Retries wait 1s, 2s, 4s, ... (capped at 30s) and resume from the bytes
+/// already on disk. Default is 5.
+///
+/// # Arguments
+/// * `max_retries` - Retries after a network error (0 fails immediately)
+#[tauri::command]
+pub fn set_download_max_retries(max_retries: u32, state: State<'_, DownloadState>) {
+    state.set_max_retries(max_retries);
+}
+
+/// Limit how many downloads transfer at once
+///
+/// Downloads over the limit report `queued` and start automatically as
+/// slots free. Default is 3.
+///
+/// # Arguments
+/// * `limit` - Maximum concurrent downloads (at least 1)
+#[tauri::command]
+#[allow(clippy::unused_async)] // Async so slot retirement spawns on the Tauri runtime
+pub async fn set_max_concurrent_downloads(
+    limit: usize,
+    state: State<'_, DownloadState>,
+) -> Result<(), String> {
+    state.set_max_concurrent_downloads(limit)
+}
+
+/// Configure the proxy used for model downloads
+///
+/// Supports `http://`, `https://` and `socks5://` URLs, including credentials
+/// in the URL. Pass `None` to connect directly (or through `HTTPS_PROXY` and
+/// friends, if set). The settings are persisted.
+///
+/// # Arguments
+/// * `url` - Proxy URL, or `None` to disable the proxy
+/// * `no_proxy` - Comma-separated hosts, domains or CIDRs that bypass the
+///   proxy; `None` falls back to the `NO_PROXY` environment variable
+#[tauri::command]
+pub fn set_proxy(
+    url: Option<String>,
+    no_proxy: Option<String>,
+    state: State<'_, DownloadState>,
+) -> Result<(), String> {
+    state.set_proxy(url, no_proxy)
 }
 
 /// Check if there's enough storage space for a download (AC5)
 ///
+/// Measures the disk holding the models directory, not all disks combined.
+/// If the models directory can't be matched to a mount point (or sysinfo
+/// lists no disks), falls back to the total free space across all disks and
+/// reports `disk_resolved: false`.
+///
 /// # Arguments
 /// * `required_mb` - Required space in megabytes
 ///
 /// # Returns
 /// * `StorageCheckResult` - Contains has_space, available_mb, required_mb, shortfall_mb
 #[tauri::command]
-pub fn check_storage_space(required_mb: u64) -> Result<StorageCheckResult, String> {
+pub fn check_storage_space(
+    required_mb: u64,
+    state: State<'_, DownloadState>,
+) -> Result<StorageCheckResult, String> {
     let disks = Disks::new_with_refreshed_list();
-    let available_bytes: u64 = disks.iter().map(sysinfo::Disk::available_space).sum();
-
-    let available_mb = available_bytes / 1024 / 1024;
-    let has_space = available_mb >= required_mb;
-
-    Ok(StorageCheckResult {
-        has_space,
-        available_mb,
+    let free_space: Vec<(&Path, u64)> = disks
+        .iter()
+        .map(|disk| (disk.mount_point(), disk.available_space()))
+        .collect();
+    Ok(storage_check_for_path(
+        &free_space,
+        state.models_dir(),
         required_mb,
-        shortfall_mb: if has_space {
-            0
-        } else {
-            required_mb.saturating_sub(available_mb)
-        },
-    })
+    ))
+}
+
+/// Compare `required_mb` against free space on the disk containing `path`
+///
+/// `free_space` lists each disk's mount point and available bytes; with no
+/// matching mount point, the sum over all of them is used instead.
+fn storage_check_for_path(
+    free_space: &[(&Path, u64)],
+    path: &Path,
+    required_mb: u64,
+) -> StorageCheckResult {
+    let models_disk = mount_for_path(path, free_space.iter().map(|(mount, _)| *mount))
+        .and_then(|mount| free_space.iter().find(|(m, _)| *m == mount))
+        .map(|(_, bytes)| *bytes);
+    let available_bytes =
+        models_disk.unwrap_or_else(|| free_space.iter().map(|(_, bytes)| bytes).sum());
+    StorageCheckResult {
+        disk_resolved: models_disk.is_some(),
+        ..StorageCheckResult::new(available_bytes / 1024 / 1024, required_mb)
+    }
 }
 
 /// Get model file path for a downloaded model
@@ -150,23 +440,95 @@ pub fn check_storage_space(required_mb: u64) -> Result<StorageCheckResult, Strin
 /// * `model_id` - The model identifier
 ///
 /// # Returns
-/// * `Some(path)` - Path to the model file if it exists
-/// * `None` - Model not downloaded
+/// * `Some(path)` - Path to the model file if the download was finalized
+/// * `None` - Model not downloaded (or not fully finalized)
 #[tauri::command]
 pub async fn get_model_path(
     model_id: String,
     state: State<'_, DownloadState>,
 ) -> Result<Option<String>, String> {
-    // Model is stored in: models/{model_id}/model.gguf
-    let file_path = state.models_dir().join(&model_id).join("model.gguf");
+    // Weights filename comes from models/{model_id}/model.json (default model.gguf)
+    let model_dir = state.models_dir().join(&model_id);
+    let file_path = weights_path(&model_dir);
 
-    if file_path.exists() {
+    // Without the .complete marker the download may have been interrupted
+    if file_path.exists() && is_complete(&model_dir) {
         Ok(Some(file_path.to_string_lossy().to_string()))
     } else {
         Ok(None)
     }
 }
 
+/// List every model in the models directory, finished or not
+///
+/// Backs the "Manage Models" screen; `modified_ms` lets it sort by recency.
+///
+/// # Returns
+/// * One entry per model directory, sorted by model id
+#[tauri::command]
+pub async fn list_downloaded_models(
+    state: State<'_, DownloadState>,
+) -> Result<Vec<InstalledModel>, String> {
+    Ok(manager::list_installed_models(state.models_dir()))
+}
+
+/// Report how much disk space downloaded models use
+///
+/// Partial downloads are broken out of the total and the quarantine is
+/// reported on its own, so the UI can show how much space is reclaimable.
+#[tauri::command]
+pub async fn get_models_storage_usage(
+    state: State<'_, DownloadState>,
+) -> Result<StorageUsage, String> {
+    let models_dir = state.models_dir().to_path_buf();
+    let quarantine_dir = state.quarantine_dir();
+
+    // Walking many files must not block the async runtime
+    tokio::task::spawn_blocking(move || manager::storage_usage(&models_dir, &quarantine_dir))
+        .await
+        .map_err(|e| format!("Storage usage scan failed: {e}"))
+}
+
+/// Find, and with `confirm` remove, leftovers of cancelled or crashed downloads
+///
+/// Targets model directories that are empty or hold only `.part` files (and a
+/// stale manifest) with no download tracked for them. Without `confirm` this
+/// is a dry run. Quarantined files are never touched. Emits `models:changed`
+/// with action `deleted` for each directory removed.
+///
+/// # Arguments
+/// * `confirm` - Actually delete; otherwise only report what would go
+///
+/// # Returns
+/// * The orphaned directories, removed or not
+#[tauri::command]
+pub async fn cleanup_orphaned_downloads(
+    app: AppHandle,
+    confirm: Option<bool>,
+    state: State<'_, DownloadState>,
+) -> Result<Vec<OrphanedDownload>, String> {
+    let confirm = confirm.unwrap_or(false);
+    let tracked: Vec<String> = state
+        .get_all_downloads()
+        .await
+        .into_iter()
+        .map(|download| download.model_id)
+        .collect();
+
+    let orphans = manager::cleanup_orphaned_downloads(
+        state.models_dir(),
+        &state.quarantine_dir(),
+        &tracked,
+        confirm,
+    )?;
+    if confirm {
+        for orphan in &orphans {
+            manager::emit_models_changed(&app, &orphan.model_id, ModelChange::Deleted);
+        }
+    }
+    Ok(orphans)
+}
+
 /// Check if a partial download exists for a model
 ///
 /// # Arguments
@@ -180,8 +542,18 @@ pub async fn get_partial_download_size(
     model_id: String,
     state: State<'_, DownloadState>,
 ) -> Result<Option<u64>, String> {
+    let model_dir = state.models_dir().join(&model_id);
+
+    // A split model's progress is spread over its shards
+    if let Some(manifest) = DownloadManifest::load(&model_dir) {
+        let shards = shards::shard_names(&manifest.url);
+        if !shards.is_empty() {
+            return Ok(shards::bytes_on_disk(&model_dir, &shards));
+        }
+    }
+
     // Partial download is stored in: models/{model_id}/model.gguf.part
-    let part_path = state.models_dir().join(&model_id).join("model.gguf.part");
+    let part_path = model_dir.join("model.gguf.part");
 
     if part_path.exists() {
         let metadata = std::fs::metadata(&part_path)
@@ -192,20 +564,131 @@ pub async fn get_partial_download_size(
     }
 }
 
+/// Discard a model's partial download to force a clean re-download
+///
+/// Never touches a completed `model.gguf`. Fails while the model is still
+/// downloading.
+///
+/// # Arguments
+/// * `model_id` - The model identifier
+///
+/// # Returns
+/// * `true` if a partial file was deleted, `false` if there was none
+#[tauri::command]
+pub async fn delete_partial_download(
+    model_id: String,
+    state: State<'_, DownloadState>,
+) -> Result<bool, String> {
+    manager::delete_partial_download(&state, &model_id).await
+}
+
+/// Check whether a model is complete and loadable
+///
+/// Confirms `model.gguf` and `tokenizer.json` exist, no `.part` remains, and
+/// re-verifies the checksum if one was stored when the model was verified.
+///
+/// # Arguments
+/// * `model_id` - The model identifier
+#[tauri::command]
+pub async fn is_model_ready(
+    model_id: String,
+    state: State<'_, DownloadState>,
+) -> Result<ModelReadiness, String> {
+    let models_dir = state.models_dir().to_path_buf();
+
+    // Re-hashing a multi-GB model must not block the async runtime
+    tokio::task::spawn_blocking(move || manager::check_model_readiness(&models_dir, &model_id))
+        .await
+        .map_err(|e| format!("Readiness check failed: {e}"))
+}
+
+/// Import a manually downloaded model so it can be loaded like a download
+///
+/// Files are hard-linked (or copied) into `models/{model_id}/`, keeping the
+/// weights filename (recorded in `model.json`) next to `tokenizer.json`.
+/// Emits `models:changed` with action `imported`.
+///
+/// # Arguments
+/// * `model_id` - Identifier to register the model under (must not exist yet)
+/// * `gguf_path` - Path to the GGUF weights
+/// * `tokenizer_path` - Path to the tokenizer.json
+/// * `expected_hash` - Optional SHA-256 hash to verify the weights against
+///
+/// # Returns
+/// * Path of the new model directory
+#[tauri::command]
+pub async fn import_local_model(
+    app: AppHandle,
+    model_id: String,
+    gguf_path: String,
+    tokenizer_path: String,
+    expected_hash: Option<String>,
+    state: State<'_, DownloadState>,
+) -> Result<String, String> {
+    let models_dir = state.models_dir().to_path_buf();
+
+    // Copying multi-GB files must not block the async runtime
+    let imported_id = model_id.clone();
+    let model_dir = tokio::task::spawn_blocking(move || {
+        manager::import_local_model(
+            &models_dir,
+            &imported_id,
+            Path::new(&gguf_path),
+            Path::new(&tokenizer_path),
+            expected_hash.as_deref(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {e}"))??;
+
+    manager::emit_models_changed(&app, &model_id, ModelChange::Imported);
+    Ok(model_dir.to_string_lossy().to_string())
+}
+
 /// Delete a downloaded model and its tokenizer
 ///
 /// Removes the entire model directory: models/{model_id}/
+/// and emits `models:changed` with action `deleted`.
+/// Refused while the model is downloading, or while it is loaded for
+/// inference unless `force` is set (which unloads it first).
 ///
 /// # Arguments
 /// * `model_id` - The model identifier
+/// * `force` - Unload the model first if it is currently loaded
 #[tauri::command]
-pub async fn delete_model(model_id: String, state: State<'_, DownloadState>) -> Result<(), String> {
+pub async fn delete_model(
+    app: AppHandle,
+    model_id: String,
+    force: Option<bool>,
+    state: State<'_, DownloadState>,
+    inference_state: State<'_, Arc<InferenceState>>,
+) -> Result<(), String> {
+    if state.has_active_download(&model_id).await {
+        return Err(format!(
+            "Download for {model_id} is in progress; pause or cancel it first"
+        ));
+    }
+
+    // The loaded model reads from these files; never pull them out from under it
+    if inference_state.is_model_loaded(&model_id).await {
+        if !force.unwrap_or(false) {
+            return Err(format!(
+                "Model {model_id} is currently loaded; unload it first or pass force"
+            ));
+        }
+        inference_state.unload(&model_id).await;
+        log::info!("Unloaded {model_id} before deleting it");
+    }
+
     let model_dir = state.models_dir().join(&model_id);
 
-    // Delete the entire model directory
+    // Delete the entire model directory, un-marking it first so a partial
+    // delete is never reported as a complete model
     if model_dir.exists() {
+        clear_complete(&model_dir)?;
         std::fs::remove_dir_all(&model_dir)
             .map_err(|e| format!("Failed to delete model directory: {e}"))?;
+        manager::emit_models_changed(&app, &model_id, ModelChange::Deleted);
     }
 
     Ok(())
@@ -216,22 +699,72 @@ pub async fn delete_model(model_id: String, state: State<'_, DownloadState>) ->
 mod tests {
     use super::*;
 
+    const MB: u64 = 1024 * 1024;
+
+    /// Temp dirs as mount points; canonical, as the checked path will be
+    fn mount_point(dir: &tempfile::TempDir) -> std::path::PathBuf {
+        dunce::canonicalize(dir.path()).unwrap()
+    }
+
     #[test]
     fn test_check_storage_space() {
-        // Should work on any system
-        let result = check_storage_space(1).unwrap();
+        let mounts = tempfile::TempDir::new().unwrap();
+        let models_dir = mounts.path().join("models");
+        std::fs::create_dir_all(&models_dir).unwrap();
+        let mount = mount_point(&mounts);
+        let free_space = [(Path::new("/"), 100 * MB), (mount.as_path(), 4_000 * MB)];
 
-        // Most systems have at least 1MB free
-        assert!(result.available_mb > 0);
+        let result = storage_check_for_path(&free_space, &models_dir, 1);
+        assert!(result.has_space);
+        assert_eq!(result.available_mb, 4_000);
         assert_eq!(result.required_mb, 1);
     }
 
     #[test]
     fn test_storage_check_with_large_requirement() {
-        // Request an impossibly large amount
-        let result = check_storage_space(1_000_000_000).unwrap(); // 1 petabyte
+        let dir = tempfile::TempDir::new().unwrap();
+        let mount = mount_point(&dir);
+        let free_space = [(mount.as_path(), 4_000 * MB)];
+
+        let result = storage_check_for_path(&free_space, dir.path(), 1_000_000_000); // 1 petabyte
+        assert!(!result.has_space);
+        assert_eq!(result.shortfall_mb, 1_000_000_000 - 4_000);
+    }
+
+    #[test]
+    fn test_storage_check_reports_disk_of_models_path() {
+        let models_disk = tempfile::TempDir::new().unwrap();
+        let other_disk = tempfile::TempDir::new().unwrap();
+        let (models_mount, other_mount) = (mount_point(&models_disk), mount_point(&other_disk));
+        let free_space = [
+            (models_mount.as_path(), 10 * MB),
+            (other_mount.as_path(), 500 * MB),
+        ];
+
+        // Only the matching disk counts, never the sum of all disks
+        let result = storage_check_for_path(&free_space, models_disk.path(), 20);
+        assert_eq!(result.available_mb, 10);
+        assert!(!result.has_space);
+        assert!(result.disk_resolved);
+    }
+
+    #[test]
+    fn test_storage_check_falls_back_to_all_disks() {
+        let models_dir = tempfile::TempDir::new().unwrap();
+        let disk_a = tempfile::TempDir::new().unwrap();
+        let disk_b = tempfile::TempDir::new().unwrap();
+        let (mount_a, mount_b) = (mount_point(&disk_a), mount_point(&disk_b));
+        let free_space = [(mount_a.as_path(), 10 * MB), (mount_b.as_path(), 30 * MB)];
+
+        // No mount point holds the models directory
+        let result = storage_check_for_path(&free_space, models_dir.path(), 35);
+        assert_eq!(result.available_mb, 40);
+        assert!(result.has_space);
+        assert!(!result.disk_resolved);
 
+        // sysinfo may list no disks at all; that's a shortfall, not an error
+        let result = storage_check_for_path(&[], models_dir.path(), 1);
+        assert_eq!(result.available_mb, 0);
         assert!(!result.has_space);
-        assert!(result.shortfall_mb > 0);
     }
 }
@@ -13,44 +13,175 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::cast_sign_loss)]
 
-use super::state::{Download, DownloadProgressEvent, DownloadState, DownloadStatus};
-use crate::verification;
+use super::metadata::{
+    clear_complete, is_complete, is_plain_file_name, mark_complete, stored_hash, weights_path,
+    weights_paths, DownloadManifest, ModelMetadata, DEFAULT_WEIGHTS_FILE, DOWNLOAD_MANIFEST_FILE,
+    HASH_FILE,
+};
+use super::shards;
+use super::state::{
+    Download, DownloadFinishedEvent, DownloadProgressEvent, DownloadRequest, DownloadState,
+    DownloadStatus, DownloadTuning, InstalledModel, ModelChange, ModelReadiness,
+    ModelsChangedEvent, NetworkError, OrphanedDownload, QuarantineRecord, SpeedSample,
+    SpeedTracker, StorageUsage, StoredHeaders, VerificationCompleteEvent,
+    VerificationProgressEvent, INSTANT_SPEED_WINDOW, SENSITIVE_HEADERS, TOKENIZER_CORRUPTED,
+};
+use crate::verification::{self, HashAlgorithm};
 use futures_util::StreamExt;
 use log::{error, info, warn};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use sha2::{Digest, Sha256};
 use std::fs::OpenOptions;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::watch;
 use uuid::Uuid;
 
-/// Progress update interval (100ms per ADR-DOWNLOAD-003)
-const PROGRESS_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+/// Error sentinel for a download that lost connectivity (like "cancelled")
+const OFFLINE: &str = "offline";
+
+/// Timeout for the connectivity pre-check before a download starts
+const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Headers the download manager controls itself; callers can't override them
+const RESERVED_HEADERS: &[&str] = &[
+    "connection",
+    "content-length",
+    "expect",
+    "host",
+    "keep-alive",
+    "proxy-authorization",
+    "proxy-connection",
+    "range",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
 
 /// Threshold for emitting verification progress (500MB per Task 12)
 const VERIFICATION_PROGRESS_THRESHOLD: u64 = 500 * 1024 * 1024;
 
+/// Where a running transfer reports its progress
+///
+/// The app in production; tests substitute a recorder so transfers can run
+/// against a local server.
+trait TransferEvents: Sync {
+    /// Send a `download_progress` event
+    fn emit_progress(&self, event: DownloadProgressEvent);
+
+    /// Download state kept in step with the events
+    fn download_state(&self) -> &DownloadState;
+}
+
+impl TransferEvents for AppHandle {
+    fn emit_progress(&self, event: DownloadProgressEvent) {
+        let _ = self.emit("download_progress", event);
+    }
+
+    fn download_state(&self) -> &DownloadState {
+        self.state::<DownloadState>().inner()
+    }
+}
+
+/// Emit a `verification_progress` event for a download being verified
+///
+/// Also re-emits the `verifying` status on `download_progress` with the
+/// percentage in `phase_percent`, so one listener can drive the progress bar.
+fn emit_verification_progress(
+    app: &AppHandle,
+    download_id: &str,
+    model_id: &str,
+    bytes_processed: u64,
+    total_bytes: u64,
+) {
+    let percent = if total_bytes > 0 {
+        (bytes_processed as f64 / total_bytes as f64 * 100.0) as u8
+    } else {
+        100
+    };
+    let _ = app.emit(
+        "verification_progress",
+        VerificationProgressEvent {
+            download_id: download_id.to_string(),
+            model_id: model_id.to_string(),
+            bytes_processed,
+            total_bytes,
+            percent,
+        },
+    );
+    let _ = app.emit(
+        "download_progress",
+        DownloadProgressEvent {
+            download_id: download_id.to_string(),
+            model_id: model_id.to_string(),
+            status: "verifying".to_string(),
+            bytes_downloaded: total_bytes,
+            total_bytes,
+            speed_bps: 0,
+            instant_speed_bps: 0,
+            average_speed_bps: 0,
+            eta_seconds: 0,
+            phase_percent: Some(percent),
+            retry_attempt: None,
+            stalled: false,
+        },
+    );
+}
+
 /// Verify file integrity with progress events for large files (Task 12)
-/// Emits verification:progress events for files larger than 500MB
+/// Emits verification_progress events for files larger than 500MB
+/// Stops with a `cancelled` error once the download's cancel token fires.
+#[allow(clippy::too_many_arguments)]
 fn verify_with_progress(
     app: &AppHandle,
-    file_path: &PathBuf,
+    file_path: &Path,
     expected_hash: &str,
     download_id: &str,
     model_id: &str,
     file_size: u64,
+    buffer_size: usize,
+    cancel_rx: &watch::Receiver<bool>,
 ) -> Result<verification::VerificationResult, verification::VerificationError> {
-    use sha2::{Digest, Sha256};
-    use std::io::Read;
-
     // For small files, use simple verification (no progress needed)
     if file_size < VERIFICATION_PROGRESS_THRESHOLD {
-        return verification::verify_integrity(file_path, expected_hash);
+        return verification::verify_integrity(file_path, expected_hash, HashAlgorithm::Sha256);
     }
 
     // For large files, use chunked reading with progress events
+    let (computed_hash, bytes_processed) =
+        hash_with_progress(file_path, buffer_size, cancel_rx, |bytes_processed| {
+            emit_verification_progress(app, download_id, model_id, bytes_processed, file_size);
+        })?;
+
+    emit_verification_progress(app, download_id, model_id, bytes_processed, file_size);
+
+    let expected_lower = expected_hash.to_lowercase();
+    let verified = computed_hash == expected_lower;
+
+    Ok(verification::VerificationResult {
+        verified,
+        computed_hash,
+        expected_hash: expected_lower,
+        file_size,
+        algorithm: HashAlgorithm::Sha256,
+    })
+}
+
+/// SHA-256 a file in `buffer_size` chunks, reporting progress every 500ms
+///
+/// Checks `cancel_rx` before each chunk. Returns the hex digest and bytes read.
+fn hash_with_progress(
+    file_path: &Path,
+    buffer_size: usize,
+    cancel_rx: &watch::Receiver<bool>,
+    mut on_progress: impl FnMut(u64),
+) -> Result<(String, u64), verification::VerificationError> {
+    use std::io::Read;
+
     let file = std::fs::File::open(file_path).map_err(|e| {
         if e.kind() == std::io::ErrorKind::NotFound {
             verification::VerificationError::file_not_found(file_path)
@@ -61,11 +192,16 @@ fn verify_with_progress(
 
     let mut reader = std::io::BufReader::new(file);
     let mut hasher = Sha256::new();
-    let mut buffer = vec![0u8; 8 * 1024 * 1024]; // 8MB chunks
+    let mut buffer = vec![0u8; buffer_size];
     let mut bytes_processed: u64 = 0;
     let mut last_progress_emit = std::time::Instant::now();
 
     loop {
+        // Pause/cancel must not wait for a multi-GB hash to finish
+        if *cancel_rx.borrow() {
+            return Err(verification::VerificationError::cancelled(file_path));
+        }
+
         let bytes_read = reader
             .read(&mut buffer)
             .map_err(|e| verification::VerificationError::io_error(file_path, &e))?;
@@ -79,38 +215,23 @@ fn verify_with_progress(
 
         // Emit progress every 500ms
         if last_progress_emit.elapsed() >= Duration::from_millis(500) {
-            let percent = (bytes_processed as f64 / file_size as f64 * 100.0) as u64;
-            let _ = app.emit(
-                "verification_progress",
-                serde_json::json!({
-                    "download_id": download_id,
-                    "model_id": model_id,
-                    "bytes_processed": bytes_processed,
-                    "total_bytes": file_size,
-                    "percent": percent,
-                }),
-            );
+            on_progress(bytes_processed);
             last_progress_emit = std::time::Instant::now();
         }
     }
 
-    let computed_hash = format!("{:x}", hasher.finalize());
-    let expected_lower = expected_hash.to_lowercase();
-    let verified = computed_hash == expected_lower;
-
-    Ok(verification::VerificationResult {
-        verified,
-        computed_hash,
-        expected_hash: expected_lower,
-        file_size,
-    })
+    Ok((format!("{:x}", hasher.finalize()), bytes_processed))
 }
 
 /// Start a new download for model and tokenizer
 ///
 /// Returns the download_id for tracking.
 /// Downloads are stored as .part files until complete.
+/// Beyond `max_concurrent_downloads`, the download is `queued` and starts
+/// automatically once a running one finishes.
 /// If expected_hash is provided, verification runs before finalizing (Story 2.5).
+/// A URL naming the first shard of a split model (`*-00001-of-00003.gguf`)
+/// downloads every shard, each verified against its entry in `shard_hashes`.
 ///
 /// File structure:
 /// ```
@@ -123,13 +244,20 @@ fn verify_with_progress(
 pub async fn start_download(
     app: &AppHandle,
     state: &DownloadState,
-    model_id: &str,
-    url: &str,
-    tokenizer_url: &str,
-    expected_hash: Option<&str>,
+    request: DownloadRequest,
 ) -> Result<String, String> {
+    let headers = build_header_map(&request.headers)?;
     let download_id = Uuid::new_v4().to_string();
     let models_dir = state.models_dir();
+    let model_id = request.model_id.as_str();
+    if !headers.is_empty() {
+        // Names only: tokens can travel in headers not marked sensitive
+        let names: Vec<&str> = headers.keys().map(HeaderName::as_str).collect();
+        info!(
+            "Sending custom headers for {model_id}: {}",
+            names.join(", ")
+        );
+    }
 
     // Create model-specific directory
     let model_dir = models_dir.join(model_id);
@@ -137,68 +265,128 @@ pub async fn start_download(
         .map_err(|e| format!("Failed to create model directory: {e}"))?;
 
     // Download tokenizer first (small file, quick)
-    download_tokenizer(state.client(), tokenizer_url, &model_dir).await?;
-
-    // Determine file paths for model (inside model directory)
-    let file_path = model_dir.join("model.gguf");
-    let part_path = model_dir.join("model.gguf.part");
-
-    // Check for existing partial download
-    let mut bytes_downloaded = 0u64;
-    if part_path.exists() {
-        bytes_downloaded = std::fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
-        info!("Resuming download for {model_id} from {bytes_downloaded} bytes");
-    }
-
-    // Get total size with HEAD request
-    let total_bytes = get_content_length(state.client(), url).await?;
+    download_tokenizer(
+        &state.client(),
+        &request.tokenizer_url,
+        &headers,
+        &model_dir,
+        request.tokenizer_hash.as_deref(),
+    )
+    .await?;
+
+    let mut on_switch = || {
+        let _ = app.emit(
+            "download_progress",
+            mirror_switch_event(&download_id, model_id, 0, 0),
+        );
+    };
+    let parts = plan_parts(
+        &state.client(),
+        &request,
+        &headers,
+        &model_dir,
+        &mut on_switch,
+    )
+    .await?;
+    let first = parts.first().ok_or("Download has no files")?;
+
+    // Progress is reported across every shard, as one combined bar
+    let bytes_downloaded: u64 = parts.iter().map(|part| part.bytes_downloaded).sum();
+    let total_bytes: u64 = parts.iter().map(|part| part.total_bytes).sum();
 
     // Create cancel token for abort support
     let (cancel_tx, cancel_rx) = watch::channel(false);
 
+    // Over the concurrency limit, wait as "queued" until a slot frees up
+    let slot = state.try_acquire_slot();
+    let status = if slot.is_some() {
+        DownloadStatus::Downloading
+    } else {
+        info!("Download limit reached, queueing {model_id}");
+        DownloadStatus::Queued
+    };
+
     let download = Download {
         id: download_id.clone(),
         model_id: model_id.to_string(),
-        url: url.to_string(),
-        tokenizer_url: tokenizer_url.to_string(),
-        file_path: file_path.clone(),
-        part_path: part_path.clone(),
+        url: request.url.clone(),
+        resolved_url: first.url.clone(),
+        tokenizer_url: request.tokenizer_url.clone(),
+        file_path: first.final_path.clone(),
+        part_path: first.part_path.clone(),
         bytes_downloaded,
         total_bytes,
-        status: DownloadStatus::Downloading,
+        speed: SpeedSample::default(),
+        eta_seconds: 0,
+        stalled: false,
+        status,
         cancel_token: Arc::new(cancel_tx),
-        expected_hash: expected_hash.map(std::string::ToString::to_string),
+        expected_hash: request.expected_hash.clone(),
+        tokenizer_hash: request.tokenizer_hash.clone(),
+        headers: request.headers.clone(),
+        missing_credentials: Vec::new(),
+        shards: shards::shard_names(&request.url),
+        shard_hashes: request.shard_hashes.clone(),
+        mirrors: request.mirrors.clone(),
+        mirror_url: first.mirror.clone(),
     };
+    if first.mirror != request.url {
+        info!(
+            "Downloading {model_id} from mirror {}",
+            url_host(&first.mirror)
+        );
+    }
+
+    // Lets the download be recovered as paused if the app exits mid-transfer
+    download.save_manifest();
+
+    // Let subscribers show "downloading" or "queued" right away, before the first tick
+    let _ = app.emit("download_progress", download.live_progress_event());
 
     state.add_download(download).await;
 
     // Clone values for async task
     let app_handle = app.clone();
-    let client = state.client().clone();
-    let url = url.to_string();
+    let client = state.client();
     let model_id = model_id.to_string();
     let id = download_id.clone();
-    let expected_hash = expected_hash.map(std::string::ToString::to_string);
     let quarantine_dir = state.quarantine_dir();
+    let tuning = state.tuning();
 
     // Spawn download task
     tokio::spawn(async move {
+        // Held until the transfer ends, then handed to the next queued download
+        let _slot = if let Some(slot) = slot {
+            slot
+        } else {
+            let download_state = app_handle.state::<DownloadState>();
+            let Some(slot) = download_state.acquire_slot(cancel_rx.clone()).await else {
+                info!("Queued download cancelled/paused for {model_id}");
+                return;
+            };
+            let Some(download) = download_state.start_queued(&id).await else {
+                info!("Download left the queue before starting: {model_id}");
+                return;
+            };
+            info!("Download slot free, starting {model_id}");
+            let _ = app_handle.emit("download_progress", download.live_progress_event());
+            slot
+        };
+
         let result = download_file(
             &app_handle,
             &client,
-            &url,
-            &part_path,
-            &file_path,
-            bytes_downloaded,
-            total_bytes,
+            &headers,
+            &parts,
             &id,
             &model_id,
-            expected_hash.as_deref(),
             &quarantine_dir,
+            tuning,
             cancel_rx,
         )
         .await;
 
+        record_outcome(&app_handle.state::<DownloadState>(), &id, &result).await;
         if let Err(e) = result {
             // Don't emit failure for intentional cancellation (pause/cancel)
             // The frontend already handles status updates for these actions
@@ -206,17 +394,24 @@ pub async fn start_download(
                 info!("Download cancelled/paused for {model_id}");
             } else {
                 error!("Download failed for {model_id}: {e}");
-                // Emit failure event only for actual errors
+                // Emit failure event only for actual errors; "offline" is
+                // reported separately so the UI can offer a resume instead
+                let status = if e == OFFLINE { OFFLINE } else { "failed" };
                 let _ = app_handle.emit(
                     "download_progress",
                     DownloadProgressEvent {
                         download_id: id,
                         model_id,
-                        status: "failed".to_string(),
+                        status: status.to_string(),
                         bytes_downloaded,
                         total_bytes,
                         speed_bps: 0,
+                        instant_speed_bps: 0,
+                        average_speed_bps: 0,
                         eta_seconds: 0,
+                        phase_percent: None,
+                        retry_attempt: None,
+                        stalled: false,
                     },
                 );
             }
@@ -226,24 +421,299 @@ pub async fn start_download(
     Ok(download_id)
 }
 
+/// Update the stored status once a download task ends
+///
+/// A finished download no longer counts as active, so the model can be
+/// deleted. Going offline leaves it `Paused`, ready to resume. Pause and
+/// cancel have already updated (or removed) the entry themselves.
+async fn record_outcome(state: &DownloadState, download_id: &str, result: &Result<(), String>) {
+    let status = match result {
+        Ok(()) => DownloadStatus::Completed,
+        Err(e) if e == "cancelled" => return,
+        Err(e) if e == OFFLINE => DownloadStatus::Paused,
+        Err(_) => DownloadStatus::Failed,
+    };
+    state.update_status(download_id, status).await;
+}
+
+/// One file of a download: the whole model, or one shard of a split model
+struct DownloadPart {
+    /// URL after redirects, used for the GET and any resume
+    url: String,
+    /// The mirror `url` was resolved from
+    mirror: String,
+    /// Mirrors not tried yet, in order
+    fallbacks: Vec<String>,
+    part_path: PathBuf,
+    final_path: PathBuf,
+    /// 0 if the server wouldn't say
+    total_bytes: u64,
+    /// Where the .part file resumes from
+    bytes_downloaded: u64,
+    expected_hash: Option<String>,
+    /// Downloaded and verified by an earlier attempt
+    done: bool,
+}
+
+/// Probe every file of a download and work out where each one resumes
+///
+/// `on_switch` runs each time a mirror fails and the next one is tried.
+async fn plan_parts(
+    client: &reqwest::Client,
+    request: &DownloadRequest,
+    headers: &HeaderMap,
+    model_dir: &Path,
+    on_switch: &mut impl FnMut(),
+) -> Result<Vec<DownloadPart>, String> {
+    let Some(shard_urls) = shards::shard_urls(&request.url) else {
+        if !request.shard_hashes.is_empty() {
+            return Err(
+                "shard_hashes given, but the URL isn't the first shard of a split model"
+                    .to_string(),
+            );
+        }
+        return Ok(vec![
+            plan_single_file(client, request, headers, model_dir, on_switch).await?,
+        ]);
+    };
+    if request.expected_hash.is_some() {
+        return Err("A split model is verified per shard; pass shard_hashes instead".to_string());
+    }
+    if !request.shard_hashes.is_empty() && request.shard_hashes.len() != shard_urls.len() {
+        return Err(format!(
+            "Expected {} shard hashes, got {}",
+            shard_urls.len(),
+            request.shard_hashes.len()
+        ));
+    }
+
+    // Shards are only renamed into place once verified, so an unfinished
+    // directory's shards are kept; an installed model is downloaded afresh
+    let resuming = !is_complete(model_dir);
+    let mut parts = Vec::with_capacity(shard_urls.len());
+    for (i, (url, name)) in shard_urls.into_iter().enumerate() {
+        let final_path = model_dir.join(&name);
+        let part_path = shards::part_path(model_dir, &name);
+        let expected_hash = request.shard_hashes.get(i).cloned();
+        // Mirrors of a split model name their own first shard
+        let fallbacks: Vec<String> = request
+            .mirrors
+            .iter()
+            .filter_map(|mirror| Some(shards::shard_urls(mirror)?.into_iter().nth(i)?.0))
+            .collect();
+
+        if resuming && final_path.exists() {
+            let total_bytes = std::fs::metadata(&final_path).map_or(0, |m| m.len());
+            parts.push(DownloadPart {
+                mirror: url.clone(),
+                url,
+                fallbacks,
+                part_path,
+                final_path,
+                total_bytes,
+                bytes_downloaded: total_bytes,
+                expected_hash,
+                done: true,
+            });
+            continue;
+        }
+
+        let (probe, mirror, fallbacks) =
+            probe_mirrors(client, url, fallbacks, headers, on_switch).await?;
+        let bytes_downloaded = resume_offset(
+            client,
+            &probe.resolved_url,
+            headers,
+            &part_path,
+            probe.total_bytes,
+        )
+        .await;
+        parts.push(DownloadPart {
+            url: probe.resolved_url,
+            mirror,
+            fallbacks,
+            part_path,
+            final_path,
+            total_bytes: probe.total_bytes,
+            bytes_downloaded,
+            expected_hash,
+            done: false,
+        });
+    }
+    Ok(parts)
+}
+
+/// Plan a download of a single `model.gguf`
+async fn plan_single_file(
+    client: &reqwest::Client,
+    request: &DownloadRequest,
+    headers: &HeaderMap,
+    model_dir: &Path,
+    on_switch: &mut impl FnMut(),
+) -> Result<DownloadPart, String> {
+    // Get total size with HEAD request, reusing a previously resolved URL if it still works
+    let reused = match request.resolved_url.as_deref() {
+        Some(resolved) => match probe_download(client, resolved, headers).await {
+            Ok(probe) => Some((probe, request.url.clone(), request.mirrors.clone())),
+            Err(e) => {
+                warn!(
+                    "Resolved URL no longer usable ({e}), re-resolving {}",
+                    request.model_id
+                );
+                None
+            },
+        },
+        None => None,
+    };
+    let (probe, mirror, fallbacks) = match reused {
+        Some(reused) => reused,
+        None => {
+            probe_mirrors(
+                client,
+                request.url.clone(),
+                request.mirrors.clone(),
+                headers,
+                on_switch,
+            )
+            .await?
+        },
+    };
+
+    let part_path = model_dir.join("model.gguf.part");
+    let bytes_downloaded = resume_offset(
+        client,
+        &probe.resolved_url,
+        headers,
+        &part_path,
+        probe.total_bytes,
+    )
+    .await;
+
+    Ok(DownloadPart {
+        url: probe.resolved_url,
+        mirror,
+        fallbacks,
+        part_path,
+        final_path: model_dir.join(DEFAULT_WEIGHTS_FILE),
+        total_bytes: probe.total_bytes,
+        bytes_downloaded,
+        expected_hash: request.expected_hash.clone(),
+        done: false,
+    })
+}
+
+/// Probe `url`, then each of `fallbacks` in turn until one answers
+///
+/// Returns the probe, the mirror that answered, and the mirrors after it.
+async fn probe_mirrors(
+    client: &reqwest::Client,
+    url: String,
+    fallbacks: Vec<String>,
+    headers: &HeaderMap,
+    on_switch: &mut impl FnMut(),
+) -> Result<(DownloadProbe, String, Vec<String>), String> {
+    let mut remaining = fallbacks.into_iter();
+    let mut mirror = url;
+    loop {
+        let error = match probe_download(client, &mirror, headers).await {
+            Ok(probe) => return Ok((probe, mirror, remaining.collect())),
+            Err(e) => e,
+        };
+        let Some(next) = remaining.next() else {
+            return Err(error);
+        };
+        warn!(
+            "Mirror {} failed ({error}), trying {}",
+            url_host(&mirror),
+            url_host(&next)
+        );
+        on_switch();
+        mirror = next;
+    }
+}
+
+/// Host of a URL for logs, leaving out paths and signed query strings
+fn url_host(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "(invalid URL)".to_string())
+}
+
+/// Progress event announcing a move to the next mirror
+fn mirror_switch_event(
+    download_id: &str,
+    model_id: &str,
+    bytes_downloaded: u64,
+    total_bytes: u64,
+) -> DownloadProgressEvent {
+    DownloadProgressEvent {
+        download_id: download_id.to_string(),
+        model_id: model_id.to_string(),
+        status: "switching_mirror".to_string(),
+        bytes_downloaded,
+        total_bytes,
+        speed_bps: 0,
+        instant_speed_bps: 0,
+        average_speed_bps: 0,
+        eta_seconds: 0,
+        phase_percent: None,
+        retry_attempt: None,
+        stalled: false,
+    }
+}
+
+/// Offset to resume `part_path` from, 0 if there's no sound .part to continue
+async fn resume_offset(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &HeaderMap,
+    part_path: &Path,
+    total_bytes: u64,
+) -> u64 {
+    let part_len = std::fs::metadata(part_path).map_or(0, |m| m.len());
+    if part_len == 0 {
+        return 0;
+    }
+    info!(
+        "Resuming download of {} from {part_len} bytes",
+        part_path.display()
+    );
+
+    // Don't append to a .part that a crash may have left torn
+    validate_partial(client, url, headers, part_path, part_len, total_bytes).await
+}
+
 /// Download tokenizer.json to the model directory
+///
+/// With `expected_hash`, a tokenizer that doesn't match is deleted and the
+/// error starts with `TOKENIZER_CORRUPTED`. One already on disk is checked
+/// too, and fetched again if it fails.
 async fn download_tokenizer(
     client: &reqwest::Client,
     url: &str,
+    headers: &HeaderMap,
     model_dir: &std::path::Path,
+    expected_hash: Option<&str>,
 ) -> Result<(), String> {
     let tokenizer_path = model_dir.join("tokenizer.json");
 
     // Skip if already downloaded
     if tokenizer_path.exists() {
-        info!("Tokenizer already exists at {}", tokenizer_path.display());
-        return Ok(());
+        match expected_hash.map(|hash| check_tokenizer(&tokenizer_path, hash)) {
+            None | Some(Ok(())) => {
+                info!("Tokenizer already exists at {}", tokenizer_path.display());
+                return Ok(());
+            },
+            Some(Err(e)) => warn!("Existing tokenizer is unusable, downloading it again: {e}"),
+        }
     }
 
     info!("Downloading tokenizer from {url}");
 
     let response = client
         .get(url)
+        .headers(headers.clone())
         .send()
         .await
         .map_err(|e| format!("Tokenizer download failed: {e}"))?;
@@ -263,237 +733,1253 @@ async fn download_tokenizer(
     std::fs::write(&tokenizer_path, &bytes)
         .map_err(|e| format!("Failed to save tokenizer: {e}"))?;
 
+    if let Some(hash) = expected_hash {
+        if let Err(e) = check_tokenizer(&tokenizer_path, hash) {
+            if let Err(remove_error) = std::fs::remove_file(&tokenizer_path) {
+                warn!("Failed to remove corrupted tokenizer: {remove_error}");
+            }
+            return Err(e);
+        }
+    }
+
     info!("Tokenizer saved to {}", tokenizer_path.display());
     Ok(())
 }
 
-/// Get content length via HEAD request
-async fn get_content_length(client: &reqwest::Client, url: &str) -> Result<u64, String> {
-    let response = client
-        .head(url)
-        .send()
+/// Compare tokenizer.json with its expected SHA-256
+fn check_tokenizer(path: &Path, expected_hash: &str) -> Result<(), String> {
+    let computed = verification::compute_checksum(path, HashAlgorithm::Sha256)
+        .map_err(|e| format!("Failed to verify tokenizer: {}", e.message))?;
+    let expected = expected_hash.to_lowercase();
+    if computed == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "{TOKENIZER_CORRUPTED}: expected {expected}, got {computed}. Please re-download the model."
+        ))
+    }
+}
+
+/// Largest checksum sidecar we'll read (they're a single line in practice)
+const MAX_HASH_SIDECAR_BYTES: usize = 64 * 1024;
+
+/// Largest file `fetch_text` will buffer in memory
+pub const MAX_FETCH_TEXT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Download a `.sha256` sidecar and return the lowercase hex digest
+pub async fn fetch_expected_hash(client: &reqwest::Client, url: &str) -> Result<String, String> {
+    let bytes = fetch_capped(client, url, None, MAX_HASH_SIDECAR_BYTES)
         .await
-        .map_err(|e| format!("HEAD request failed: {e}"))?;
+        .map_err(|e| format!("Checksum download failed: {e}"))?;
 
-    response
-        .headers()
-        .get("content-length")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|v| v.parse().ok())
-        .ok_or_else(|| "Could not determine file size".to_string())
+    parse_sha256_sidecar(&String::from_utf8_lossy(&bytes))
+        .ok_or_else(|| "No SHA-256 digest found in checksum file".to_string())
 }
 
-/// Download file with resume support and optional integrity verification (Story 2.5)
-#[allow(clippy::too_many_arguments)]
-async fn download_file(
-    app: &AppHandle,
+/// Fetch a small text file (config, checksum, model card) without touching disk
+///
+/// Sends `auth_token` as a bearer token when given. Bodies over
+/// `MAX_FETCH_TEXT_BYTES` or not valid UTF-8 are rejected.
+pub async fn fetch_text(
     client: &reqwest::Client,
     url: &str,
-    part_path: &PathBuf,
-    final_path: &PathBuf,
-    mut bytes_downloaded: u64,
-    total_bytes: u64,
-    download_id: &str,
-    model_id: &str,
-    expected_hash: Option<&str>,
-    quarantine_dir: &std::path::Path,
-    #[allow(unused_mut)] mut cancel_rx: watch::Receiver<bool>,
-) -> Result<(), String> {
-    // Build request with Range header for resume
-    let mut request = client.get(url);
-    if bytes_downloaded > 0 {
-        request = request.header("Range", format!("bytes={bytes_downloaded}-"));
-    }
-
-    let response = request
-        .send()
+    auth_token: Option<&str>,
+) -> Result<String, String> {
+    let bytes = fetch_capped(client, url, auth_token, MAX_FETCH_TEXT_BYTES)
         .await
-        .map_err(|e| format!("Download request failed: {e}"))?;
+        .map_err(|e| format!("Fetch failed: {e}"))?;
 
-    // Check for successful response
-    if !response.status().is_success() && response.status().as_u16() != 206 {
-        return Err(format!("HTTP error: {}", response.status()));
+    String::from_utf8(bytes).map_err(|_| "Fetched file is not valid UTF-8 text".to_string())
+}
+
+/// GET `url` into memory, aborting as soon as the body exceeds `max_bytes`
+async fn fetch_capped(
+    client: &reqwest::Client,
+    url: &str,
+    auth_token: Option<&str>,
+    max_bytes: usize,
+) -> Result<Vec<u8>, String> {
+    let mut request = client.get(url);
+    if let Some(token) = auth_token {
+        request = request.bearer_auth(token);
     }
+    let response = request.send().await.map_err(|e| e.to_string())?;
 
-    // Open file for appending
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(part_path)
-        .map_err(|e| format!("Failed to open file: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
 
-    let start_time = Instant::now();
-    let mut last_update = Instant::now();
-    let start_bytes = bytes_downloaded;
+    let too_large = || format!("file exceeds the {max_bytes} byte limit");
+    if response
+        .content_length()
+        .is_some_and(|len| len > max_bytes as u64)
+    {
+        return Err(too_large());
+    }
 
+    // Content-Length may be absent or wrong, so enforce the cap while streaming
+    let mut body = Vec::new();
     let mut stream = response.bytes_stream();
-
-    while let Some(chunk_result) = stream.next().await {
-        // Check for cancellation
-        if *cancel_rx.borrow() {
-            info!("Download cancelled: {model_id}");
-            return Err("cancelled".to_string());
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Failed to read response: {e}"))?;
+        if body.len() + chunk.len() > max_bytes {
+            return Err(too_large());
         }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
 
-        let chunk = chunk_result.map_err(|e| format!("Stream error: {e}"))?;
-
-        file.write_all(&chunk)
-            .map_err(|e| format!("Write error: {e}"))?;
-
-        bytes_downloaded += chunk.len() as u64;
-
-        // Update progress at interval
-        if last_update.elapsed() >= PROGRESS_UPDATE_INTERVAL {
-            let elapsed = start_time.elapsed().as_secs_f64();
-            let bytes_since_start = bytes_downloaded.saturating_sub(start_bytes);
-            let speed_bps = if elapsed > 0.0 {
-                (bytes_since_start as f64 / elapsed) as u64
-            } else {
-                0
-            };
-            let remaining_bytes = total_bytes.saturating_sub(bytes_downloaded);
-            let eta_seconds = if speed_bps > 0 {
-                remaining_bytes / speed_bps
-            } else {
-                0
-            };
+/// Extract the digest from a checksum file
+///
+/// Accepts a bare digest, `sha256sum` output (`<hash>  <filename>` or
+/// `<hash> *<filename>`), and BSD style (`SHA256 (<filename>) = <hash>`).
+/// Only the first non-empty line is considered.
+pub fn parse_sha256_sidecar(contents: &str) -> Option<String> {
+    let line = contents.lines().map(str::trim).find(|l| !l.is_empty())?;
+
+    let candidate = match line.rsplit_once(" = ") {
+        Some((prefix, hash)) if prefix.starts_with("SHA256") => hash,
+        _ => line.split_whitespace().next()?,
+    };
 
-            let _ = app.emit(
-                "download_progress",
-                DownloadProgressEvent {
-                    download_id: download_id.to_string(),
-                    model_id: model_id.to_string(),
-                    status: "downloading".to_string(),
-                    bytes_downloaded,
-                    total_bytes,
-                    speed_bps,
-                    eta_seconds,
-                },
-            );
+    let is_sha256 = candidate.len() == 64 && candidate.chars().all(|c| c.is_ascii_hexdigit());
+    is_sha256.then(|| candidate.to_lowercase())
+}
 
-            last_update = Instant::now();
+/// Validate caller-supplied headers for a download
+///
+/// Rejects malformed names/values (including CR/LF injection) and headers
+/// that would interfere with connection handling or resume (`Range`, `Host`, ...).
+/// Credentials (`Authorization`, ...) are marked sensitive so logging the map
+/// redacts them.
+pub fn build_header_map(headers: &[(String, String)]) -> Result<HeaderMap, String> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| format!("Invalid header name: {name:?}"))?;
+        if RESERVED_HEADERS.contains(&header_name.as_str()) {
+            return Err(format!("Header {name:?} can't be set on downloads"));
         }
+        let mut header_value = HeaderValue::from_str(value)
+            .map_err(|_| format!("Invalid value for header {name:?}"))?;
+        // `HeaderValue`'s `Debug` prints `Sensitive` instead of the value
+        header_value.set_sensitive(SENSITIVE_HEADERS.contains(&header_name.as_str()));
+        map.append(header_name, header_value);
     }
+    Ok(map)
+}
 
-    // Sync and close file
-    file.sync_all().map_err(|e| format!("Sync error: {e}"))?;
-    drop(file);
+/// Result of probing a download URL before fetching it
+struct DownloadProbe {
+    /// Size of the remote file, 0 if the server wouldn't say
+    total_bytes: u64,
+    /// URL after following redirects (signed CDN URLs, relative Locations)
+    resolved_url: String,
+}
 
-    // Story 2.5: Checksum verification before rename
-    if let Some(hash) = expected_hash {
-        info!("Verifying integrity of downloaded file: {model_id}");
+/// Get content length and the redirect-resolved URL via HEAD request
+///
+/// Redirects are followed here once, and the GET (and any later resume)
+/// goes straight to the resolved URL so it can't be redirected elsewhere.
+/// Falls back to a `bytes=0-0` GET when HEAD is refused (405/501) or has no
+/// length; if that doesn't reveal the size either (chunked responses), the
+/// total is 0 (unknown) and the download goes ahead without a percentage.
+/// Every request asks for `identity` encoding so lengths are the file's own.
+async fn probe_download(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &HeaderMap,
+) -> Result<DownloadProbe, String> {
+    // Sizes and Range offsets must refer to the file, not a compressed copy
+    let response = client
+        .head(url)
+        .header(reqwest::header::ACCEPT_ENCODING, "identity")
+        .headers(headers.clone())
+        .send()
+        .await
+        .map_err(|e| format!("HEAD request failed: {e}"))?;
+
+    // Some hosts refuse HEAD outright; a one-byte ranged GET can stand in
+    let status = response.status();
+    let head_rejected = matches!(
+        status,
+        reqwest::StatusCode::METHOD_NOT_ALLOWED | reqwest::StatusCode::NOT_IMPLEMENTED
+    );
+    if !status.is_success() && !head_rejected {
+        return Err(format!("HEAD request failed: HTTP {status}"));
+    }
+
+    let mut resolved = response.url().clone();
+    let mut total_bytes = if head_rejected {
+        None
+    } else {
+        header_u64(response.headers(), "content-length")
+    };
+
+    if total_bytes.is_none() {
+        info!("HEAD gave no file size (HTTP {status}), probing with a ranged GET");
+        let response = client
+            .get(url)
+            .header(reqwest::header::ACCEPT_ENCODING, "identity")
+            .headers(headers.clone())
+            .header(reqwest::header::RANGE, "bytes=0-0")
+            .send()
+            .await
+            .map_err(|e| format!("Size probe failed: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!("Size probe failed: HTTP {}", response.status()));
+        }
+
+        resolved = response.url().clone();
+        // A server that ignores Range answers 200 with the full length instead
+        total_bytes = if response.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+            response
+                .headers()
+                .get("content-range")
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_content_range_total)
+        } else {
+            header_u64(response.headers(), "content-length")
+        };
+    }
+
+    if resolved.as_str() != url {
+        info!("Download URL redirected to {}", resolved.path());
+    }
+
+    let total_bytes = total_bytes.unwrap_or_else(|| {
+        warn!("Could not determine file size, downloading with an unknown total");
+        0
+    });
+
+    Ok(DownloadProbe {
+        total_bytes,
+        resolved_url: resolved.to_string(),
+    })
+}
+
+/// Parse a numeric response header
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.parse().ok()
+}
+
+/// Full size from a `Content-Range: bytes 0-0/<total>` header (`*` = unknown)
+fn parse_content_range_total(value: &str) -> Option<u64> {
+    value.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+/// Bytes re-fetched from the server to check the tail of a `.part` on resume
+const RESUME_CHECK_BYTES: u64 = 64 * 1024;
+
+/// Decide how much of an existing `.part` can be resumed from
+///
+/// A `.part` larger than the remote file, or whose last few KB differ from
+/// the server's bytes at the same offset (a torn write), is deleted and the
+/// download restarts from 0. If the check itself can't be made (no Range
+/// support, network error) the `.part` is trusted as before.
+async fn validate_partial(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &HeaderMap,
+    part_path: &Path,
+    part_len: u64,
+    total_bytes: u64,
+) -> u64 {
+    let sound = if total_bytes > 0 && part_len > total_bytes {
+        warn!(
+            "Partial download {} is {part_len} bytes, larger than the {total_bytes}-byte file",
+            part_path.display()
+        );
+        false
+    } else {
+        match part_tail_matches_remote(client, url, headers, part_path, part_len).await {
+            Ok(Some(matches)) => {
+                if !matches {
+                    warn!("Partial download {} has a torn tail", part_path.display());
+                }
+                matches
+            },
+            Ok(None) => true,
+            Err(e) => {
+                warn!("Couldn't check partial download before resuming: {e}");
+                true
+            },
+        }
+    };
+
+    if sound {
+        return part_len;
+    }
+    info!("Restarting download from scratch: {}", part_path.display());
+    if let Err(e) = std::fs::remove_file(part_path) {
+        warn!("Failed to remove partial download: {e}");
+    }
+    0
+}
+
+/// Compare the last `RESUME_CHECK_BYTES` of a `.part` with the server's copy
+///
+/// `None` if the server doesn't honour the Range request.
+async fn part_tail_matches_remote(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &HeaderMap,
+    part_path: &Path,
+    part_len: u64,
+) -> Result<Option<bool>, String> {
+    let start = part_len.saturating_sub(RESUME_CHECK_BYTES);
+    let response = client
+        .get(url)
+        .headers(headers.clone())
+        .header("Range", format!("bytes={start}-{}", part_len - 1))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return Ok(None);
+    }
+    let remote = response.bytes().await.map_err(|e| e.to_string())?;
+
+    let mut local = Vec::new();
+    let mut file = std::fs::File::open(part_path).map_err(|e| e.to_string())?;
+    std::io::Seek::seek(&mut file, std::io::SeekFrom::Start(start)).map_err(|e| e.to_string())?;
+    std::io::Read::read_to_end(&mut file, &mut local).map_err(|e| e.to_string())?;
+
+    Ok(Some(local == remote.as_ref()))
+}
+
+/// First retry delay; each further attempt doubles it
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest wait between retries
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// A transfer cut short by the network, resumable from `bytes_downloaded`
+struct Interrupted {
+    bytes_downloaded: u64,
+    error: reqwest::Error,
+    /// The request never reached the server (vs. a stream that broke mid-body)
+    connecting: bool,
+}
+
+/// Why a transfer pass stopped early
+enum TransferError {
+    /// Worth retrying after a backoff
+    Interrupted(Interrupted),
+    /// The server answered with an error status; a mirror may still serve the file
+    Unavailable(String),
+    /// Cancelled or unrecoverable; returned to the caller as-is
+    Failed(String),
+}
+
+/// Backoff before retry number `attempt` (1-based): 1s, 2s, 4s, ... up to the cap
+fn retry_delay(attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    RETRY_BASE_DELAY.saturating_mul(factor).min(RETRY_MAX_DELAY)
+}
+
+/// Error to report once retries are exhausted
+async fn give_up(
+    client: &reqwest::Client,
+    url: &str,
+    model_id: &str,
+    interrupted: &Interrupted,
+) -> String {
+    // A dropped connection only means "offline" if the host is gone too
+    if interrupted.connecting
+        || check_connectivity(client, url)
+            .await
+            .is_err_and(|e| e.offline)
+    {
+        warn!(
+            "Lost connectivity while downloading {model_id}: {}",
+            interrupted.error
+        );
+        return OFFLINE.to_string();
+    }
+    format!("Stream error: {}", interrupted.error)
+}
+
+/// Send the GET (ranged from `bytes_downloaded`) and open the .part to match
+///
+/// A server that ignores Range answers `200` with the whole file; appending
+/// that would corrupt the .part, so it is truncated instead and
+/// `bytes_downloaded` reset to 0.
+async fn open_transfer(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &HeaderMap,
+    part_path: &Path,
+    bytes_downloaded: &mut u64,
+    model_id: &str,
+) -> Result<(reqwest::Response, std::fs::File), TransferError> {
+    // Build request with Range header for resume
+    let mut request = client
+        .get(url)
+        .header(reqwest::header::ACCEPT_ENCODING, "identity")
+        .headers(headers.clone());
+    if *bytes_downloaded > 0 {
+        request = request.header("Range", format!("bytes={bytes_downloaded}-"));
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) if is_connectivity_error(&e) => {
+            return Err(TransferError::Interrupted(Interrupted {
+                bytes_downloaded: *bytes_downloaded,
+                error: e,
+                connecting: true,
+            }));
+        },
+        Err(e) => {
+            return Err(TransferError::Failed(format!(
+                "Download request failed: {e}"
+            )))
+        },
+    };
+
+    // Check for successful response
+    if !response.status().is_success() && response.status().as_u16() != 206 {
+        return Err(TransferError::Unavailable(format!(
+            "HTTP error: {}",
+            response.status()
+        )));
+    }
+
+    let restart = *bytes_downloaded > 0 && response.status() == reqwest::StatusCode::OK;
+    if restart {
+        warn!("Server ignored the Range request for {model_id}; restarting from 0");
+        *bytes_downloaded = 0;
+    }
+
+    // Open file for appending (buffered by the caller; flushed on drop if paused)
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(!restart)
+        .truncate(restart)
+        .open(part_path)
+        .map_err(|e| TransferError::Failed(format!("Failed to open file: {e}")))?;
+
+    Ok((response, file))
+}
+
+/// Stream the body into the .part file from `bytes_downloaded` onwards
+///
+/// Progress events add `done_bytes` (earlier shards) and report
+/// `total_bytes` of the whole download. Each chunk written also goes into
+/// `hasher`, if given. Returns the final byte count of this file once the
+/// response ends.
+#[allow(clippy::too_many_arguments)]
+async fn transfer(
+    app: &impl TransferEvents,
+    client: &reqwest::Client,
+    url: &str,
+    headers: &HeaderMap,
+    part_path: &Path,
+    mut bytes_downloaded: u64,
+    done_bytes: u64,
+    total_bytes: u64,
+    download_id: &str,
+    model_id: &str,
+    mut hasher: Option<&mut Sha256>,
+    tuning: DownloadTuning,
+    cancel_rx: &watch::Receiver<bool>,
+) -> Result<u64, TransferError> {
+    let requested_from = bytes_downloaded;
+    let (response, file) = open_transfer(
+        client,
+        url,
+        headers,
+        part_path,
+        &mut bytes_downloaded,
+        model_id,
+    )
+    .await?;
+    if bytes_downloaded < requested_from {
+        // The .part was truncated, so the hash starts over with it
+        if let Some(hasher) = hasher.as_mut() {
+            Digest::reset(&mut **hasher);
+        }
+        app.emit_progress(DownloadProgressEvent {
+            download_id: download_id.to_string(),
+            model_id: model_id.to_string(),
+            status: "restarting".to_string(),
+            bytes_downloaded: done_bytes,
+            total_bytes,
+            speed_bps: 0,
+            instant_speed_bps: 0,
+            average_speed_bps: 0,
+            eta_seconds: 0,
+            phase_percent: None,
+            retry_attempt: None,
+            stalled: false,
+        });
+    }
+    let mut file = BufWriter::with_capacity(tuning.buffer_size, file);
+
+    let mut speed_tracker =
+        SpeedTracker::new(Instant::now(), bytes_downloaded, INSTANT_SPEED_WINDOW);
+    let mut last_update = Instant::now();
+    let mut last_data = Instant::now();
+    let mut stalled = false;
+
+    let mut stream = response.bytes_stream();
+
+    loop {
+        // Wake up during silence too, so a frozen connection is still reported
+        let next = tokio::time::timeout(tuning.stall_check_interval, stream.next()).await;
+
+        // Check for cancellation
+        if *cancel_rx.borrow() {
+            info!("Download cancelled: {model_id}");
+            return Err(TransferError::Failed("cancelled".to_string()));
+        }
+
+        let Ok(next) = next else {
+            stalled = last_data.elapsed() >= tuning.stall_threshold;
+            let speed = speed_tracker.record(Instant::now(), bytes_downloaded);
+            report_transfer_progress(
+                app,
+                download_id,
+                model_id,
+                (done_bytes + bytes_downloaded, total_bytes),
+                speed,
+                stalled,
+            )
+            .await;
+            last_update = Instant::now();
+            continue;
+        };
+        let Some(chunk_result) = next else {
+            break;
+        };
+
+        let chunk = match chunk_result {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                // Everything counted so far must be on disk before resuming
+                file.flush()
+                    .map_err(|e| TransferError::Failed(format!("Write error: {e}")))?;
+                return Err(TransferError::Interrupted(Interrupted {
+                    bytes_downloaded,
+                    error: e,
+                    connecting: false,
+                }));
+            },
+        };
+
+        file.write_all(&chunk)
+            .map_err(|e| TransferError::Failed(format!("Write error: {e}")))?;
+        if let Some(hasher) = hasher.as_mut() {
+            hasher.update(&chunk);
+        }
+
+        bytes_downloaded += chunk.len() as u64;
+        last_data = Instant::now();
+
+        // Update progress at interval, or right away once a stall clears
+        if stalled || last_update.elapsed() >= tuning.progress_interval {
+            let speed = speed_tracker.record(Instant::now(), bytes_downloaded);
+            report_transfer_progress(
+                app,
+                download_id,
+                model_id,
+                (done_bytes + bytes_downloaded, total_bytes),
+                speed,
+                false,
+            )
+            .await;
+            last_update = Instant::now();
+            stalled = false;
+        }
+    }
+
+    // Flush, sync and close file
+    let file = file
+        .into_inner()
+        .map_err(|e| TransferError::Failed(format!("Write error: {}", e.error())))?;
+    file.sync_all()
+        .map_err(|e| TransferError::Failed(format!("Sync error: {e}")))?;
+    drop(file);
+
+    Ok(bytes_downloaded)
+}
+
+/// Emit `download_progress` for a running transfer and record it in state
+///
+/// `progress` is `(bytes_downloaded, total_bytes)` of the whole download.
+async fn report_transfer_progress(
+    app: &impl TransferEvents,
+    download_id: &str,
+    model_id: &str,
+    progress: (u64, u64),
+    speed: SpeedSample,
+    stalled: bool,
+) {
+    let (overall_bytes, total_bytes) = progress;
+    // The average keeps the ETA from jumping with every burst or stall
+    let remaining_bytes = total_bytes.saturating_sub(overall_bytes);
+    let eta_seconds = remaining_bytes.checked_div(speed.average_bps).unwrap_or(0);
+
+    app.emit_progress(DownloadProgressEvent {
+        download_id: download_id.to_string(),
+        model_id: model_id.to_string(),
+        status: "downloading".to_string(),
+        bytes_downloaded: overall_bytes,
+        total_bytes,
+        speed_bps: speed.average_bps,
+        instant_speed_bps: speed.instant_bps,
+        average_speed_bps: speed.average_bps,
+        eta_seconds,
+        phase_percent: None,
+        retry_attempt: None,
+        stalled,
+    });
+
+    // Keep get_download_progress in step with the events
+    app.download_state()
+        .update_progress(download_id, overall_bytes, speed, eta_seconds, stalled)
+        .await;
+}
+
+/// Download every file with resume support and optional integrity verification (Story 2.5)
+///
+/// Files (the shards of a split model) are fetched one after another, each
+/// verified and renamed into place before the next starts. Finished shards
+/// from an earlier attempt are skipped.
+#[allow(clippy::too_many_arguments)]
+async fn download_file(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    headers: &HeaderMap,
+    parts: &[DownloadPart],
+    download_id: &str,
+    model_id: &str,
+    quarantine_dir: &std::path::Path,
+    tuning: DownloadTuning,
+    mut cancel_rx: watch::Receiver<bool>,
+) -> Result<(), String> {
+    let first = parts.first().ok_or("Download has no files")?;
+    let model_dir = first
+        .final_path
+        .parent()
+        .ok_or("Download path has no model directory")?;
+    let sharded = parts.len() > 1;
+
+    let mut total_bytes: u64 = parts.iter().map(|part| part.total_bytes).sum();
+    // Size of the files before the current one
+    let mut done_bytes = 0;
+    for part in parts {
+        if part.done {
+            done_bytes += part.total_bytes;
+            continue;
+        }
+
+        let (bytes_downloaded, computed_hash) = fetch_part(
+            app,
+            client,
+            headers,
+            part,
+            done_bytes,
+            total_bytes,
+            download_id,
+            model_id,
+            tuning,
+            &mut cancel_rx,
+        )
+        .await?;
+
+        // With no size from the server, what arrived is the whole file
+        let part_bytes = if part.total_bytes == 0 {
+            total_bytes += bytes_downloaded;
+            bytes_downloaded
+        } else {
+            part.total_bytes
+        };
+
+        // Story 2.5: Checksum verification before rename
+        if let Some(hash) = part.expected_hash.as_deref() {
+            verify_part(
+                app,
+                part,
+                hash,
+                computed_hash,
+                done_bytes + part_bytes,
+                total_bytes,
+                part_bytes,
+                download_id,
+                model_id,
+                quarantine_dir,
+                !sharded,
+                tuning,
+                &cancel_rx,
+            )
+            .await?;
+        }
+
+        // Until the marker is rewritten below, the directory counts as unfinished
+        clear_complete(model_dir)?;
+
+        // Rename .part to final file
+        std::fs::rename(&part.part_path, &part.final_path)
+            .map_err(|e| format!("Rename failed: {e}"))?;
+        done_bytes += part_bytes;
+    }
+
+    info!("Download completed: {model_id}");
+
+    let file_names: Vec<String> = parts
+        .iter()
+        .filter_map(|part| part.final_path.file_name()?.to_str().map(str::to_string))
+        .collect();
+    let metadata = if sharded {
+        ModelMetadata::sharded(file_names)
+    } else {
+        ModelMetadata::new(
+            file_names
+                .first()
+                .map_or(DEFAULT_WEIGHTS_FILE, String::as_str),
+        )
+    };
+    if let Err(e) = metadata.save(model_dir) {
+        warn!("{e} for {model_id}");
+    }
+    // Readiness re-checks a single hash; split models are verified per shard
+    if let (false, Some(hash)) = (sharded, first.expected_hash.as_deref()) {
+        store_expected_hash(model_dir, hash);
+    }
+    // Written last: every file is downloaded, verified, and in place
+    mark_complete(model_dir)?;
+    DownloadManifest::remove(model_dir);
+
+    // Emit completion event with verified status if hash was checked
+    let verified = parts.iter().all(|part| part.expected_hash.is_some());
+    let status = if verified { "verified" } else { "completed" };
+
+    let _ = app.emit(
+        "download_progress",
+        DownloadProgressEvent {
+            download_id: download_id.to_string(),
+            model_id: model_id.to_string(),
+            status: status.to_string(),
+            bytes_downloaded: total_bytes,
+            total_bytes,
+            speed_bps: 0,
+            instant_speed_bps: 0,
+            average_speed_bps: 0,
+            eta_seconds: 0,
+            phase_percent: None,
+            retry_attempt: None,
+            stalled: false,
+        },
+    );
+
+    // Single terminal trigger for consumers (auto-load, library refresh)
+    let _ = app.emit(
+        "download_finished",
+        DownloadFinishedEvent {
+            download_id: download_id.to_string(),
+            model_id: model_id.to_string(),
+            final_path: first.final_path.to_string_lossy().to_string(),
+            verified,
+            total_bytes,
+        },
+    );
+    emit_models_changed(app, model_id, ModelChange::Downloaded);
+
+    Ok(())
+}
+
+/// Transfer one file into its .part, returning its size once complete
+///
+/// When the file has an expected hash, it's computed as the bytes arrive
+/// and returned too, so verification needn't read the file back. A resumed
+/// .part has its existing prefix hashed once first (emitting `rehashing`);
+/// if that fails, no hash is returned and verification falls back to
+/// re-reading the file.
+///
+/// A transfer dropped by a network error is retried with exponential
+/// backoff (emitting `retrying`), resuming from the bytes already written.
+/// With the default retries that is about 31s of waiting before a lost
+/// connection gives up as `offline`.
+/// An unreachable host or an error status moves on to the next mirror
+/// (emitting `switching_mirror`) before giving up.
+#[allow(clippy::too_many_arguments)]
+async fn fetch_part(
+    app: &impl TransferEvents,
+    client: &reqwest::Client,
+    headers: &HeaderMap,
+    part: &DownloadPart,
+    done_bytes: u64,
+    total_bytes: u64,
+    download_id: &str,
+    model_id: &str,
+    tuning: DownloadTuning,
+    cancel_rx: &mut watch::Receiver<bool>,
+) -> Result<(u64, Option<String>), String> {
+    let mut bytes_downloaded = part.bytes_downloaded;
+    let mut hasher = match part.expected_hash {
+        None => None,
+        Some(_) if bytes_downloaded == 0 => Some(Sha256::new()),
+        Some(_) => {
+            let progress = (done_bytes + bytes_downloaded, total_bytes);
+            rehash_part(app, part, progress, download_id, model_id, tuning).await
+        },
+    };
+    let mut url = part.url.clone();
+    let mut fallbacks = part.fallbacks.iter();
+    let mut attempt = 0;
+    loop {
+        let interrupted = match transfer(
+            app,
+            client,
+            &url,
+            headers,
+            &part.part_path,
+            bytes_downloaded,
+            done_bytes,
+            total_bytes,
+            download_id,
+            model_id,
+            hasher.as_mut(),
+            tuning,
+            cancel_rx,
+        )
+        .await
+        {
+            Ok(bytes) => {
+                let computed_hash = hasher.map(|hasher| format!("{:x}", hasher.finalize()));
+                return Ok((bytes, computed_hash));
+            },
+            Err(TransferError::Failed(e)) => return Err(e),
+            Err(TransferError::Unavailable(e)) => {
+                let Some(next) = fallbacks.next() else {
+                    return Err(e);
+                };
+                warn!(
+                    "Mirror {} failed for {model_id} ({e}), switching to {}",
+                    url_host(&url),
+                    url_host(next)
+                );
+                (url, attempt) = (next.clone(), 0);
+                let progress = (done_bytes + bytes_downloaded, total_bytes);
+                switch_mirror(app, download_id, model_id, &url, progress).await;
+                continue;
+            },
+            Err(TransferError::Interrupted(interrupted)) => interrupted,
+        };
+        bytes_downloaded = interrupted.bytes_downloaded;
+
+        // An unreachable host is skipped rather than waited on; the .part and
+        // its offset carry over, since mirrors serve identical content
+        let next_mirror = if interrupted.connecting {
+            fallbacks.next()
+        } else {
+            None
+        };
+        if let Some(next) = next_mirror {
+            warn!(
+                "Mirror {} unreachable for {model_id} ({}), switching to {}",
+                url_host(&url),
+                interrupted.error,
+                url_host(next)
+            );
+            (url, attempt) = (next.clone(), 0);
+            let progress = (done_bytes + bytes_downloaded, total_bytes);
+            switch_mirror(app, download_id, model_id, &url, progress).await;
+            continue;
+        }
+
+        attempt += 1;
+        if attempt > tuning.max_retries {
+            return Err(give_up(client, &url, model_id, &interrupted).await);
+        }
+
+        let delay = retry_delay(attempt);
+        warn!(
+            "Transfer of {model_id} interrupted ({}); retry {attempt}/{} in {}s",
+            interrupted.error,
+            tuning.max_retries,
+            delay.as_secs()
+        );
+        app.emit_progress(DownloadProgressEvent {
+            download_id: download_id.to_string(),
+            model_id: model_id.to_string(),
+            status: "retrying".to_string(),
+            bytes_downloaded: done_bytes + bytes_downloaded,
+            total_bytes,
+            speed_bps: 0,
+            instant_speed_bps: 0,
+            average_speed_bps: 0,
+            eta_seconds: 0,
+            phase_percent: None,
+            retry_attempt: Some(attempt),
+            stalled: false,
+        });
+
+        tokio::select! {
+            () = tokio::time::sleep(delay) => {},
+            _ = cancel_rx.wait_for(|cancelled| *cancelled) => {
+                info!("Download cancelled while waiting to retry: {model_id}");
+                return Err("cancelled".to_string());
+            },
+        }
+    }
+}
+
+/// Hash the existing prefix of a resumed .part on a blocking thread
+///
+/// Re-reading gigabytes takes a while, so `rehashing` progress goes out
+/// meanwhile, with the share hashed in `phase_percent`. `progress` is the
+/// bytes downloaded and total of the whole download.
+async fn rehash_part(
+    app: &impl TransferEvents,
+    part: &DownloadPart,
+    progress: (u64, u64),
+    download_id: &str,
+    model_id: &str,
+    tuning: DownloadTuning,
+) -> Option<Sha256> {
+    let event = |percent| DownloadProgressEvent {
+        download_id: download_id.to_string(),
+        model_id: model_id.to_string(),
+        status: "rehashing".to_string(),
+        bytes_downloaded: progress.0,
+        total_bytes: progress.1,
+        speed_bps: 0,
+        instant_speed_bps: 0,
+        average_speed_bps: 0,
+        eta_seconds: 0,
+        phase_percent: Some(percent),
+        retry_attempt: None,
+        stalled: false,
+    };
+    app.emit_progress(event(0));
+
+    let len = part.bytes_downloaded;
+    let part_path = part.part_path.clone();
+    let (hashed_tx, mut hashed_rx) = watch::channel(0);
+    let mut task = tokio::task::spawn_blocking(move || {
+        let mut last_update = Instant::now();
+        seed_hasher(&part_path, len, tuning.buffer_size, |hashed| {
+            if last_update.elapsed() >= tuning.progress_interval {
+                let _ = hashed_tx.send(hashed);
+                last_update = Instant::now();
+            }
+        })
+    });
+    loop {
+        tokio::select! {
+            hasher = &mut task => return hasher.ok().flatten(),
+            Ok(()) = hashed_rx.changed() => {
+                let hashed = *hashed_rx.borrow_and_update();
+                app.emit_progress(event((hashed as f64 / len as f64 * 100.0) as u8));
+            },
+        }
+    }
+}
+
+/// Hash the first `len` bytes of a resumed .part, to continue as it grows
+///
+/// `on_progress` gets the bytes hashed so far after each buffer. `None` if
+/// the prefix can't be read in full.
+fn seed_hasher(
+    part_path: &Path,
+    len: u64,
+    buffer_size: usize,
+    mut on_progress: impl FnMut(u64),
+) -> Option<Sha256> {
+    use std::io::Read;
+
+    let file = std::fs::File::open(part_path).ok()?;
+    let mut reader = file.take(len);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; buffer_size];
+    let mut hashed = 0;
+    loop {
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => return None,
+        };
+        hasher.update(&buffer[..n]);
+        hashed += n as u64;
+        on_progress(hashed);
+    }
+    (hashed == len).then_some(hasher)
+}
+
+/// Record the new mirror and tell the frontend about the switch
+///
+/// `progress` is the bytes downloaded and total of the whole download.
+async fn switch_mirror(
+    app: &impl TransferEvents,
+    download_id: &str,
+    model_id: &str,
+    mirror: &str,
+    progress: (u64, u64),
+) {
+    app.download_state()
+        .set_mirror_url(download_id, mirror)
+        .await;
+    app.emit_progress(mirror_switch_event(
+        download_id,
+        model_id,
+        progress.0,
+        progress.1,
+    ));
+}
+
+/// Check a finished .part against its expected SHA-256
+///
+/// A mismatch moves the file to quarantine and fails the download.
+/// `computed_hash` is the hash taken during the transfer; without one the
+/// file is read back and hashed. `bytes_downloaded`/`total_bytes` cover the
+/// whole download, `part_bytes` this file. `record_source` saves what `redownload_corrupted` needs to
+/// retry; that only works for single-file models.
+#[allow(clippy::too_many_arguments)]
+async fn verify_part(
+    app: &AppHandle,
+    part: &DownloadPart,
+    hash: &str,
+    computed_hash: Option<String>,
+    bytes_downloaded: u64,
+    total_bytes: u64,
+    part_bytes: u64,
+    download_id: &str,
+    model_id: &str,
+    quarantine_dir: &Path,
+    record_source: bool,
+    tuning: DownloadTuning,
+    cancel_rx: &watch::Receiver<bool>,
+) -> Result<(), String> {
+    let part_path = &part.part_path;
+    info!("Verifying integrity of downloaded file: {model_id}");
+
+    // Emit verifying status
+    let _ = app.emit(
+        "download_progress",
+        DownloadProgressEvent {
+            download_id: download_id.to_string(),
+            model_id: model_id.to_string(),
+            status: "verifying".to_string(),
+            bytes_downloaded,
+            total_bytes,
+            speed_bps: 0,
+            instant_speed_bps: 0,
+            average_speed_bps: 0,
+            eta_seconds: 0,
+            phase_percent: Some(0),
+            retry_attempt: None,
+            stalled: false,
+        },
+    );
+
+    // Task 12: Use streaming verification with progress events for large files
+    // Progress numbers go out on verification_progress, the outcome on verification_complete
+    // Without a hash from the transfer, the file is read back to compute one
+    let verification_result = computed_hash.map_or_else(
+        || {
+            verify_with_progress(
+                app,
+                part_path,
+                hash,
+                download_id,
+                model_id,
+                part_bytes,
+                tuning.buffer_size,
+                cancel_rx,
+            )
+        },
+        |computed_hash| {
+            emit_verification_progress(app, download_id, model_id, part_bytes, part_bytes);
+            let expected_hash = hash.to_lowercase();
+            Ok(verification::VerificationResult {
+                verified: computed_hash == expected_hash,
+                computed_hash,
+                expected_hash,
+                file_size: part_bytes,
+                algorithm: HashAlgorithm::Sha256,
+            })
+        },
+    );
 
-        // Emit verifying status
+    if let Ok(result) = &verification_result {
         let _ = app.emit(
-            "download_progress",
-            DownloadProgressEvent {
+            "verification_complete",
+            VerificationCompleteEvent {
                 download_id: download_id.to_string(),
                 model_id: model_id.to_string(),
-                status: "verifying".to_string(),
-                bytes_downloaded: total_bytes,
-                total_bytes,
-                speed_bps: 0,
-                eta_seconds: 0,
+                verified: result.verified,
+                expected_hash: result.expected_hash.clone(),
+                computed_hash: result.computed_hash.clone(),
+                total_bytes: result.file_size,
             },
         );
+    }
 
-        // Task 12: Use streaming verification with progress events for large files
-        // Note: We emit progress via download_progress event with "verifying" status
-        // and percentage in eta_seconds field (repurposed for verification progress)
-        let verification_result =
-            verify_with_progress(app, part_path, hash, download_id, model_id, total_bytes);
-
-        match verification_result {
-            Ok(result) if result.verified => {
-                info!(
-                    "Checksum verified for {}: {}",
-                    model_id, result.computed_hash
-                );
-            },
-            Ok(result) => {
-                // Verification failed - quarantine the file
-                warn!(
-                    "Checksum mismatch for {}: expected {}, got {}",
-                    model_id, result.expected_hash, result.computed_hash
-                );
+    match verification_result {
+        Ok(result) if result.verified => {
+            info!(
+                "Checksum verified for {}: {}",
+                model_id, result.computed_hash
+            );
+            Ok(())
+        },
+        Ok(result) => {
+            // Verification failed - quarantine the file
+            warn!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                model_id, result.expected_hash, result.computed_hash
+            );
 
-                // Move to quarantine
-                let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-                let quarantine_filename = format!("{model_id}_{timestamp}.gguf.corrupted");
-                let quarantine_path = quarantine_dir.join(&quarantine_filename);
-
-                std::fs::rename(part_path, &quarantine_path).map_err(|e| {
-                    format!(
-                        "Failed to quarantine corrupted file: {}. Source: {}, Dest: {}",
-                        e,
-                        part_path.display(),
-                        quarantine_path.display()
-                    )
-                })?;
-
-                // Emit corrupted event
-                let _ = app.emit(
-                    "download_progress",
-                    DownloadProgressEvent {
-                        download_id: download_id.to_string(),
+            // Move to quarantine
+            let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+            let quarantine_stem = format!("{model_id}_{timestamp}");
+            let quarantine_path = quarantine_dir.join(format!("{quarantine_stem}.gguf.corrupted"));
+
+            // Make room under the quarantine size cap, oldest entries first
+            if let Some(limit_bytes) = app.state::<DownloadState>().quarantine_size_limit() {
+                let incoming_bytes = std::fs::metadata(part_path).map_or(0, |m| m.len());
+                evict_quarantine(quarantine_dir, incoming_bytes, limit_bytes);
+            }
+
+            std::fs::rename(part_path, &quarantine_path).map_err(|e| {
+                format!(
+                    "Failed to quarantine corrupted file: {}. Source: {}, Dest: {}",
+                    e,
+                    part_path.display(),
+                    quarantine_path.display()
+                )
+            })?;
+
+            // Remember the source so redownload_corrupted can retry it
+            let download = if record_source {
+                app.state::<DownloadState>().get_download(download_id).await
+            } else {
+                None
+            };
+            if let Some(download) = download {
+                write_quarantine_record(
+                    quarantine_dir,
+                    &quarantine_stem,
+                    &QuarantineRecord {
                         model_id: model_id.to_string(),
-                        status: "corrupted".to_string(),
-                        bytes_downloaded: total_bytes,
-                        total_bytes,
-                        speed_bps: 0,
-                        eta_seconds: 0,
+                        url: download.url,
+                        tokenizer_url: download.tokenizer_url,
+                        expected_hash: result.expected_hash.clone(),
+                        actual_hash: result.computed_hash.clone(),
+                        tokenizer_hash: download.tokenizer_hash,
+                        headers: StoredHeaders::new(&download.headers),
+                        shard_hashes: download.shard_hashes,
+                        mirrors: download.mirrors,
                     },
                 );
+            }
 
-                // Also emit a detailed corruption event for the UI
-                let _ = app.emit(
-                    "download_corrupted",
-                    serde_json::json!({
-                        "model_id": model_id,
-                        "expected_hash": result.expected_hash,
-                        "actual_hash": result.computed_hash,
-                        "quarantine_path": quarantine_path.to_string_lossy(),
-                    }),
-                );
-
-                return Err(format!(
-                    "Checksum verification failed: expected {}, got {}",
-                    result.expected_hash, result.computed_hash
-                ));
-            },
-            Err(e) => {
-                error!("Verification error for {model_id}: {e:?}");
-                return Err(format!("Verification failed: {}", e.message));
-            },
-        }
-    }
-
-    // Rename .part to final file
-    std::fs::rename(part_path, final_path).map_err(|e| format!("Rename failed: {e}"))?;
+            // Emit corrupted event
+            let _ = app.emit(
+                "download_progress",
+                DownloadProgressEvent {
+                    download_id: download_id.to_string(),
+                    model_id: model_id.to_string(),
+                    status: "corrupted".to_string(),
+                    bytes_downloaded,
+                    total_bytes,
+                    speed_bps: 0,
+                    instant_speed_bps: 0,
+                    average_speed_bps: 0,
+                    eta_seconds: 0,
+                    phase_percent: None,
+                    retry_attempt: None,
+                    stalled: false,
+                },
+            );
 
-    info!("Download completed: {model_id}");
+            // Also emit a detailed corruption event for the UI
+            let _ = app.emit(
+                "download_corrupted",
+                serde_json::json!({
+                    "model_id": model_id,
+                    "expected_hash": result.expected_hash,
+                    "actual_hash": result.computed_hash,
+                    "quarantine_path": quarantine_path.to_string_lossy(),
+                }),
+            );
 
-    // Emit completion event with verified status if hash was checked
-    let status = if expected_hash.is_some() {
-        "verified"
-    } else {
-        "completed"
-    };
+            Err(format!(
+                "Checksum verification failed: expected {}, got {}",
+                result.expected_hash, result.computed_hash
+            ))
+        },
+        Err(e) if e.kind == "cancelled" => {
+            info!("Verification interrupted for {model_id}");
+            // cancel_download drops the entry; it may not have been able to
+            // delete the .part while the hash still had it open
+            if app
+                .state::<DownloadState>()
+                .get_download(download_id)
+                .await
+                .is_none()
+            {
+                if let Err(e) = std::fs::remove_file(part_path) {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        warn!("Failed to remove partial file: {e}");
+                    }
+                }
+            }
+            Err("cancelled".to_string())
+        },
+        Err(e) => {
+            error!("Verification error for {model_id}: {e:?}");
+            Err(format!("Verification failed: {}", e.message))
+        },
+    }
+}
 
+/// Tell the frontend the installed model library changed
+pub fn emit_models_changed(app: &AppHandle, model_id: &str, action: ModelChange) {
     let _ = app.emit(
-        "download_progress",
-        DownloadProgressEvent {
-            download_id: download_id.to_string(),
+        "models:changed",
+        ModelsChangedEvent {
             model_id: model_id.to_string(),
-            status: status.to_string(),
-            bytes_downloaded: total_bytes,
-            total_bytes,
-            speed_bps: 0,
-            eta_seconds: 0,
+            action,
         },
     );
+}
 
-    Ok(())
+/// Quick reachability check against the download host
+///
+/// Any HTTP response (even an error status) counts as reachable; only
+/// connection failures and timeouts are reported as offline.
+pub async fn check_connectivity(client: &reqwest::Client, url: &str) -> Result<(), NetworkError> {
+    let mut origin = reqwest::Url::parse(url)
+        .map_err(|e| NetworkError::from(format!("Invalid download URL: {e}")))?;
+    origin.set_path("/");
+    origin.set_query(None);
+    let host = origin.host_str().unwrap_or_default().to_string();
+
+    match client
+        .head(origin)
+        .timeout(CONNECTIVITY_TIMEOUT)
+        .send()
+        .await
+    {
+        Err(e) if is_connectivity_error(&e) => {
+            warn!("Download host {host} unreachable: {e}");
+            Err(NetworkError::offline(&host))
+        },
+        // Other failures are left for the download itself to report
+        _ => Ok(()),
+    }
+}
+
+/// Whether a request failed because the host couldn't be reached at all
+fn is_connectivity_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout()
 }
 
 /// Pause a download
@@ -512,37 +1998,697 @@ pub async fn pause_download(state: &DownloadState, download_id: &str) -> Result<
 }
 
 /// Resume a paused download
+///
+/// Covers downloads paused by the user and those that went offline.
 pub async fn resume_download(
     app: &AppHandle,
     state: &DownloadState,
     download_id: &str,
 ) -> Result<(), String> {
-    if let Some(download) = state.get_download(download_id).await {
-        if download.status != DownloadStatus::Paused {
-            return Err("Download is not paused".to_string());
+    // Remove old download entry
+    let download = state.take_paused(download_id).await?;
+
+    // Start a new download (will resume from .part file)
+    // Tokenizer should already be downloaded since it downloads first
+    // Story 2.5: Pass stored expected_hash for verification on resume
+    // Reuses the resolved URL so signed redirects aren't re-resolved
+    start_download(app, state, download.to_request()).await?;
+
+    info!("Download resumed: {download_id}");
+    Ok(())
+}
+
+/// Resume a partial download that has no in-memory entry (e.g. after restart)
+///
+/// Rebuilds the download from the existing .part file; `start_download`
+/// picks up from its current size using HTTP Range headers. Also takes over a
+/// download recovered from its manifest that lacks credential headers, which
+/// `request.headers` must then supply.
+pub async fn resume_from_disk(
+    app: &AppHandle,
+    state: &DownloadState,
+    mut request: DownloadRequest,
+) -> Result<String, String> {
+    let model_id = request.model_id.clone();
+    if !has_partial_download(&state.models_dir().join(&model_id), &request.url) {
+        return Err(format!("No partial download found for {model_id}"));
+    }
+
+    let tracked = state
+        .get_all_downloads()
+        .await
+        .into_iter()
+        .find(|d| d.model_id == model_id);
+    if let Some(tracked) = tracked {
+        if tracked.missing_credentials.is_empty() {
+            return Err(format!(
+                "A download for {model_id} is already tracked; use resume_download instead"
+            ));
+        }
+        let stored = tracked.stored_headers();
+        let missing = stored.missing_credentials(&request.headers);
+        if !missing.is_empty() {
+            return Err(format!(
+                "Resuming {model_id} needs the {} header it was started with",
+                missing.join(", ")
+            ));
+        }
+        request.headers = stored.with_supplied(request.headers);
+        state.remove_download(&tracked.id).await;
+    }
+
+    info!("Recovering partial download from disk: {model_id}");
+    start_download(app, state, request).await
+}
+
+/// Save the source of a quarantined download as `{stem}.json`
+fn write_quarantine_record(quarantine_dir: &Path, stem: &str, record: &QuarantineRecord) {
+    let result = serde_json::to_string_pretty(record)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            std::fs::write(quarantine_dir.join(format!("{stem}.json")), json)
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        warn!("Failed to save quarantine metadata for {stem}: {e}");
+    }
+}
+
+/// Find the most recent quarantine record for a model
+///
+/// Returns the `{model_id}_{timestamp}` stem shared by the record and its
+/// `.gguf.corrupted` file.
+pub fn find_quarantine_record(
+    quarantine_dir: &Path,
+    model_id: &str,
+) -> Option<(String, QuarantineRecord)> {
+    std::fs::read_dir(quarantine_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?.to_string();
+            let record: QuarantineRecord =
+                serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+            (record.model_id == model_id).then_some((stem, record))
+        })
+        // Timestamps are zero-padded, so the newest stem sorts last
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+}
+
+/// Delete a quarantined file and its metadata record
+pub fn remove_quarantined(quarantine_dir: &Path, stem: &str) -> Result<(), String> {
+    for name in [format!("{stem}.gguf.corrupted"), format!("{stem}.json")] {
+        match std::fs::remove_file(quarantine_dir.join(&name)) {
+            Ok(()) => {},
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+            Err(e) => return Err(format!("Failed to delete quarantined {name}: {e}")),
+        }
+    }
+    Ok(())
+}
+
+/// Evict the oldest quarantined files until `incoming_bytes` more fits the cap
+///
+/// Each eviction also removes the file's metadata record. Returns the evicted
+/// stems, oldest first.
+pub fn evict_quarantine(
+    quarantine_dir: &Path,
+    incoming_bytes: u64,
+    limit_bytes: u64,
+) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(quarantine_dir) else {
+        return vec![];
+    };
+    let mut files: Vec<(String, u64)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let stem = name.strip_suffix(".gguf.corrupted")?.to_string();
+            Some((stem, entry.metadata().ok()?.len()))
+        })
+        .collect();
+    // Stems end in a zero-padded timestamp; compare that, not the model_id
+    files.sort_by(|(a, _), (b, _)| quarantine_timestamp(a).cmp(quarantine_timestamp(b)));
+
+    let mut total: u64 = files.iter().map(|(_, size)| size).sum();
+    let mut evicted = Vec::new();
+    for (stem, size) in files {
+        if total.saturating_add(incoming_bytes) <= limit_bytes {
+            break;
+        }
+        match remove_quarantined(quarantine_dir, &stem) {
+            Ok(()) => {
+                info!("Evicted quarantined {stem} ({size} bytes) to stay under the size cap");
+                total -= size;
+                evicted.push(stem);
+            },
+            Err(e) => warn!("Failed to evict quarantined {stem}: {e}"),
+        }
+    }
+    if total.saturating_add(incoming_bytes) > limit_bytes {
+        warn!("Quarantine will exceed its {limit_bytes}-byte cap: the new file alone is too large");
+    }
+    evicted
+}
+
+/// The `YYYYMMDD_HHMMSS` suffix of a `{model_id}_{timestamp}` quarantine stem
+fn quarantine_timestamp(stem: &str) -> &str {
+    stem.get(stem.len().saturating_sub(15)..).unwrap_or(stem)
+}
+
+/// Fetch a quarantined model again from the URL and hash it was stored with
+///
+/// `headers` are added to the stored ones and must include any credentials
+/// the original download sent. The quarantined file is removed once the new
+/// download has started, so a failed start can be retried. Returns the new
+/// download_id.
+pub async fn redownload_corrupted(
+    app: &AppHandle,
+    state: &DownloadState,
+    model_id: &str,
+    headers: Vec<(String, String)>,
+) -> Result<String, String> {
+    let quarantine_dir = state.quarantine_dir();
+    let (stem, record) = find_quarantine_record(&quarantine_dir, model_id)
+        .ok_or_else(|| format!("No quarantine metadata found for '{model_id}'"))?;
+    restart_quarantined(app, state, &stem, record, headers).await
+}
+
+/// Retry one quarantined file, by its `list_quarantined_files` ID
+///
+/// Starts a fresh download from the source in the file's sidecar, with
+/// `headers` as for `redownload_corrupted`, then deletes the file and sidecar.
+/// Returns the new download_id.
+pub async fn requarantine_retry(
+    app: &AppHandle,
+    state: &DownloadState,
+    file_id: &str,
+    headers: Vec<(String, String)>,
+) -> Result<String, String> {
+    let (stem, record) = read_quarantine_record(&state.quarantine_dir(), file_id)?;
+    restart_quarantined(app, state, &stem, record, headers).await
+}
+
+/// Load the sidecar of a quarantined file ID (`{stem}.gguf` or `{stem}`)
+///
+/// Returns the shared stem alongside the record.
+pub fn read_quarantine_record(
+    quarantine_dir: &Path,
+    file_id: &str,
+) -> Result<(String, QuarantineRecord), String> {
+    let stem = file_id.strip_suffix(".gguf").unwrap_or(file_id);
+    if !is_plain_file_name(stem) {
+        return Err(format!("Invalid quarantined file ID: {file_id}"));
+    }
+    if !quarantine_dir
+        .join(format!("{stem}.gguf.corrupted"))
+        .exists()
+    {
+        return Err(format!("Quarantined file not found: {file_id}"));
+    }
+
+    let record = std::fs::read_to_string(quarantine_dir.join(format!("{stem}.json")))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .ok_or_else(|| {
+            format!(
+                "No download metadata was saved for '{file_id}'; delete it and re-download \
+                 the model manually"
+            )
+        })?;
+    Ok((stem.to_string(), record))
+}
+
+/// Start a fresh download for a quarantine record, then drop the quarantined copy
+///
+/// The quarantined file is removed only once the new download has started, so
+/// a failed start can be retried.
+async fn restart_quarantined(
+    app: &AppHandle,
+    state: &DownloadState,
+    stem: &str,
+    record: QuarantineRecord,
+    headers: Vec<(String, String)>,
+) -> Result<String, String> {
+    let model_id = record.model_id.clone();
+    let download_id = start_download(app, state, quarantine_request(record, headers)?).await?;
+
+    if let Err(e) = remove_quarantined(&state.quarantine_dir(), stem) {
+        warn!("{e}");
+    }
+    info!("Re-downloading corrupted model {model_id} as {download_id}");
+    Ok(download_id)
+}
+
+/// Request that downloads a quarantined model again as it was first fetched
+///
+/// Fails if the original download sent credentials that `headers` doesn't
+/// supply again, since their values were never stored.
+fn quarantine_request(
+    record: QuarantineRecord,
+    headers: Vec<(String, String)>,
+) -> Result<DownloadRequest, String> {
+    let missing = record.headers.missing_credentials(&headers);
+    if !missing.is_empty() {
+        return Err(format!(
+            "'{}' was downloaded with credentials that aren't stored; pass the {} header{} \
+             again to re-download it",
+            record.model_id,
+            missing.join(", "),
+            if missing.len() == 1 { "" } else { "s" }
+        ));
+    }
+    Ok(DownloadRequest {
+        headers: record.headers.with_supplied(headers),
+        model_id: record.model_id,
+        url: record.url,
+        tokenizer_url: record.tokenizer_url,
+        expected_hash: Some(record.expected_hash),
+        tokenizer_hash: record.tokenizer_hash,
+        resolved_url: None,
+        shard_hashes: record.shard_hashes,
+        mirrors: record.mirrors,
+    })
+}
+
+/// Adopt manually downloaded model files into `models/{model_id}/`
+///
+/// Hard-links the files when possible (same filesystem, no extra space) and
+/// falls back to copying. The model directory must not already exist.
+/// If `expected_hash` is given the imported weights are verified, and the
+/// directory is removed again on mismatch.
+pub fn import_local_model(
+    models_dir: &Path,
+    model_id: &str,
+    gguf_path: &Path,
+    tokenizer_path: &Path,
+    expected_hash: Option<&str>,
+) -> Result<PathBuf, String> {
+    let model_dir = models_dir.join(model_id);
+    if model_dir.exists() {
+        return Err(format!("Model '{model_id}' already exists"));
+    }
+
+    // Validate both sources are readable before touching the models directory
+    for source in [gguf_path, tokenizer_path] {
+        std::fs::File::open(source)
+            .map_err(|e| format!("Cannot read {}: {e}", source.display()))?;
+    }
+
+    // Keep the original weights filename unless it would clash with a sidecar
+    let weights_file = gguf_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| !["tokenizer.json", "model.json", HASH_FILE].contains(name))
+        .unwrap_or(DEFAULT_WEIGHTS_FILE);
+    let weights_path = model_dir.join(weights_file);
+
+    std::fs::create_dir_all(&model_dir)
+        .map_err(|e| format!("Failed to create model directory: {e}"))?;
+
+    let result = link_or_copy(gguf_path, &weights_path)
+        .and_then(|()| link_or_copy(tokenizer_path, &model_dir.join("tokenizer.json")))
+        .and_then(|()| ModelMetadata::new(weights_file).save(&model_dir))
+        .and_then(|()| match expected_hash {
+            Some(hash) => {
+                let result =
+                    verification::verify_integrity(&weights_path, hash, HashAlgorithm::Sha256)
+                        .map_err(|e| e.message)?;
+                if result.verified {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "Checksum verification failed: expected {}, got {}",
+                        result.expected_hash, result.computed_hash
+                    ))
+                }
+            },
+            None => Ok(()),
+        });
+
+    if let Err(e) = result {
+        // Leave no half-imported model behind
+        let _ = std::fs::remove_dir_all(&model_dir);
+        return Err(e);
+    }
+
+    if let Some(hash) = expected_hash {
+        store_expected_hash(&model_dir, hash);
+    }
+    if let Err(e) = mark_complete(&model_dir) {
+        let _ = std::fs::remove_dir_all(&model_dir);
+        return Err(e);
+    }
+
+    info!(
+        "Imported local model {model_id} from {}",
+        gguf_path.display()
+    );
+    Ok(model_dir)
+}
+
+/// Hard-link `source` to `dest`, copying when linking isn't possible
+fn link_or_copy(source: &Path, dest: &Path) -> Result<(), String> {
+    if std::fs::hard_link(source, dest).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(source, dest)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to copy {}: {e}", source.display()))
+}
+
+/// Remember the verified hash so readiness checks can re-verify later
+fn store_expected_hash(model_dir: &Path, hash: &str) {
+    if let Err(e) = std::fs::write(model_dir.join(HASH_FILE), hash.to_lowercase()) {
+        warn!("Failed to store hash for {}: {e}", model_dir.display());
+    }
+}
+
+/// Check whether a model is fully downloaded and safe to load
+///
+/// Requires the model weights and `tokenizer.json`, no leftover `.part`, the
+/// `.complete` marker, and a matching checksum when a verified hash was
+/// stored for the model.
+pub fn check_model_readiness(models_dir: &Path, model_id: &str) -> ModelReadiness {
+    let model_dir = models_dir.join(model_id);
+    let model_path = weights_path(&model_dir);
+    let mut reasons = Vec::new();
+
+    // A split model needs every shard
+    let has_model = weights_paths(&model_dir).iter().all(|path| path.exists());
+    if !has_model {
+        reasons.push("model weights are missing".to_string());
+    }
+    let has_tokenizer = model_dir.join("tokenizer.json").exists();
+    if !has_tokenizer {
+        reasons.push("tokenizer.json is missing".to_string());
+    }
+    let has_partial = model_dir.join("model.gguf.part").exists();
+    if has_partial {
+        reasons.push("a partial download (.part) remains".to_string());
+    }
+    if has_model && has_tokenizer && !has_partial && !is_complete(&model_dir) {
+        reasons.push("the download was never finalized".to_string());
+    }
+
+    let checksum_verified = match stored_hash(&model_dir) {
+        Some(hash) if has_model => {
+            match verification::verify_integrity(&model_path, &hash, HashAlgorithm::Sha256) {
+                Ok(result) if result.verified => Some(true),
+                Ok(result) => {
+                    reasons.push(format!(
+                        "checksum mismatch: expected {}, got {}",
+                        result.expected_hash, result.computed_hash
+                    ));
+                    Some(false)
+                },
+                Err(e) => {
+                    reasons.push(format!("checksum could not be computed: {}", e.message));
+                    Some(false)
+                },
+            }
+        },
+        _ => None,
+    };
+
+    ModelReadiness {
+        model_id: model_id.to_string(),
+        ready: reasons.is_empty(),
+        has_model,
+        has_tokenizer,
+        has_partial,
+        checksum_verified,
+        reasons,
+    }
+}
+
+/// Every model directory under `models_dir`, sorted by model id
+///
+/// Includes unfinished downloads so the UI can offer to resume or remove them.
+pub fn list_installed_models(models_dir: &Path) -> Vec<InstalledModel> {
+    let Ok(entries) = std::fs::read_dir(models_dir) else {
+        return Vec::new();
+    };
+
+    let mut models: Vec<InstalledModel> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| {
+            let model_dir = entry.path();
+            let files: Vec<std::fs::Metadata> = std::fs::read_dir(&model_dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter_map(|file| file.metadata().ok())
+                .filter(std::fs::Metadata::is_file)
+                .collect();
+
+            InstalledModel {
+                model_id: entry.file_name().to_string_lossy().to_string(),
+                size_bytes: files.iter().map(std::fs::Metadata::len).sum(),
+                has_tokenizer: model_dir.join("tokenizer.json").exists(),
+                complete: is_complete(&model_dir)
+                    && weights_paths(&model_dir).iter().all(|path| path.exists()),
+                modified_ms: files
+                    .iter()
+                    .filter_map(|file| file.modified().ok())
+                    .max()
+                    .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                    .and_then(|age| u64::try_from(age.as_millis()).ok()),
+            }
+        })
+        .collect();
+
+    models.sort_by(|a, b| a.model_id.cmp(&b.model_id));
+    models
+}
+
+/// Find model directories holding nothing but leftovers of a download
+///
+/// A directory is orphaned when it is empty, or holds only `.part` files and
+/// a stale manifest, and no download in `tracked` (model ids known to
+/// `DownloadState`, paused ones included) still owns it. Only with `confirm`
+/// are the directories removed; either way the orphans are returned. The
+/// quarantine is never scanned or touched.
+pub fn cleanup_orphaned_downloads(
+    models_dir: &Path,
+    quarantine_dir: &Path,
+    tracked: &[String],
+    confirm: bool,
+) -> Result<Vec<OrphanedDownload>, String> {
+    let entries = std::fs::read_dir(models_dir)
+        .map_err(|e| format!("Failed to read models directory: {e}"))?;
+
+    let mut orphans: Vec<(PathBuf, OrphanedDownload)> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|entry| !entry.path().starts_with(quarantine_dir))
+        .filter_map(|entry| {
+            let model_id = entry.file_name().to_str()?.to_string();
+            if tracked.contains(&model_id) {
+                return None;
+            }
+            let orphan = find_orphan(&entry.path(), model_id)?;
+            Some((entry.path(), orphan))
+        })
+        .collect();
+    orphans.sort_by(|(_, a), (_, b)| a.model_id.cmp(&b.model_id));
+
+    if confirm {
+        for (model_dir, orphan) in &orphans {
+            std::fs::remove_dir_all(model_dir)
+                .map_err(|e| format!("Failed to remove {}: {e}", orphan.model_id))?;
+            info!(
+                "Removed orphaned download {} ({} bytes)",
+                orphan.model_id, orphan.bytes
+            );
+        }
+    }
+    Ok(orphans.into_iter().map(|(_, orphan)| orphan).collect())
+}
+
+/// The leftovers in `model_dir`, or `None` if it holds anything worth keeping
+fn find_orphan(model_dir: &Path, model_id: String) -> Option<OrphanedDownload> {
+    let mut files = Vec::new();
+    let mut bytes = 0;
+    for entry in std::fs::read_dir(model_dir).ok()?.flatten() {
+        let name = entry.file_name().to_str()?.to_string();
+        let leftover = Path::new(&name)
+            .extension()
+            .is_some_and(|ext| ext == "part")
+            || name == DOWNLOAD_MANIFEST_FILE;
+        if !leftover || !entry.file_type().ok()?.is_file() {
+            return None;
+        }
+        bytes += entry.metadata().ok()?.len();
+        files.push(name);
+    }
+    files.sort();
+
+    Some(OrphanedDownload {
+        model_id,
+        files,
+        bytes,
+    })
+}
+
+/// Disk usage of the models directory and, separately, of the quarantine
+pub fn storage_usage(models_dir: &Path, quarantine_dir: &Path) -> StorageUsage {
+    let (total_bytes, partial_bytes) = dir_usage(models_dir);
+    let mut per_model: Vec<(String, u64)> = std::fs::read_dir(models_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| {
+            let model_id = entry.file_name().to_string_lossy().to_string();
+            (model_id, dir_usage(&entry.path()).0)
+        })
+        .collect();
+    per_model.sort();
+
+    StorageUsage {
+        total_bytes,
+        per_model,
+        partial_bytes,
+        quarantine_bytes: dir_usage(quarantine_dir).0,
+    }
+}
+
+/// Bytes of every file under `dir`, and of the `.part` files among them
+///
+/// Symlinks are not followed, so nothing outside `dir` is counted.
+fn dir_usage(dir: &Path) -> (u64, u64) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (0, 0);
+    };
+
+    let mut total = 0;
+    let mut partial = 0;
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            let (dir_total, dir_partial) = dir_usage(&entry.path());
+            total += dir_total;
+            partial += dir_partial;
+        } else if file_type.is_file() {
+            let len = entry.metadata().map_or(0, |m| m.len());
+            total += len;
+            if entry.path().extension().is_some_and(|ext| ext == "part") {
+                partial += len;
+            }
+        }
+    }
+    (total, partial)
+}
+
+/// Discard the partial download for a model, keeping any completed `model.gguf`
+///
+/// Refuses while a download for the model is running, since that would pull
+/// the file out from under it. Returns whether a `.part` file was removed.
+pub async fn delete_partial_download(
+    state: &DownloadState,
+    model_id: &str,
+) -> Result<bool, String> {
+    if state.has_active_download(model_id).await {
+        return Err(format!(
+            "Download for {model_id} is in progress; pause or cancel it first"
+        ));
+    }
+
+    let model_dir = state.models_dir().join(model_id);
+    let shards = DownloadManifest::load(&model_dir)
+        .map(|manifest| shards::shard_names(&manifest.url))
+        .unwrap_or_default();
+    DownloadManifest::remove(&model_dir);
+    remove_part_files(&model_dir, &shards)
+}
+
+/// Remove `model.gguf.part`, or the `.part` of each shard, if present
+fn remove_part_files(model_dir: &Path, shards: &[String]) -> Result<bool, String> {
+    let part_paths = if shards.is_empty() {
+        vec![model_dir.join("model.gguf.part")]
+    } else {
+        shards
+            .iter()
+            .map(|shard| shards::part_path(model_dir, shard))
+            .collect()
+    };
+
+    let mut removed = false;
+    for part_path in part_paths {
+        match std::fs::remove_file(&part_path) {
+            Ok(()) => removed = true,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+            Err(e) => return Err(format!("Failed to delete partial download: {e}")),
+        }
+    }
+    if removed {
+        info!("Deleted partial download in {}", model_dir.display());
+    }
+    Ok(removed)
+}
+
+/// Remove files from a model directory, logging failures
+fn remove_files(model_dir: &Path, names: &[String]) {
+    for name in names {
+        match std::fs::remove_file(model_dir.join(name)) {
+            Ok(()) => {},
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+            Err(e) => warn!("Failed to remove {name}: {e}"),
         }
+    }
+}
 
-        // Remove old download entry
-        state.remove_download(download_id).await;
+/// Whether a download of `url` into `model_dir` was started and left unfinished
+pub fn has_partial_download(model_dir: &Path, url: &str) -> bool {
+    let shards = shards::shard_names(url);
+    if shards.is_empty() {
+        model_dir.join("model.gguf.part").exists()
+    } else {
+        !is_complete(model_dir) && shards::bytes_on_disk(model_dir, &shards).is_some()
+    }
+}
 
-        // Start a new download (will resume from .part file)
-        // Tokenizer should already be downloaded since it downloads first
-        // Story 2.5: Pass stored expected_hash for verification on resume
-        start_download(
-            app,
-            state,
-            &download.model_id,
-            &download.url,
-            &download.tokenizer_url,
-            download.expected_hash.as_deref(),
-        )
-        .await?;
+/// Pause every active download, preserving their .part files
+///
+/// Returns the number of downloads that were paused.
+pub async fn pause_all_downloads(state: &DownloadState) -> usize {
+    let mut paused = 0;
+    for download in state.get_all_downloads().await {
+        if matches!(
+            download.status,
+            DownloadStatus::Downloading | DownloadStatus::Queued
+        ) && pause_download(state, &download.id).await.is_ok()
+        {
+            paused += 1;
+        }
+    }
+    info!("Paused {paused} downloads");
+    paused
+}
 
-        info!("Download resumed: {download_id}");
-        Ok(())
-    } else {
-        Err("Download not found".to_string())
+/// Resume every paused download from its .part file
+///
+/// Each download restarts with its stored URLs and expected hash.
+/// Returns the number of downloads that were resumed.
+pub async fn resume_all_downloads(app: &AppHandle, state: &DownloadState) -> usize {
+    let mut resumed = 0;
+    for download in state.get_all_downloads().await {
+        if download.status != DownloadStatus::Paused {
+            continue;
+        }
+        match resume_download(app, state, &download.id).await {
+            Ok(()) => resumed += 1,
+            Err(e) => warn!("Failed to resume download {}: {e}", download.id),
+        }
     }
+    info!("Resumed {resumed} downloads");
+    resumed
 }
 
 /// Cancel a download and clean up partial files
@@ -555,11 +2701,20 @@ pub async fn cancel_download(
         // Signal cancellation
         let _ = download.cancel_token.send(true);
 
-        // Clean up partial file
-        if download.part_path.exists() {
-            if let Err(e) = std::fs::remove_file(&download.part_path) {
-                warn!("Failed to remove partial file: {e}");
+        // Clean up partial files
+        for part_path in download.part_paths() {
+            if part_path.exists() {
+                if let Err(e) = std::fs::remove_file(&part_path) {
+                    warn!("Failed to remove partial file: {e}");
+                }
+            }
+        }
+        if let Some(model_dir) = download.part_path.parent() {
+            // Shards finished before the cancel belong to no installed model
+            if !is_complete(model_dir) {
+                remove_files(model_dir, &download.shards);
             }
+            DownloadManifest::remove(model_dir);
         }
 
         // Emit cancellation event
@@ -572,7 +2727,12 @@ pub async fn cancel_download(
                 bytes_downloaded: download.bytes_downloaded,
                 total_bytes: download.total_bytes,
                 speed_bps: 0,
+                instant_speed_bps: 0,
+                average_speed_bps: 0,
                 eta_seconds: 0,
+                phase_percent: None,
+                retry_attempt: None,
+                stalled: false,
             },
         );
 
@@ -584,7 +2744,9 @@ pub async fn cancel_download(
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
 mod tests {
+    use super::super::test_server::{TestResponse, TestServer};
     use super::*;
 
     #[test]
@@ -594,23 +2756,1262 @@ mod tests {
             id: "test-123".to_string(),
             model_id: "phi-3-mini".to_string(),
             url: "https://example.com/model.gguf".to_string(),
+            resolved_url: "https://cdn.example.com/model.gguf?sig=abc".to_string(),
             tokenizer_url: "https://example.com/tokenizer.json".to_string(),
             file_path: PathBuf::from("/tmp/model.gguf"),
             part_path: PathBuf::from("/tmp/model.gguf.part"),
             bytes_downloaded: 500_000_000,
             total_bytes: 2_500_000_000,
+            speed: SpeedSample::default(),
+            eta_seconds: 0,
+            stalled: false,
             status: DownloadStatus::Downloading,
             cancel_token: Arc::new(tx),
             expected_hash: Some("abc123".to_string()),
+            tokenizer_hash: None,
+            headers: Vec::new(),
+            missing_credentials: Vec::new(),
+            shards: Vec::new(),
+            shard_hashes: Vec::new(),
+            mirrors: Vec::new(),
+            mirror_url: String::new(),
         };
 
-        let event = download.to_progress_event(10_000_000, 200);
+        let speed = SpeedSample {
+            instant_bps: 12_000_000,
+            average_bps: 10_000_000,
+        };
+        let event = download.to_progress_event(speed, 200);
 
         assert_eq!(event.download_id, "test-123");
         assert_eq!(event.model_id, "phi-3-mini");
         assert_eq!(event.status, "downloading");
         assert_eq!(event.bytes_downloaded, 500_000_000);
         assert_eq!(event.speed_bps, 10_000_000);
+        assert_eq!(event.instant_speed_bps, 12_000_000);
+    }
+
+    #[test]
+    fn test_to_request_prefers_resolved_url() {
+        let (tx, _rx) = watch::channel(false);
+        let download = Download {
+            id: "test-123".to_string(),
+            model_id: "phi-3-mini".to_string(),
+            url: "https://example.com/model.gguf".to_string(),
+            resolved_url: "https://cdn.example.com/model.gguf?sig=abc".to_string(),
+            tokenizer_url: "https://example.com/tokenizer.json".to_string(),
+            file_path: PathBuf::from("/tmp/model.gguf"),
+            part_path: PathBuf::from("/tmp/model.gguf.part"),
+            bytes_downloaded: 0,
+            total_bytes: 0,
+            speed: SpeedSample::default(),
+            eta_seconds: 0,
+            stalled: false,
+            status: DownloadStatus::Paused,
+            cancel_token: Arc::new(tx),
+            expected_hash: Some("abc123".to_string()),
+            tokenizer_hash: None,
+            headers: vec![("User-Agent".to_string(), "continuum/1.0".to_string())],
+            missing_credentials: Vec::new(),
+            shards: Vec::new(),
+            shard_hashes: Vec::new(),
+            mirrors: Vec::new(),
+            mirror_url: String::new(),
+        };
+
+        let request = download.to_request();
+        assert_eq!(request.url, "https://example.com/model.gguf");
+        assert_eq!(
+            request.resolved_url.as_deref(),
+            Some("https://cdn.example.com/model.gguf?sig=abc")
+        );
+        assert_eq!(request.expected_hash.as_deref(), Some("abc123"));
+        assert_eq!(request.headers, download.headers);
+    }
+
+    #[tokio::test]
+    async fn test_probe_follows_relative_redirect() {
+        let server = TestServer::start(|req| match req.path.as_str() {
+            "/model.gguf" => TestResponse::new(302).header("Location", "/signed/model.gguf?sig=1"),
+            "/signed/model.gguf?sig=1" => TestResponse::new(200).header("Content-Length", "4096"),
+            _ => TestResponse::new(404),
+        })
+        .await;
+
+        let probe = probe_download(
+            &reqwest::Client::new(),
+            &server.url("/model.gguf"),
+            &HeaderMap::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(probe.total_bytes, 4096);
+        assert_eq!(probe.resolved_url, server.url("/signed/model.gguf?sig=1"));
+        assert!(server.requests().iter().all(|r| r.method == "HEAD"));
+    }
+
+    #[tokio::test]
+    async fn test_probe_falls_back_to_ranged_get_when_head_rejected() {
+        let server = TestServer::start(|req| match (req.method.as_str(), req.header("range")) {
+            ("HEAD", _) => TestResponse::new(405),
+            ("GET", Some("bytes=0-0")) if req.path == "/model.gguf" => TestResponse::new(206)
+                .header("Content-Range", "bytes 0-0/8192")
+                .body(b"G"),
+            ("GET", _) => TestResponse::new(206)
+                .header("Content-Range", "bytes 0-0/*")
+                .body(b"G"),
+            _ => TestResponse::new(400),
+        })
+        .await;
+        let client = reqwest::Client::new();
+
+        let probe = probe_download(&client, &server.url("/model.gguf"), &HeaderMap::new())
+            .await
+            .unwrap();
+        assert_eq!(probe.total_bytes, 8192);
+
+        // Neither request reveals the size: carry on with an unknown total
+        let probe = probe_download(&client, &server.url("/stream.gguf"), &HeaderMap::new())
+            .await
+            .unwrap();
+        assert_eq!(probe.total_bytes, 0);
+
+        assert_eq!(parse_content_range_total("bytes 0-0/1234"), Some(1234));
+        assert_eq!(parse_content_range_total("bytes 0-0/*"), None);
+    }
+
+    #[tokio::test]
+    async fn test_download_without_content_length_streams_whole_file() {
+        let server =
+            TestServer::start(|_| TestResponse::new(200).body(b"gguf-bytes").unsized_body()).await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let client = reqwest::Client::new();
+        let url = server.url("/model.gguf");
+
+        let probe = probe_download(&client, &url, &HeaderMap::new())
+            .await
+            .unwrap();
+        assert_eq!(probe.total_bytes, 0);
+
+        let part = dir.path().join("model.gguf.part");
+        let mut bytes_downloaded = 0;
+        let (response, _file) = open_transfer(
+            &client,
+            &url,
+            &HeaderMap::new(),
+            &part,
+            &mut bytes_downloaded,
+            "chunked",
+        )
+        .await
+        .ok()
+        .unwrap();
+        assert_eq!(response.content_length(), None);
+        assert_eq!(response.bytes().await.unwrap().as_ref(), b"gguf-bytes");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests
+            .iter()
+            .all(|r| r.header("accept-encoding") == Some("identity")));
+    }
+
+    #[tokio::test]
+    async fn test_connectivity_check_detects_unreachable_host() {
+        let server = TestServer::start(|_| TestResponse::new(405)).await;
+        let client = reqwest::Client::new();
+
+        // Any HTTP response, even an error status, means the host is reachable
+        check_connectivity(&client, &server.url("/model.gguf"))
+            .await
+            .unwrap();
+
+        // Grab a free port and close it so nothing is listening there
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let err = check_connectivity(&client, &format!("http://127.0.0.1:{port}/model.gguf"))
+            .await
+            .unwrap_err();
+        assert!(err.offline);
+    }
+
+    #[test]
+    fn test_seeded_hasher_continues_where_the_part_ends() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let part = dir.path().join("model.gguf.part");
+        std::fs::write(&part, b"hello wor").unwrap();
+
+        let mut reported = Vec::new();
+        let mut hasher = seed_hasher(&part, 9, 4, |hashed| reported.push(hashed)).unwrap();
+        assert_eq!(reported, [4, 8, 9]);
+        hasher.update(b"ld");
+        let whole = format!("{:x}", Sha256::digest(b"hello world"));
+        assert_eq!(format!("{:x}", hasher.finalize()), whole);
+
+        // A .part shorter than its recorded offset can't seed the hash
+        assert!(seed_hasher(&part, 20, 4, |_| {}).is_none());
+        assert!(seed_hasher(&dir.path().join("missing.part"), 1, 4, |_| {}).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resumed_part_is_rehashed_with_progress() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let app = RecordedEvents::new(dir.path());
+        let part = DownloadPart {
+            url: String::new(),
+            mirror: String::new(),
+            fallbacks: Vec::new(),
+            part_path: dir.path().join("model.gguf.part"),
+            final_path: dir.path().join("model.gguf"),
+            total_bytes: 11,
+            bytes_downloaded: 9,
+            expected_hash: None,
+            done: false,
+        };
+        std::fs::write(&part.part_path, b"hello wor").unwrap();
+
+        let tuning = DownloadTuning::default();
+        let mut hasher = rehash_part(&app, &part, (9, 11), "dl-1", "phi-3", tuning)
+            .await
+            .unwrap();
+        hasher.update(b"ld");
+        let whole = format!("{:x}", Sha256::digest(b"hello world"));
+        assert_eq!(format!("{:x}", hasher.finalize()), whole);
+
+        let first = app.events().into_iter().next().unwrap();
+        assert_eq!(first.status, "rehashing");
+        assert_eq!(first.phase_percent, Some(0));
+        assert_eq!(first.bytes_downloaded, 9);
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_exponentially_up_to_cap() {
+        let delays: Vec<u64> = (1..=7).map(|n| retry_delay(n).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30]);
+        assert_eq!(retry_delay(u32::MAX), RETRY_MAX_DELAY);
+    }
+
+    #[test]
+    fn test_parse_sha256_sidecar_formats() {
+        let hash = "9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08";
+        let expected = Some(hash.to_lowercase());
+
+        assert_eq!(parse_sha256_sidecar(hash), expected);
+        assert_eq!(
+            parse_sha256_sidecar(&format!("{hash}  model.gguf\n")),
+            expected
+        );
+        assert_eq!(
+            parse_sha256_sidecar(&format!("\n{hash} *model.gguf")),
+            expected
+        );
+        assert_eq!(
+            parse_sha256_sidecar(&format!("SHA256 (model.gguf) = {hash}")),
+            expected
+        );
+
+        assert_eq!(parse_sha256_sidecar(""), None);
+        assert_eq!(parse_sha256_sidecar("not-a-hash  model.gguf"), None);
+        assert_eq!(parse_sha256_sidecar(&hash[..40]), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_text_sends_token_and_enforces_cap() {
+        let large = vec![b'x'; MAX_FETCH_TEXT_BYTES + 1];
+        let server = TestServer::start(move |req| match req.path.as_str() {
+            "/config.json" => TestResponse::new(200).body(br#"{"n_ctx":4096}"#),
+            "/huge.json" => TestResponse::new(200).body(&large),
+            _ => TestResponse::new(404),
+        })
+        .await;
+        let client = reqwest::Client::new();
+
+        let text = fetch_text(&client, &server.url("/config.json"), Some("hf_secret"))
+            .await
+            .unwrap();
+        assert_eq!(text, r#"{"n_ctx":4096}"#);
+        assert_eq!(
+            server.requests()[0].header("authorization"),
+            Some("Bearer hf_secret")
+        );
+
+        assert!(fetch_text(&client, &server.url("/huge.json"), None)
+            .await
+            .is_err());
+        assert!(fetch_text(&client, &server.url("/missing.json"), None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_expected_hash_from_sidecar() {
+        let hash = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        let body = format!("{hash}  model.gguf\n");
+        let server = TestServer::start(move |req| match req.path.as_str() {
+            "/model.gguf.sha256" => TestResponse::new(200).body(body.as_bytes()),
+            _ => TestResponse::new(404),
+        })
+        .await;
+        let client = reqwest::Client::new();
+
+        let fetched = fetch_expected_hash(&client, &server.url("/model.gguf.sha256"))
+            .await
+            .unwrap();
+        assert_eq!(fetched, hash);
+
+        assert!(fetch_expected_hash(&client, &server.url("/missing.sha256"))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_remove_part_file_keeps_completed_model() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("model.gguf"), b"done").unwrap();
+        std::fs::write(dir.path().join("model.gguf.part"), b"do").unwrap();
+
+        assert!(remove_part_files(dir.path(), &[]).unwrap());
+        assert!(!dir.path().join("model.gguf.part").exists());
+        assert!(dir.path().join("model.gguf").exists());
+
+        // Nothing left to delete
+        assert!(!remove_part_files(dir.path(), &[]).unwrap());
+    }
+
+    /// A tracked download for `model_id`, as `start_download` leaves it
+    async fn add_running_download(state: &DownloadState, model_id: &str) -> String {
+        let model_dir = state.models_dir().join(model_id);
+        std::fs::create_dir_all(&model_dir).unwrap();
+        let manifest = DownloadManifest {
+            url: "https://example.com/model.gguf".to_string(),
+            resolved_url: String::new(),
+            tokenizer_url: String::new(),
+            total_bytes: 4,
+            expected_hash: None,
+            tokenizer_hash: None,
+            shard_hashes: Vec::new(),
+            mirrors: Vec::new(),
+            headers: StoredHeaders::default(),
+            bytes_downloaded: 0,
+        };
+        let download = Download::from_manifest(model_id, &model_dir, manifest, 0);
+        let id = download.id.clone();
+        state.add_download(download).await;
+        state.update_status(&id, DownloadStatus::Downloading).await;
+        id
+    }
+
+    #[tokio::test]
+    async fn test_model_can_be_deleted_right_after_download_completes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let state = DownloadState::new(dir.path().to_path_buf());
+        let id = add_running_download(&state, "phi-3").await;
+        assert!(delete_partial_download(&state, "phi-3").await.is_err());
+
+        record_outcome(&state, &id, &Ok(())).await;
+        assert!(!state.has_active_download("phi-3").await);
+        assert_eq!(
+            state.get_download(&id).await.unwrap().status,
+            DownloadStatus::Completed
+        );
+        assert!(delete_partial_download(&state, "phi-3").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failed_and_offline_downloads_stop_counting_as_active() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let state = DownloadState::new(dir.path().to_path_buf());
+
+        let failed = add_running_download(&state, "phi-3").await;
+        record_outcome(&state, &failed, &Err("HTTP error: 404".to_string())).await;
+        let status = state.get_download(&failed).await.unwrap().status;
+        assert_eq!(status, DownloadStatus::Failed);
+
+        let offline = add_running_download(&state, "llama-3").await;
+        record_outcome(&state, &offline, &Err(OFFLINE.to_string())).await;
+        let status = state.get_download(&offline).await.unwrap().status;
+        assert_eq!(status, DownloadStatus::Paused);
+
+        // Pause already set the status; the cancelled task leaves it alone
+        let paused = add_running_download(&state, "qwen").await;
+        state.update_status(&paused, DownloadStatus::Paused).await;
+        record_outcome(&state, &paused, &Err("cancelled".to_string())).await;
+        let status = state.get_download(&paused).await.unwrap().status;
+        assert_eq!(status, DownloadStatus::Paused);
+
+        for model_id in ["phi-3", "llama-3", "qwen"] {
+            assert!(!state.has_active_download(model_id).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_that_went_offline_can_be_resumed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let state = DownloadState::new(dir.path().to_path_buf());
+        let id = add_running_download(&state, "phi-3").await;
+        assert_eq!(
+            state.take_paused(&id).await.unwrap_err(),
+            "Download is not paused"
+        );
+
+        record_outcome(&state, &id, &Err(OFFLINE.to_string())).await;
+        let download = state.take_paused(&id).await.unwrap();
+        assert_eq!(download.to_request().url, "https://example.com/model.gguf");
+        // Resuming starts a fresh entry in its place
+        assert!(state.get_download(&id).await.is_none());
+        assert_eq!(
+            state.take_paused(&id).await.unwrap_err(),
+            "Download not found"
+        );
+    }
+
+    /// Records what a transfer reports, in place of the app
+    struct RecordedEvents {
+        state: DownloadState,
+        events: std::sync::Mutex<Vec<DownloadProgressEvent>>,
+    }
+
+    impl RecordedEvents {
+        fn new(data_dir: &Path) -> Self {
+            Self {
+                state: DownloadState::new(data_dir.to_path_buf()),
+                events: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+
+        fn events(&self) -> Vec<DownloadProgressEvent> {
+            self.events.lock().unwrap().clone()
+        }
+    }
+
+    impl TransferEvents for RecordedEvents {
+        fn emit_progress(&self, event: DownloadProgressEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+
+        fn download_state(&self) -> &DownloadState {
+            &self.state
+        }
+    }
+
+    #[tokio::test]
+    async fn test_silent_connection_is_reported_as_stalled() {
+        let server = TestServer::start(|_| {
+            TestResponse::new(200)
+                .body(b"abcdefgh")
+                .pause_after(4, Duration::from_millis(600))
+        })
+        .await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let app = RecordedEvents::new(dir.path());
+        let id = add_running_download(&app.state, "phi-3").await;
+        let tuning = DownloadTuning {
+            stall_check_interval: Duration::from_millis(50),
+            stall_threshold: Duration::from_millis(150),
+            ..DownloadTuning::default()
+        };
+        let (_cancel_tx, cancel_rx) = watch::channel(false);
+
+        let transferred = transfer(
+            &app,
+            &reqwest::Client::new(),
+            &server.url("/model.gguf"),
+            &HeaderMap::new(),
+            &dir.path().join("model.gguf.part"),
+            0,
+            0,
+            8,
+            &id,
+            "phi-3",
+            None,
+            tuning,
+            &cancel_rx,
+        );
+        let polled = async {
+            tokio::time::sleep(Duration::from_millis(400)).await;
+            app.state
+                .get_download(&id)
+                .await
+                .unwrap()
+                .live_progress_event()
+        };
+        let (transferred, polled) = tokio::join!(transferred, polled);
+        assert_eq!(transferred.ok(), Some(8));
+        // Polling agrees with the events sent during the silence
+        assert_eq!(polled.bytes_downloaded, 4);
+        assert!(polled.stalled);
+
+        let events = app.events();
+        let silent: Vec<_> = events.iter().filter(|e| e.bytes_downloaded == 4).collect();
+        assert!(silent.len() > 1, "progress repeats while no data arrives");
+        assert!(!silent[0].stalled, "not stalled before the threshold");
+        assert!(silent.iter().any(|e| e.stalled));
+        // Reported right away once data flows again
+        let last = events.last().unwrap();
+        assert_eq!(last.bytes_downloaded, 8);
+        assert!(!last.stalled);
+        let download = app.state.get_download(&id).await.unwrap();
+        assert!(!download.live_progress_event().stalled);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_transfer_resumes_with_range_after_retry() {
+        const BODY: &[u8] = b"abcdefgh";
+        let server = TestServer::start(|req| match req.header("Range") {
+            Some("bytes=4-") => TestResponse::new(206)
+                .header("Content-Range", "bytes 4-7/8")
+                .body(&BODY[4..]),
+            _ => TestResponse::new(200).body(BODY).cut_off_after(4),
+        })
+        .await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let app = RecordedEvents::new(dir.path());
+        let id = add_running_download(&app.state, "phi-3").await;
+        let part = DownloadPart {
+            url: server.url("/model.gguf"),
+            mirror: server.url("/model.gguf"),
+            fallbacks: Vec::new(),
+            part_path: dir.path().join("model.gguf.part"),
+            final_path: dir.path().join("model.gguf"),
+            total_bytes: 8,
+            bytes_downloaded: 0,
+            expected_hash: Some(format!("{:x}", Sha256::digest(BODY))),
+            done: false,
+        };
+        let tuning = DownloadTuning {
+            max_retries: 1,
+            ..DownloadTuning::default()
+        };
+        let (_cancel_tx, mut cancel_rx) = watch::channel(false);
+
+        let (bytes, computed_hash) = fetch_part(
+            &app,
+            &reqwest::Client::new(),
+            &HeaderMap::new(),
+            &part,
+            0,
+            8,
+            &id,
+            "phi-3",
+            tuning,
+            &mut cancel_rx,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(bytes, 8);
+        assert_eq!(std::fs::read(&part.part_path).unwrap(), BODY);
+        // The hash carried over the interruption
+        assert_eq!(computed_hash, part.expected_hash);
+        let ranges: Vec<Option<String>> = server
+            .requests()
+            .iter()
+            .map(|r| r.header("Range").map(str::to_string))
+            .collect();
+        assert_eq!(ranges, [None, Some("bytes=4-".to_string())]);
+        let retry = app.events().into_iter().find(|e| e.status == "retrying");
+        let retry = retry.unwrap();
+        assert_eq!(retry.retry_attempt, Some(1));
+        assert_eq!(retry.bytes_downloaded, 4);
+    }
+
+    #[test]
+    fn test_remove_part_files_of_split_model() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let shards = vec![
+            "m-00001-of-00002.gguf".to_string(),
+            "m-00002-of-00002.gguf".to_string(),
+        ];
+        std::fs::write(dir.path().join(&shards[0]), b"done").unwrap();
+        std::fs::write(dir.path().join("m-00002-of-00002.gguf.part"), b"do").unwrap();
+        assert!(has_partial_download(
+            dir.path(),
+            "https://example.com/m-00001-of-00002.gguf"
+        ));
+
+        assert!(remove_part_files(dir.path(), &shards).unwrap());
+        assert!(!dir.path().join("m-00002-of-00002.gguf.part").exists());
+        // Finished shards are only removed by cancel_download
+        assert!(dir.path().join(&shards[0]).exists());
+    }
+
+    #[tokio::test]
+    async fn test_plan_parts_covers_every_shard() {
+        let server =
+            TestServer::start(|_| TestResponse::new(200).header("Content-Length", "100")).await;
+        let dir = tempfile::TempDir::new().unwrap();
+        // Shard 1 finished before the app was closed
+        std::fs::write(dir.path().join("m-00001-of-00003.gguf"), vec![0u8; 50]).unwrap();
+
+        let mut request = DownloadRequest {
+            model_id: "big".to_string(),
+            url: server.url("/m-00001-of-00003.gguf"),
+            tokenizer_url: server.url("/tokenizer.json"),
+            expected_hash: None,
+            tokenizer_hash: None,
+            resolved_url: None,
+            headers: Vec::new(),
+            shard_hashes: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            mirrors: Vec::new(),
+        };
+        let client = reqwest::Client::new();
+        let parts = plan_parts(&client, &request, &HeaderMap::new(), dir.path(), &mut || {})
+            .await
+            .unwrap();
+
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].done);
+        assert_eq!(parts[0].total_bytes, 50);
+        assert!(!parts[1].done && !parts[2].done);
+        assert_eq!(parts[2].total_bytes, 100);
+        assert_eq!(parts[2].expected_hash.as_deref(), Some("c"));
+        assert_eq!(
+            parts[2].part_path,
+            dir.path().join("m-00003-of-00003.gguf.part")
+        );
+        // Only the unfinished shards were probed
+        let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(
+            paths,
+            vec!["/m-00002-of-00003.gguf", "/m-00003-of-00003.gguf"]
+        );
+
+        request.shard_hashes.pop();
+        assert!(
+            plan_parts(&client, &request, &HeaderMap::new(), dir.path(), &mut || {})
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_probe_falls_back_through_mirrors() {
+        let server = TestServer::start(|req| match req.path.as_str() {
+            "/primary/model.gguf" => TestResponse::new(503),
+            _ => TestResponse::new(200).header("Content-Length", "100"),
+        })
+        .await;
+        // Grab a free port and close it so nothing is listening there
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let unreachable = format!(
+            "http://127.0.0.1:{}/model.gguf",
+            listener.local_addr().unwrap().port()
+        );
+        drop(listener);
+
+        let mut switches = 0;
+        let (probe, mirror, remaining) = probe_mirrors(
+            &reqwest::Client::new(),
+            server.url("/primary/model.gguf"),
+            vec![
+                unreachable,
+                server.url("/mirror/model.gguf"),
+                server.url("/spare/model.gguf"),
+            ],
+            &HeaderMap::new(),
+            &mut || switches += 1,
+        )
+        .await
+        .ok()
+        .unwrap();
+
+        assert_eq!(switches, 2);
+        assert_eq!(mirror, server.url("/mirror/model.gguf"));
+        assert_eq!(probe.total_bytes, 100);
+        assert_eq!(remaining, vec![server.url("/spare/model.gguf")]);
+
+        // With every mirror failing, the last error is reported
+        let result = probe_mirrors(
+            &reqwest::Client::new(),
+            server.url("/primary/model.gguf"),
+            Vec::new(),
+            &HeaderMap::new(),
+            &mut || {},
+        )
+        .await;
+        assert!(result.is_err_and(|e| e.contains("503")));
+    }
+
+    #[tokio::test]
+    async fn test_probe_sends_custom_headers() {
+        let server =
+            TestServer::start(|_| TestResponse::new(200).header("Content-Length", "1")).await;
+        let headers = build_header_map(&[
+            ("User-Agent".to_string(), "continuum/1.0".to_string()),
+            ("Referer".to_string(), "https://example.com/".to_string()),
+        ])
+        .unwrap();
+
+        probe_download(
+            &reqwest::Client::new(),
+            &server.url("/model.gguf"),
+            &headers,
+        )
+        .await
+        .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("user-agent"), Some("continuum/1.0"));
+        assert_eq!(requests[0].header("referer"), Some("https://example.com/"));
+    }
+
+    #[test]
+    fn test_build_header_map_rejects_unsafe_headers() {
+        let header = |name: &str, value: &str| vec![(name.to_string(), value.to_string())];
+
+        assert!(build_header_map(&header("Host", "evil.example")).is_err());
+        assert!(build_header_map(&header("range", "bytes=0-")).is_err());
+        assert!(build_header_map(&header("X-Token", "a\r\nInjected: 1")).is_err());
+        assert!(build_header_map(&header("Bad Name", "x")).is_err());
+        assert!(build_header_map(&header("X-Token", "abc")).is_ok());
+    }
+
+    #[test]
+    fn test_auth_header_values_are_redacted_in_logs() {
+        let headers = build_header_map(&[
+            ("Authorization".to_string(), "Bearer hf_secret".to_string()),
+            ("User-Agent".to_string(), "continuum/1.0".to_string()),
+        ])
+        .unwrap();
+
+        let logged = format!("{headers:?}");
+        assert!(!logged.contains("hf_secret"));
+        assert!(logged.contains("continuum/1.0"));
+        // Redaction only affects logging, not what is sent
+        assert_eq!(headers["authorization"], "Bearer hf_secret");
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_sent_on_every_download_request() {
+        let server = TestServer::start(|req| match (req.method.as_str(), req.path.as_str()) {
+            (_, "/tokenizer.json") => TestResponse::new(200).body(b"{}"),
+            ("HEAD", _) => TestResponse::new(200).header("Content-Length", "6"),
+            _ => TestResponse::new(206).body(b"def"),
+        })
+        .await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let client = reqwest::Client::new();
+        let headers =
+            build_header_map(&[("Authorization".to_string(), "Bearer hf_secret".to_string())])
+                .unwrap();
+
+        download_tokenizer(
+            &client,
+            &server.url("/tokenizer.json"),
+            &headers,
+            dir.path(),
+            None,
+        )
+        .await
+        .unwrap();
+        probe_download(&client, &server.url("/model.gguf"), &headers)
+            .await
+            .unwrap();
+        let part = dir.path().join("model.gguf.part");
+        std::fs::write(&part, b"abc").unwrap();
+        let mut bytes_downloaded = 3;
+        let _ = open_transfer(
+            &client,
+            &server.url("/model.gguf"),
+            &headers,
+            &part,
+            &mut bytes_downloaded,
+            "gated",
+        )
+        .await
+        .ok()
+        .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests
+            .iter()
+            .all(|r| r.header("authorization") == Some("Bearer hf_secret")));
+        assert_eq!(requests[2].header("range"), Some("bytes=3-"));
+    }
+
+    #[tokio::test]
+    async fn test_tokenizer_checked_against_expected_hash() {
+        let server = TestServer::start(|_| TestResponse::new(200).body(b"{}")).await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let client = reqwest::Client::new();
+        let url = server.url("/tokenizer.json");
+        let tokenizer = dir.path().join("tokenizer.json");
+        let good = "44136FA355B3678A1146AD16F7E8649E94FB4FC21FE77E8310C060F61CAAFF8A";
+
+        download_tokenizer(&client, &url, &HeaderMap::new(), dir.path(), Some(good))
+            .await
+            .unwrap();
+        assert!(tokenizer.exists());
+
+        // The existing file no longer matches, so it is fetched again and rejected
+        let err = download_tokenizer(&client, &url, &HeaderMap::new(), dir.path(), Some("00"))
+            .await
+            .unwrap_err();
+        assert!(err.starts_with(TOKENIZER_CORRUPTED));
+        assert!(NetworkError::from(err).tokenizer_corrupted);
+        assert!(!tokenizer.exists());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    /// Serve `body`, honouring single `bytes=a-b` / `bytes=a-` Range requests
+    fn ranged(body: &'static [u8], range: Option<&str>) -> TestResponse {
+        let Some(spec) = range.and_then(|r| r.strip_prefix("bytes=")) else {
+            return TestResponse::new(200).body(body);
+        };
+        let (start, end) = spec.split_once('-').unwrap();
+        let start: usize = start.parse().unwrap();
+        let end = end.parse::<usize>().map_or(body.len(), |end| end + 1);
+        TestResponse::new(206).body(&body[start..end])
+    }
+
+    #[tokio::test]
+    async fn test_validate_partial_restarts_torn_or_oversized_part() {
+        static BODY: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+        let server = TestServer::start(|req| ranged(BODY, req.header("range"))).await;
+        let client = reqwest::Client::new();
+        let url = server.url("/model.gguf");
+        let dir = tempfile::TempDir::new().unwrap();
+        let part = dir.path().join("model.gguf.part");
+        let total = BODY.len() as u64;
+
+        // A sound prefix is resumed as-is
+        std::fs::write(&part, &BODY[..20]).unwrap();
+        let resume_from =
+            validate_partial(&client, &url, &HeaderMap::new(), &part, 20, total).await;
+        assert_eq!(resume_from, 20);
+        assert!(part.exists());
+
+        // A torn write (zeros where data should be) restarts the download
+        std::fs::write(&part, b"0123456789abcdef\0\0\0\0").unwrap();
+        let resume_from =
+            validate_partial(&client, &url, &HeaderMap::new(), &part, 20, total).await;
+        assert_eq!(resume_from, 0);
+        assert!(!part.exists());
+
+        // So does a .part larger than the remote file
+        std::fs::write(&part, [b'x'; 40]).unwrap();
+        let resume_from =
+            validate_partial(&client, &url, &HeaderMap::new(), &part, 40, total).await;
+        assert_eq!(resume_from, 0);
+        assert!(!part.exists());
+    }
+
+    #[tokio::test]
+    async fn test_open_transfer_restarts_when_range_is_ignored() {
+        let server = TestServer::start(|_| TestResponse::new(200).body(b"whole file")).await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let part = dir.path().join("model.gguf.part");
+        std::fs::write(&part, b"abc").unwrap();
+
+        let mut bytes_downloaded = 3;
+        let (response, file) = open_transfer(
+            &reqwest::Client::new(),
+            &server.url("/model.gguf"),
+            &HeaderMap::new(),
+            &part,
+            &mut bytes_downloaded,
+            "phi-3",
+        )
+        .await
+        .ok()
+        .unwrap();
+        drop(file);
+
+        assert_eq!(server.requests()[0].header("range"), Some("bytes=3-"));
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        // The stale partial is dropped rather than appended to
+        assert_eq!(bytes_downloaded, 0);
+        assert_eq!(std::fs::metadata(&part).unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_open_transfer_appends_on_partial_content() {
+        let server = TestServer::start(|_| TestResponse::new(206).body(b"def")).await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let part = dir.path().join("model.gguf.part");
+        std::fs::write(&part, b"abc").unwrap();
+
+        let mut bytes_downloaded = 3;
+        let (_response, mut file) = open_transfer(
+            &reqwest::Client::new(),
+            &server.url("/model.gguf"),
+            &HeaderMap::new(),
+            &part,
+            &mut bytes_downloaded,
+            "phi-3",
+        )
+        .await
+        .ok()
+        .unwrap();
+        file.write_all(b"def").unwrap();
+        drop(file);
+
+        assert_eq!(bytes_downloaded, 3);
+        assert_eq!(std::fs::read(&part).unwrap(), b"abcdef");
+    }
+
+    #[tokio::test]
+    async fn test_validate_partial_trusts_part_without_range_support() {
+        let server = TestServer::start(|_| TestResponse::new(200).body(b"different")).await;
+        let dir = tempfile::TempDir::new().unwrap();
+        let part = dir.path().join("model.gguf.part");
+        std::fs::write(&part, b"abc").unwrap();
+
+        let resume_from = validate_partial(
+            &reqwest::Client::new(),
+            &server.url("/model.gguf"),
+            &HeaderMap::new(),
+            &part,
+            3,
+            9,
+        )
+        .await;
+        assert_eq!(resume_from, 3);
+    }
+
+    #[test]
+    fn test_hash_with_progress_stops_on_cancel() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("model.gguf.part");
+        std::fs::write(&path, b"test").unwrap();
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+
+        let (hash, bytes) = hash_with_progress(&path, 2, &cancel_rx, |_| {}).unwrap();
+        assert_eq!(
+            hash,
+            "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+        );
+        assert_eq!(bytes, 4);
+
+        cancel_tx.send(true).unwrap();
+        let err = hash_with_progress(&path, 2, &cancel_rx, |_| {}).unwrap_err();
+        assert_eq!(err.kind, "cancelled");
+    }
+
+    #[test]
+    fn test_evict_quarantine_removes_oldest_first() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for (stem, size) in [
+            ("zephyr_20250101_120000", 40),
+            ("phi-3_20250201_120000", 40),
+            ("alpha_20250301_120000", 40),
+        ] {
+            std::fs::write(
+                dir.path().join(format!("{stem}.gguf.corrupted")),
+                vec![0u8; size],
+            )
+            .unwrap();
+            std::fs::write(dir.path().join(format!("{stem}.json")), "{}").unwrap();
+        }
+
+        // Already fits: nothing to evict
+        assert!(evict_quarantine(dir.path(), 30, 150).is_empty());
+
+        // 120 + 50 > 100: the two oldest by timestamp go, with their records
+        let evicted = evict_quarantine(dir.path(), 50, 100);
+        assert_eq!(evicted, ["zephyr_20250101_120000", "phi-3_20250201_120000"]);
+        assert!(!dir.path().join("zephyr_20250101_120000.json").exists());
+        assert!(dir
+            .path()
+            .join("alpha_20250301_120000.gguf.corrupted")
+            .exists());
+    }
+
+    #[test]
+    fn test_find_and_remove_quarantine_record() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let record = |model_id: &str, hash: &str| QuarantineRecord {
+            model_id: model_id.to_string(),
+            url: format!("https://example.com/{model_id}.gguf"),
+            tokenizer_url: "https://example.com/tokenizer.json".to_string(),
+            expected_hash: hash.to_string(),
+            actual_hash: "bad".to_string(),
+            tokenizer_hash: None,
+            shard_hashes: Vec::new(),
+            mirrors: Vec::new(),
+            headers: StoredHeaders::default(),
+        };
+        write_quarantine_record(dir.path(), "phi-3_20250101_120000", &record("phi-3", "old"));
+        write_quarantine_record(dir.path(), "phi-3_20250301_120000", &record("phi-3", "new"));
+        write_quarantine_record(
+            dir.path(),
+            "phi-3_x_20250401_120000",
+            &record("phi-3_x", "x"),
+        );
+        std::fs::write(
+            dir.path().join("phi-3_20250301_120000.gguf.corrupted"),
+            b"bad",
+        )
+        .unwrap();
+
+        let (stem, found) = find_quarantine_record(dir.path(), "phi-3").unwrap();
+        assert_eq!(stem, "phi-3_20250301_120000");
+        assert_eq!(found, record("phi-3", "new"));
+        assert!(find_quarantine_record(dir.path(), "llama-3").is_none());
+
+        remove_quarantined(dir.path(), &stem).unwrap();
+        assert!(!dir
+            .path()
+            .join("phi-3_20250301_120000.gguf.corrupted")
+            .exists());
+        assert!(!dir.path().join("phi-3_20250301_120000.json").exists());
+        assert_eq!(
+            find_quarantine_record(dir.path(), "phi-3").unwrap().1,
+            record("phi-3", "old")
+        );
+    }
+
+    #[test]
+    fn test_read_quarantine_record_by_file_id() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let record = QuarantineRecord {
+            model_id: "phi-3".to_string(),
+            url: "https://example.com/phi-3.gguf".to_string(),
+            tokenizer_url: "https://example.com/tokenizer.json".to_string(),
+            expected_hash: "abc".to_string(),
+            actual_hash: "bad".to_string(),
+            tokenizer_hash: Some("def".to_string()),
+            shard_hashes: vec!["s1".to_string(), "s2".to_string()],
+            mirrors: vec!["https://mirror.example.com/phi-3.gguf".to_string()],
+            headers: StoredHeaders::new(&[("User-Agent".to_string(), "continuum".to_string())]),
+        };
+        write_quarantine_record(dir.path(), "phi-3_20250101_120000", &record);
+        for stem in ["phi-3_20250101_120000", "llama-3_20250101_120000"] {
+            std::fs::write(dir.path().join(format!("{stem}.gguf.corrupted")), b"bad").unwrap();
+        }
+
+        // IDs from list_quarantined_files keep the .gguf of the file stem
+        let (stem, found) =
+            read_quarantine_record(dir.path(), "phi-3_20250101_120000.gguf").unwrap();
+        assert_eq!(stem, "phi-3_20250101_120000");
+        assert_eq!(found, record);
+
+        let err = read_quarantine_record(dir.path(), "llama-3_20250101_120000.gguf").unwrap_err();
+        assert!(err.contains("manually"));
+        assert!(
+            read_quarantine_record(dir.path(), "gone_20250101_120000.gguf")
+                .unwrap_err()
+                .contains("not found")
+        );
+        assert!(read_quarantine_record(dir.path(), "../phi-3_20250101_120000").is_err());
+    }
+
+    #[test]
+    fn test_quarantine_request_replays_source() {
+        let header = |name: &str, value: &str| (name.to_string(), value.to_string());
+        let record = QuarantineRecord {
+            model_id: "phi-3".to_string(),
+            url: "https://example.com/phi-3-00001-of-00002.gguf".to_string(),
+            tokenizer_url: "https://example.com/tokenizer.json".to_string(),
+            expected_hash: "abc".to_string(),
+            actual_hash: "bad".to_string(),
+            tokenizer_hash: None,
+            shard_hashes: vec!["s1".to_string(), "s2".to_string()],
+            mirrors: vec!["https://mirror.example.com/phi-3-00001-of-00002.gguf".to_string()],
+            headers: StoredHeaders::new(&[
+                header("User-Agent", "continuum"),
+                header("Authorization", "Bearer hf_secret"),
+            ]),
+        };
+        // The token itself never reaches the sidecar
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(!json.contains("hf_secret"));
+        assert!(json.contains("Authorization"));
+
+        let err = quarantine_request(record.clone(), Vec::new()).unwrap_err();
+        assert!(err.contains("Authorization"));
+
+        let request = quarantine_request(
+            record.clone(),
+            vec![header("authorization", "Bearer hf_new")],
+        )
+        .unwrap();
+        assert_eq!(request.expected_hash.as_deref(), Some("abc"));
+        assert_eq!(request.shard_hashes, record.shard_hashes);
+        assert_eq!(request.mirrors, record.mirrors);
+        assert_eq!(
+            request.headers,
+            [
+                header("User-Agent", "continuum"),
+                header("authorization", "Bearer hf_new")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_local_model_places_files() {
+        let source = tempfile::TempDir::new().unwrap();
+        let models = tempfile::TempDir::new().unwrap();
+        let gguf = source.path().join("phi-3-q4.gguf");
+        let tokenizer = source.path().join("tokenizer.json");
+        std::fs::write(&gguf, b"test").unwrap();
+        std::fs::write(&tokenizer, b"{}").unwrap();
+
+        // SHA-256("test")
+        let hash = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        let dir =
+            import_local_model(models.path(), "phi-3", &gguf, &tokenizer, Some(hash)).unwrap();
+
+        // The original weights filename is kept and recorded in metadata
+        assert_eq!(weights_path(&dir), dir.join("phi-3-q4.gguf"));
+        assert!(dir.join("phi-3-q4.gguf").exists());
+        assert!(dir.join("tokenizer.json").exists());
+
+        // Importing over an existing model is refused
+        assert!(import_local_model(models.path(), "phi-3", &gguf, &tokenizer, None).is_err());
+    }
+
+    #[test]
+    fn test_import_local_model_cleans_up_on_hash_mismatch() {
+        let source = tempfile::TempDir::new().unwrap();
+        let models = tempfile::TempDir::new().unwrap();
+        let gguf = source.path().join("model.gguf");
+        let tokenizer = source.path().join("tokenizer.json");
+        std::fs::write(&gguf, b"corrupted").unwrap();
+        std::fs::write(&tokenizer, b"{}").unwrap();
+
+        let result = import_local_model(
+            models.path(),
+            "phi-3",
+            &gguf,
+            &tokenizer,
+            Some(&"0".repeat(64)),
+        );
+
+        assert!(result.is_err());
+        assert!(!models.path().join("phi-3").exists());
+    }
+
+    #[test]
+    fn test_model_readiness_reports_partial_and_missing_files() {
+        let models = tempfile::TempDir::new().unwrap();
+        let model_dir = models.path().join("phi-3");
+        std::fs::create_dir_all(&model_dir).unwrap();
+        std::fs::write(model_dir.join("model.gguf"), b"test").unwrap();
+        std::fs::write(model_dir.join("model.gguf.part"), b"te").unwrap();
+
+        let readiness = check_model_readiness(models.path(), "phi-3");
+
+        assert!(!readiness.ready);
+        assert!(readiness.has_model);
+        assert!(!readiness.has_tokenizer);
+        assert!(readiness.has_partial);
+        assert_eq!(readiness.checksum_verified, None);
+        assert_eq!(readiness.reasons.len(), 2);
+    }
+
+    #[test]
+    fn test_list_installed_models_marks_partial_downloads_incomplete() {
+        let source = tempfile::TempDir::new().unwrap();
+        let models = tempfile::TempDir::new().unwrap();
+        let gguf = source.path().join("model.gguf");
+        let tokenizer = source.path().join("tokenizer.json");
+        std::fs::write(&gguf, b"test").unwrap();
+        std::fs::write(&tokenizer, b"{}").unwrap();
+        import_local_model(models.path(), "phi-3", &gguf, &tokenizer, None).unwrap();
+        let partial = models.path().join("llama");
+        std::fs::create_dir_all(&partial).unwrap();
+        std::fs::write(partial.join("model.gguf.part"), b"abc").unwrap();
+        // Stray files beside the model directories are not models
+        std::fs::write(models.path().join(".complete-markers-migrated"), b"").unwrap();
+
+        let installed = list_installed_models(models.path());
+
+        assert_eq!(installed.len(), 2);
+        assert_eq!(installed[0].model_id, "llama");
+        assert_eq!(installed[0].size_bytes, 3);
+        assert!(!installed[0].complete && !installed[0].has_tokenizer);
+        assert!(installed[0].modified_ms.is_some());
+        assert_eq!(installed[1].model_id, "phi-3");
+        assert!(installed[1].complete && installed[1].has_tokenizer);
+        assert!(installed[1].size_bytes >= 6);
+    }
+
+    #[test]
+    fn test_storage_usage_splits_out_partial_and_quarantined_bytes() {
+        let models = tempfile::TempDir::new().unwrap();
+        let quarantine = tempfile::TempDir::new().unwrap();
+        let phi = models.path().join("phi-3");
+        let llama = models.path().join("llama");
+        std::fs::create_dir_all(&phi).unwrap();
+        std::fs::create_dir_all(&llama).unwrap();
+        std::fs::write(phi.join("model.gguf"), vec![0u8; 100]).unwrap();
+        std::fs::write(phi.join("tokenizer.json"), vec![0u8; 10]).unwrap();
+        std::fs::write(llama.join("model.gguf.part"), vec![0u8; 40]).unwrap();
+        std::fs::write(quarantine.path().join("x_1.gguf.corrupted"), vec![0u8; 7]).unwrap();
+
+        let usage = storage_usage(models.path(), quarantine.path());
+
+        assert_eq!(usage.total_bytes, 150);
+        assert_eq!(
+            usage.per_model,
+            vec![("llama".to_string(), 40), ("phi-3".to_string(), 110)]
+        );
+        assert_eq!(usage.partial_bytes, 40);
+        assert_eq!(usage.quarantine_bytes, 7);
+    }
+
+    #[test]
+    fn test_cleanup_orphaned_downloads_is_dry_run_until_confirmed() {
+        let models = tempfile::TempDir::new().unwrap();
+        let quarantine = tempfile::TempDir::new().unwrap();
+        let crashed = models.path().join("crashed");
+        let paused = models.path().join("paused");
+        let installed = models.path().join("installed");
+        for dir in [&crashed, &paused, &installed, &models.path().join("empty")] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        std::fs::write(crashed.join("model.gguf.part"), b"abc").unwrap();
+        std::fs::write(crashed.join(DOWNLOAD_MANIFEST_FILE), b"{}").unwrap();
+        std::fs::write(paused.join("model.gguf.part"), b"abc").unwrap();
+        std::fs::write(installed.join("model.gguf"), b"test").unwrap();
+        std::fs::write(installed.join("model.gguf.part"), b"te").unwrap();
+        std::fs::write(quarantine.path().join("x_1.gguf.corrupted"), b"bad").unwrap();
+        let tracked = vec!["paused".to_string()];
+
+        let orphans =
+            cleanup_orphaned_downloads(models.path(), quarantine.path(), &tracked, false).unwrap();
+        let ids: Vec<&str> = orphans.iter().map(|o| o.model_id.as_str()).collect();
+        assert_eq!(ids, ["crashed", "empty"]);
+        assert_eq!(orphans[0].bytes, 5);
+        assert_eq!(orphans[0].files, [".download.json", "model.gguf.part"]);
+        assert!(crashed.exists());
+
+        cleanup_orphaned_downloads(models.path(), quarantine.path(), &tracked, true).unwrap();
+        assert!(!crashed.exists());
+        assert!(!models.path().join("empty").exists());
+        assert!(paused.join("model.gguf.part").exists());
+        assert!(installed.join("model.gguf").exists());
+        assert!(quarantine.path().join("x_1.gguf.corrupted").exists());
+    }
+
+    #[test]
+    fn test_model_readiness_rechecks_stored_hash() {
+        let source = tempfile::TempDir::new().unwrap();
+        let models = tempfile::TempDir::new().unwrap();
+        let gguf = source.path().join("model.gguf");
+        let tokenizer = source.path().join("tokenizer.json");
+        std::fs::write(&gguf, b"test").unwrap();
+        std::fs::write(&tokenizer, b"{}").unwrap();
+        let hash = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        import_local_model(models.path(), "phi-3", &gguf, &tokenizer, Some(hash)).unwrap();
+
+        let readiness = check_model_readiness(models.path(), "phi-3");
+        assert!(readiness.ready);
+        assert_eq!(readiness.checksum_verified, Some(true));
+
+        // Replace rather than write through, in case the import hard-linked
+        let model_path = models.path().join("phi-3").join("model.gguf");
+        std::fs::remove_file(&model_path).unwrap();
+        std::fs::write(&model_path, b"tampered").unwrap();
+
+        let readiness = check_model_readiness(models.path(), "phi-3");
+        assert!(!readiness.ready);
+        assert_eq!(readiness.checksum_verified, Some(false));
     }
 
     #[test]
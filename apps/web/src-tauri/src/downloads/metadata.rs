@@ -0,0 +1,353 @@
+//! Per-model metadata stored alongside the weights
+//!
+//! Records the actual weights filename so imported or multi-quant models
+//! (e.g. `phi-3-q4.gguf`) don't have to be renamed to `model.gguf`.
+//!
+//! File structure:
+//! ```text
+//! models/{model_id}/
+//!   model.json       <- this metadata
+//!   {weights_file}   <- main model weights (model.gguf for downloads)
+//!   {shards}         <- every shard of a split model, weights_file first
+//!   tokenizer.json   <- tokenizer for the model
+//!   model.sha256     <- SHA-256 the weights were verified against, if known
+//!   .complete        <- written last, once every file is in place
+//!   .download.json   <- manifest of an unfinished download (removed when done)
+//! ```
+
+use super::state::StoredHeaders;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Metadata file name inside a model directory
+const METADATA_FILE: &str = "model.json";
+
+/// Sentinel marking a model directory as fully finalized
+const COMPLETE_MARKER: &str = ".complete";
+
+/// Models-dir flag recording that pre-sentinel installs were migrated
+const MARKERS_MIGRATED: &str = ".complete-markers-migrated";
+
+/// Manifest of an in-progress download, so it survives an app restart
+pub const DOWNLOAD_MANIFEST_FILE: &str = ".download.json";
+
+/// Sidecar next to the weights holding the SHA-256 they were verified against
+pub const HASH_FILE: &str = "model.sha256";
+
+/// Weights filename used by downloads and installs without metadata
+pub const DEFAULT_WEIGHTS_FILE: &str = "model.gguf";
+
+/// Metadata persisted for each installed model
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelMetadata {
+    /// File name of the GGUF weights inside the model directory
+    pub weights_file: String,
+    /// Every file of a split model in order, empty for a single file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shards: Vec<String>,
+}
+
+impl ModelMetadata {
+    /// Metadata for weights stored under `weights_file`
+    pub fn new(weights_file: &str) -> Self {
+        Self {
+            weights_file: weights_file.to_string(),
+            shards: Vec::new(),
+        }
+    }
+
+    /// Metadata for a split model, `weights_file` naming its first shard
+    pub fn sharded(shards: Vec<String>) -> Self {
+        Self {
+            weights_file: shards.first().cloned().unwrap_or_default(),
+            shards,
+        }
+    }
+
+    /// Read a model's metadata, `None` if missing, malformed, or unsafe
+    pub fn load(model_dir: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(model_dir.join(METADATA_FILE)).ok()?;
+        let metadata: Self = serde_json::from_str(&contents)
+            .map_err(|e| warn!("Ignoring malformed {}: {e}", model_dir.display()))
+            .ok()?;
+
+        // Must name a file inside the model directory, never a path out of it
+        let safe = is_plain_file_name(&metadata.weights_file)
+            && metadata
+                .shards
+                .iter()
+                .all(|shard| is_plain_file_name(shard));
+        safe.then_some(metadata)
+    }
+
+    /// Write the metadata into a model directory
+    pub fn save(&self, model_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize model metadata: {e}"))?;
+        std::fs::write(model_dir.join(METADATA_FILE), json)
+            .map_err(|e| format!("Failed to save model metadata: {e}"))
+    }
+}
+
+/// What's needed to resume a download after the app restarts
+///
+/// Credential header values (auth tokens) are deliberately not stored; a
+/// recovered download that needs them must be resumed with `resume_from_disk`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadManifest {
+    pub url: String,
+    /// Final URL after redirects, retried first on resume
+    pub resolved_url: String,
+    pub tokenizer_url: String,
+    pub total_bytes: u64,
+    pub expected_hash: Option<String>,
+    #[serde(default)]
+    pub tokenizer_hash: Option<String>,
+    /// Per-shard SHA-256 hashes of a split model, in shard order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shard_hashes: Vec<String>,
+    /// Fallback URLs tried in order when `url` fails
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+    /// Custom headers, without credential values
+    #[serde(default)]
+    pub headers: StoredHeaders,
+    /// Progress when last saved; the .part size on disk is authoritative
+    pub bytes_downloaded: u64,
+}
+
+impl DownloadManifest {
+    /// Read a model's download manifest, `None` if missing or malformed
+    pub fn load(model_dir: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(model_dir.join(DOWNLOAD_MANIFEST_FILE)).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(manifest) => Some(manifest),
+            Err(e) => {
+                warn!(
+                    "Ignoring malformed manifest in {}: {e}",
+                    model_dir.display()
+                );
+                None
+            },
+        }
+    }
+
+    /// Write the manifest into a model directory
+    pub fn save(&self, model_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize download manifest: {e}"))?;
+        std::fs::write(model_dir.join(DOWNLOAD_MANIFEST_FILE), json)
+            .map_err(|e| format!("Failed to save download manifest: {e}"))
+    }
+
+    /// Delete the manifest once the download finishes or is abandoned
+    pub fn remove(model_dir: &Path) {
+        match std::fs::remove_file(model_dir.join(DOWNLOAD_MANIFEST_FILE)) {
+            Ok(()) => {},
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+            Err(e) => warn!("Failed to remove download manifest: {e}"),
+        }
+    }
+}
+
+/// Resolve the weights file for a model directory
+///
+/// Falls back to `model.gguf` for installs that predate the metadata file.
+pub fn weights_path(model_dir: &Path) -> PathBuf {
+    ModelMetadata::load(model_dir).map_or_else(
+        || model_dir.join(DEFAULT_WEIGHTS_FILE),
+        |metadata| model_dir.join(metadata.weights_file),
+    )
+}
+
+/// Every weights file of a model: all shards of a split model, else one
+pub fn weights_paths(model_dir: &Path) -> Vec<PathBuf> {
+    match ModelMetadata::load(model_dir) {
+        Some(metadata) if !metadata.shards.is_empty() => metadata
+            .shards
+            .iter()
+            .map(|shard| model_dir.join(shard))
+            .collect(),
+        _ => vec![weights_path(model_dir)],
+    }
+}
+
+/// The lowercase SHA-256 stored for a model, `None` if it was never verified
+pub fn stored_hash(model_dir: &Path) -> Option<String> {
+    let hash = std::fs::read_to_string(model_dir.join(HASH_FILE)).ok()?;
+    let hash = hash.trim();
+    (!hash.is_empty()).then(|| hash.to_lowercase())
+}
+
+/// Whether every file of the model was downloaded, verified, and renamed
+///
+/// A crash partway through finalizing leaves the marker missing, so a
+/// half-finalized directory is never mistaken for a complete model.
+pub fn is_complete(model_dir: &Path) -> bool {
+    model_dir.join(COMPLETE_MARKER).exists()
+}
+
+/// Mark a model directory complete; call only after all files are final
+pub fn mark_complete(model_dir: &Path) -> Result<(), String> {
+    std::fs::write(model_dir.join(COMPLETE_MARKER), b"")
+        .map_err(|e| format!("Failed to mark model complete: {e}"))
+}
+
+/// Remove the completion marker before files in the directory are replaced
+pub fn clear_complete(model_dir: &Path) -> Result<(), String> {
+    match std::fs::remove_file(model_dir.join(COMPLETE_MARKER)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to clear completion marker: {e}")),
+    }
+}
+
+/// One-time migration: mark models installed before the sentinel existed
+///
+/// Directories with weights and a tokenizer but no `.part` were complete
+/// under the old rules. Runs once per models directory.
+pub fn migrate_complete_markers(models_dir: &Path) {
+    let flag = models_dir.join(MARKERS_MIGRATED);
+    if flag.exists() {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(models_dir) else {
+        return;
+    };
+
+    for model_dir in entries.flatten().map(|entry| entry.path()) {
+        let finished = weights_path(&model_dir).exists()
+            && model_dir.join("tokenizer.json").exists()
+            && !model_dir.join("model.gguf.part").exists();
+        if finished && !is_complete(&model_dir) {
+            if let Err(e) = mark_complete(&model_dir) {
+                warn!("{e} for {}", model_dir.display());
+            }
+        }
+    }
+
+    if let Err(e) = std::fs::write(&flag, b"") {
+        warn!("Failed to record completion marker migration: {e}");
+    }
+}
+
+/// Whether `name` is a bare file name (no separators, not `.`/`..`)
+pub fn is_plain_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(std::path::Component::Normal(_)), None)
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_weights_path_falls_back_to_model_gguf() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(weights_path(dir.path()), dir.path().join("model.gguf"));
+    }
+
+    #[test]
+    fn test_weights_path_uses_stored_filename() {
+        let dir = TempDir::new().unwrap();
+        ModelMetadata::new("phi-3-q4.gguf")
+            .save(dir.path())
+            .unwrap();
+        assert_eq!(weights_path(dir.path()), dir.path().join("phi-3-q4.gguf"));
+    }
+
+    #[test]
+    fn test_weights_paths_lists_every_shard() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(
+            weights_paths(dir.path()),
+            vec![dir.path().join("model.gguf")]
+        );
+
+        let shards = vec![
+            "m-00001-of-00002.gguf".to_string(),
+            "m-00002-of-00002.gguf".to_string(),
+        ];
+        ModelMetadata::sharded(shards.clone())
+            .save(dir.path())
+            .unwrap();
+        assert_eq!(weights_path(dir.path()), dir.path().join(&shards[0]));
+        assert_eq!(
+            weights_paths(dir.path()),
+            vec![dir.path().join(&shards[0]), dir.path().join(&shards[1])]
+        );
+    }
+
+    #[test]
+    fn test_completion_marker_migration_runs_once() {
+        let models = TempDir::new().unwrap();
+        let finished = models.path().join("phi-3");
+        let partial = models.path().join("llama-3");
+        for dir in [&finished, &partial] {
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(dir.join("model.gguf"), b"test").unwrap();
+            std::fs::write(dir.join("tokenizer.json"), b"{}").unwrap();
+        }
+        std::fs::write(partial.join("model.gguf.part"), b"te").unwrap();
+
+        migrate_complete_markers(models.path());
+        assert!(is_complete(&finished));
+        assert!(!is_complete(&partial));
+
+        // Later crashes must not be papered over by re-running the migration
+        clear_complete(&finished).unwrap();
+        migrate_complete_markers(models.path());
+        assert!(!is_complete(&finished));
+    }
+
+    #[test]
+    fn test_download_manifest_round_trip() {
+        let dir = TempDir::new().unwrap();
+        assert!(DownloadManifest::load(dir.path()).is_none());
+
+        let manifest = DownloadManifest {
+            url: "https://example.com/model.gguf".to_string(),
+            resolved_url: "https://cdn.example.com/model.gguf".to_string(),
+            tokenizer_url: "https://example.com/tokenizer.json".to_string(),
+            total_bytes: 1000,
+            expected_hash: Some("abc123".to_string()),
+            tokenizer_hash: None,
+            shard_hashes: Vec::new(),
+            mirrors: Vec::new(),
+            headers: StoredHeaders::new(&[
+                ("User-Agent".to_string(), "continuum".to_string()),
+                ("Authorization".to_string(), "Bearer hf_secret".to_string()),
+            ]),
+            bytes_downloaded: 400,
+        };
+        manifest.save(dir.path()).unwrap();
+        assert_eq!(DownloadManifest::load(dir.path()), Some(manifest));
+        let saved = std::fs::read_to_string(dir.path().join(DOWNLOAD_MANIFEST_FILE)).unwrap();
+        assert!(!saved.contains("hf_secret"));
+
+        DownloadManifest::remove(dir.path());
+        assert!(DownloadManifest::load(dir.path()).is_none());
+        // Removing twice is harmless
+        DownloadManifest::remove(dir.path());
+    }
+
+    #[test]
+    fn test_metadata_rejects_paths_outside_model_dir() {
+        let dir = TempDir::new().unwrap();
+        ModelMetadata::new("../other/model.gguf")
+            .save(dir.path())
+            .unwrap();
+        assert_eq!(weights_path(dir.path()), dir.path().join("model.gguf"));
+
+        assert!(is_plain_file_name("phi-3-q4.gguf"));
+        assert!(!is_plain_file_name(".."));
+        assert!(!is_plain_file_name("/etc/passwd"));
+        assert!(!is_plain_file_name(""));
+    }
+}
@@ -12,7 +12,12 @@
 
 mod commands;
 mod manager;
+mod metadata;
+mod shards;
 mod state;
+#[cfg(test)]
+mod test_server;
 
 pub use commands::*;
+pub use metadata::*;
 pub use state::*;
@@ -0,0 +1,143 @@
+//! Split GGUF models stored as `{name}-00001-of-00003.gguf`, ...
+//!
+//! A download whose URL names the first shard fetches every shard of the set
+//! into the model directory under its original name. The inference backend
+//! reads a single GGUF file, so the shards are merged into `model.gguf` the
+//! first time the model loads.
+
+use std::path::{Path, PathBuf};
+
+/// Split a shard file name into its prefix, index digits, and count digits
+fn split_shard_name(file_name: &str) -> Option<(&str, &str, &str)> {
+    let stem = file_name.strip_suffix(".gguf")?;
+    let (rest, count) = stem.rsplit_once("-of-")?;
+    let (prefix, index) = rest.rsplit_once('-')?;
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    (!prefix.is_empty() && is_number(index) && is_number(count)).then_some((prefix, index, count))
+}
+
+/// File names of every shard in the set `first` begins
+///
+/// `None` unless `first` is shard 1 of a set of at least two.
+pub fn shard_file_names(first: &str) -> Option<Vec<String>> {
+    let (prefix, index, count_digits) = split_shard_name(first)?;
+    let count: u32 = count_digits.parse().ok()?;
+    if index.parse::<u32>().ok()? != 1 || count < 2 {
+        return None;
+    }
+
+    let width = index.len();
+    Some(
+        (1..=count)
+            .map(|i| format!("{prefix}-{i:0width$}-of-{count_digits}.gguf"))
+            .collect(),
+    )
+}
+
+/// URL and file name of every shard, when `url` points at the first shard
+///
+/// The other shards are expected beside it, with the same query string.
+pub fn shard_urls(url: &str) -> Option<Vec<(String, String)>> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let names = shard_file_names(url.path_segments()?.next_back()?)?;
+
+    names
+        .into_iter()
+        .map(|name| {
+            let mut shard = url.clone();
+            shard.path_segments_mut().ok()?.pop().push(&name);
+            Some((shard.to_string(), name))
+        })
+        .collect()
+}
+
+/// File names of the shards `url` starts, empty for a single-file model
+pub fn shard_names(url: &str) -> Vec<String> {
+    shard_urls(url)
+        .into_iter()
+        .flatten()
+        .map(|(_, name)| name)
+        .collect()
+}
+
+/// The `.part` file a shard downloads into
+pub fn part_path(model_dir: &Path, shard: &str) -> PathBuf {
+    model_dir.join(format!("{shard}.part"))
+}
+
+/// Bytes of a shard set already on disk, finished shards and `.part`s alike
+///
+/// `None` if none of the shards has been started.
+pub fn bytes_on_disk(model_dir: &Path, shards: &[String]) -> Option<u64> {
+    let sizes: Vec<u64> = shards
+        .iter()
+        .filter_map(|shard| {
+            std::fs::metadata(model_dir.join(shard))
+                .or_else(|_| std::fs::metadata(part_path(model_dir, shard)))
+                .ok()
+                .map(|m| m.len())
+        })
+        .collect();
+    (!sizes.is_empty()).then(|| sizes.iter().sum())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_shard_file_names_from_first_shard() {
+        assert_eq!(
+            shard_file_names("qwen-72b-q4-00001-of-00003.gguf").unwrap(),
+            vec![
+                "qwen-72b-q4-00001-of-00003.gguf",
+                "qwen-72b-q4-00002-of-00003.gguf",
+                "qwen-72b-q4-00003-of-00003.gguf",
+            ]
+        );
+
+        // Only the first shard of a real split starts a set
+        assert_eq!(shard_file_names("model-00002-of-00003.gguf"), None);
+        assert_eq!(shard_file_names("model-00001-of-00001.gguf"), None);
+        assert_eq!(shard_file_names("model.gguf"), None);
+        assert_eq!(shard_file_names("model-1-of-x.gguf"), None);
+        assert_eq!(shard_file_names("-00001-of-00002.gguf"), None);
+    }
+
+    #[test]
+    fn test_shard_urls_keep_directory_and_query() {
+        let urls =
+            shard_urls("https://hf.co/org/repo/resolve/main/m-00001-of-00002.gguf?download=true")
+                .unwrap();
+
+        assert_eq!(
+            urls,
+            vec![
+                (
+                    "https://hf.co/org/repo/resolve/main/m-00001-of-00002.gguf?download=true"
+                        .to_string(),
+                    "m-00001-of-00002.gguf".to_string()
+                ),
+                (
+                    "https://hf.co/org/repo/resolve/main/m-00002-of-00002.gguf?download=true"
+                        .to_string(),
+                    "m-00002-of-00002.gguf".to_string()
+                ),
+            ]
+        );
+        assert_eq!(shard_urls("https://hf.co/org/repo/model.gguf"), None);
+    }
+
+    #[test]
+    fn test_bytes_on_disk_counts_finished_and_partial_shards() {
+        let dir = TempDir::new().unwrap();
+        let shards = shard_file_names("m-00001-of-00003.gguf").unwrap();
+        assert_eq!(bytes_on_disk(dir.path(), &shards), None);
+
+        std::fs::write(dir.path().join(&shards[0]), vec![0u8; 100]).unwrap();
+        std::fs::write(part_path(dir.path(), &shards[1]), vec![0u8; 30]).unwrap();
+        assert_eq!(bytes_on_disk(dir.path(), &shards), Some(130));
+    }
+}
@@ -0,0 +1,206 @@
+//! Minimal HTTP server for download tests
+//!
+//! Serves canned responses from a handler closure and records every request
+//! so tests can assert on methods, paths, and headers without real hosts.
+
+#![allow(clippy::unwrap_used)] // Test helper - panics are desired on failure
+#![allow(dead_code)] // Shared helper - not every test uses every builder
+
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// A request received by the test server
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl RecordedRequest {
+    /// Look up a header value (case-insensitive name)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A canned response
+pub struct TestResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    /// Send `Content-Length` unless the handler set one or opted out
+    sized: bool,
+    /// Go silent for the duration after this many body bytes
+    pause: Option<(usize, Duration)>,
+    /// Drop the connection after this many body bytes
+    cut_off: Option<usize>,
+}
+
+impl TestResponse {
+    pub const fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            sized: true,
+            pause: None,
+            cut_off: None,
+        }
+    }
+
+    #[must_use]
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    #[must_use]
+    pub fn body(mut self, body: &[u8]) -> Self {
+        self.body = body.to_vec();
+        self
+    }
+
+    /// Omit `Content-Length`; the body then ends when the connection closes
+    #[must_use]
+    pub const fn unsized_body(mut self) -> Self {
+        self.sized = false;
+        self
+    }
+
+    /// Send the first `at` body bytes, then nothing for `duration` before the rest
+    #[must_use]
+    pub const fn pause_after(mut self, at: usize, duration: Duration) -> Self {
+        self.pause = Some((at, duration));
+        self
+    }
+
+    /// Close the connection after `at` body bytes, short of `Content-Length`
+    #[must_use]
+    pub const fn cut_off_after(mut self, at: usize) -> Self {
+        self.cut_off = Some(at);
+        self
+    }
+}
+
+/// Local HTTP/1.1 server answering every connection with the handler
+pub struct TestServer {
+    base_url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl TestServer {
+    /// Bind to a random local port and start serving
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&RecordedRequest) -> TestResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler = Arc::new(handler);
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let Some(request) = read_request(&mut socket).await else {
+                        return;
+                    };
+                    recorded.lock().unwrap().push(request.clone());
+                    let response = handler(&request);
+                    let _ = write_response(&mut socket, &request, &response).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        Self { base_url, requests }
+    }
+
+    /// Absolute URL for a path on this server
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    /// All requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<RecordedRequest> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let head = String::from_utf8_lossy(&buf);
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers = lines
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+    })
+}
+
+async fn write_response(
+    socket: &mut tokio::net::TcpStream,
+    request: &RecordedRequest,
+    response: &TestResponse,
+) -> std::io::Result<()> {
+    socket.write_all(&encode_head(response)).await?;
+    if request.method == "HEAD" {
+        return Ok(());
+    }
+    let mut body = response.body.as_slice();
+    if let Some(at) = response.cut_off {
+        body = &body[..at.min(body.len())];
+    }
+    if let Some((at, duration)) = response.pause {
+        let (first, rest) = body.split_at(at.min(body.len()));
+        socket.write_all(first).await?;
+        socket.flush().await?;
+        tokio::time::sleep(duration).await;
+        body = rest;
+    }
+    socket.write_all(body).await
+}
+
+fn encode_head(response: &TestResponse) -> Vec<u8> {
+    let mut out = format!("HTTP/1.1 {} Test\r\nConnection: close\r\n", response.status);
+    let has_length = response
+        .headers
+        .iter()
+        .any(|(k, _)| k.eq_ignore_ascii_case("content-length"));
+    if response.sized && !has_length {
+        let _ = write!(out, "Content-Length: {}\r\n", response.body.len());
+    }
+    for (name, value) in &response.headers {
+        let _ = write!(out, "{name}: {value}\r\n");
+    }
+    out.push_str("\r\n");
+    out.into_bytes()
+}
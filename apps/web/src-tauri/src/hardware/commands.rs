@@ -7,10 +7,14 @@
 //!
 //! ADR-HARDWARE-002: Uses sysinfo 0.31+ crate for cross-platform detection
 
-use super::state::{GpuInfo, HardwareState, SystemInfo};
+use super::monitor;
+use super::recommend::{self, ModelRecommendation, ModelRequirement};
+use super::state::{DiskInfo, GpuInfo, GpuStats, GpuVendor, HardwareState, SystemInfo};
+use crate::downloads::DownloadState;
 use log::warn;
-use sysinfo::{Disks, System};
-use tauri::State;
+use std::path::Path;
+use sysinfo::{Disk, DiskKind, Disks, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::{AppHandle, State};
 
 /// Get system RAM, CPU, and storage info
 ///
@@ -19,24 +23,52 @@ use tauri::State;
 ///
 /// # Returns
 /// - `ram_mb`: Total system RAM in megabytes
-/// - `cpu_cores`: Number of CPU cores
+/// - `cpu_cores`: Number of logical CPU cores
+/// - `physical_cores`: Number of physical cores (`physical_cores_estimated` if it fell back to logical)
+/// - `cpu_brand` / `cpu_features`: CPU model name and SIMD extensions (AVX2, AVX-512, NEON, ...)
 /// - `storage_available_mb`: Total available storage across all disks in megabytes
+/// - `swap_total_mb` / `swap_used_mb`: Swap size and usage in megabytes (0 if unavailable)
 #[tauri::command]
 #[allow(clippy::needless_pass_by_value)] // Tauri State is designed to be passed by value
 #[allow(clippy::unnecessary_wraps)] // Tauri commands require Result return type
 pub fn get_system_info(state: State<'_, HardwareState>) -> Result<SystemInfo, String> {
+    Ok(cached_system_info(&state))
+}
+
+/// System info from the cache, querying (and caching) on a miss
+fn cached_system_info(state: &HardwareState) -> SystemInfo {
     // Check cache first
     if let Some(cached) = state.get_cached_system() {
-        return Ok(cached);
+        return cached;
     }
 
-    // Query system info
+    let info = query_system_info();
+
+    // Cache the result
+    state.cache_system(info.clone());
+
+    info
+}
+
+/// Query RAM, CPU, and storage info directly (bypasses the cache)
+pub(super) fn query_system_info() -> SystemInfo {
     let mut sys = System::new_all();
     sys.refresh_all();
 
     // sysinfo 0.31+: total_memory() returns bytes, no trait import needed
     let ram_mb = sys.total_memory() / 1024 / 1024;
     let cpu_cores = sys.cpus().len();
+    let (physical_cores, physical_cores_estimated) =
+        physical_core_count(System::physical_core_count(), cpu_cores);
+    let cpu_brand = sys
+        .cpus()
+        .first()
+        .map(|cpu| cpu.brand().trim().to_string())
+        .unwrap_or_default();
+
+    // Swap reports 0 on platforms where it's unavailable
+    let swap_total_mb = sys.total_swap() / 1024 / 1024;
+    let swap_used_mb = sys.used_swap() / 1024 / 1024;
 
     // sysinfo 0.31+: Use Disks struct directly (DiskExt deprecated)
     let disks = Disks::new_with_refreshed_list();
@@ -45,103 +77,643 @@ pub fn get_system_info(state: State<'_, HardwareState>) -> Result<SystemInfo, St
         .map(|d| d.available_space() / 1024 / 1024)
         .sum();
 
-    let info = SystemInfo {
+    SystemInfo {
         ram_mb,
         cpu_cores,
+        physical_cores,
+        physical_cores_estimated,
+        cpu_brand,
+        cpu_features: detect_cpu_features(),
         storage_available_mb,
-    };
+        swap_total_mb,
+        swap_used_mb,
+    }
+}
 
-    // Cache the result
-    state.cache_system(info.clone());
+/// Physical core count, falling back to the logical count when unknown
+///
+/// Returns the count and whether it is such an estimate.
+const fn physical_core_count(physical: Option<usize>, logical: usize) -> (usize, bool) {
+    match physical {
+        Some(count) => (count, false),
+        None => (logical, true),
+    }
+}
+
+/// SIMD extensions relevant to GGUF inference that this CPU supports
+#[cfg(target_arch = "x86_64")]
+fn detect_cpu_features() -> Vec<String> {
+    let detected = [
+        ("sse4.2", std::arch::is_x86_feature_detected!("sse4.2")),
+        ("avx", std::arch::is_x86_feature_detected!("avx")),
+        ("avx2", std::arch::is_x86_feature_detected!("avx2")),
+        ("fma", std::arch::is_x86_feature_detected!("fma")),
+        ("f16c", std::arch::is_x86_feature_detected!("f16c")),
+        ("avx512f", std::arch::is_x86_feature_detected!("avx512f")),
+        (
+            "avx512vnni",
+            std::arch::is_x86_feature_detected!("avx512vnni"),
+        ),
+    ];
+    supported(&detected)
+}
+
+/// SIMD extensions relevant to GGUF inference that this CPU supports
+#[cfg(target_arch = "aarch64")]
+fn detect_cpu_features() -> Vec<String> {
+    let detected = [
+        ("neon", std::arch::is_aarch64_feature_detected!("neon")),
+        (
+            "dotprod",
+            std::arch::is_aarch64_feature_detected!("dotprod"),
+        ),
+        ("i8mm", std::arch::is_aarch64_feature_detected!("i8mm")),
+        ("sve", std::arch::is_aarch64_feature_detected!("sve")),
+    ];
+    supported(&detected)
+}
+
+/// No runtime detection on other architectures
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+const fn detect_cpu_features() -> Vec<String> {
+    Vec::new()
+}
 
-    Ok(info)
+/// Names of the features detected as available
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn supported(detected: &[(&str, bool)]) -> Vec<String> {
+    detected
+        .iter()
+        .filter(|(_, available)| *available)
+        .map(|(name, _)| (*name).to_string())
+        .collect()
 }
 
-/// Get GPU info via nvidia-smi (NVIDIA) or fallback
+/// Get the primary GPU via nvidia-smi (NVIDIA) or, on macOS, system_profiler (Apple)
 ///
-/// Returns None if no compatible GPU detected.
-/// Caches the result (including None) to avoid repeated nvidia-smi calls.
+/// Returns None if no compatible GPU detected. Derived from the cached list
+/// behind `get_all_gpus`, so both stay consistent.
 ///
 /// # Returns
-/// - `Some(GpuInfo)`: GPU name, VRAM in MB, and compute capability
-/// - `None`: No GPU detected or nvidia-smi not available
+/// - `Some(GpuInfo)`: GPU name, VRAM in MB (unified memory on Apple Silicon),
+///   compute capability, `vendor`, and current `vram_used_mb`/`vram_free_mb`
+/// - `None`: No GPU detected
+///
+/// Static fields are cached for the hardware cache duration (30s), the VRAM
+/// usage fields for at most 5s.
 #[tauri::command]
 #[allow(clippy::needless_pass_by_value)] // Tauri State is designed to be passed by value
 #[allow(clippy::unnecessary_wraps)] // Tauri commands require Result return type
 pub fn get_gpu_info(state: State<'_, HardwareState>) -> Result<Option<GpuInfo>, String> {
+    Ok(cached_gpu_info(&state))
+}
+
+/// Get every detected GPU, in nvidia-smi index order
+///
+/// Multi-GPU workstations report one entry per card; empty when there's
+/// no GPU. Cached like `get_gpu_info`.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)] // Tauri State is designed to be passed by value
+#[allow(clippy::unnecessary_wraps)] // Tauri commands require Result return type
+pub fn get_all_gpus(state: State<'_, HardwareState>) -> Result<Vec<GpuInfo>, String> {
+    Ok(cached_gpus(&state))
+}
+
+/// All GPUs from the cache, detecting (and caching) on a miss
+fn cached_gpus(state: &HardwareState) -> Vec<GpuInfo> {
+    let gpus = cached_static_gpus(state);
+    if !gpus.iter().any(|gpu| gpu.vendor == GpuVendor::Nvidia) {
+        return gpus;
+    }
+    with_memory_usage(gpus, &cached_gpu_memory(state))
+}
+
+/// Name, VRAM size, and driver fields, which rarely change
+fn cached_static_gpus(state: &HardwareState) -> Vec<GpuInfo> {
     // Check cache first
-    if let Some(cached) = state.get_cached_gpu() {
-        return Ok(cached);
+    if let Some(cached) = state.get_cached_gpus() {
+        return cached;
+    }
+
+    let gpus = detect_gpus();
+
+    // Cache the result (including an empty list)
+    state.cache_gpus(gpus.clone());
+
+    gpus
+}
+
+/// VRAM usage per GPU (the `get_gpu_stats` reading), from the short-lived cache
+fn cached_gpu_memory(state: &HardwareState) -> Vec<GpuStats> {
+    if let Some(cached) = state.get_cached_gpu_memory() {
+        return cached;
+    }
+
+    let stats = query_gpu_stats();
+    state.cache_gpu_memory(stats.clone());
+    stats
+}
+
+/// Fill in `vram_used_mb`/`vram_free_mb`, matching GPUs by nvidia-smi index
+fn with_memory_usage(mut gpus: Vec<GpuInfo>, stats: &[GpuStats]) -> Vec<GpuInfo> {
+    for gpu in &mut gpus {
+        let memory = stats
+            .iter()
+            .find(|stats| stats.index == gpu.index)
+            .and_then(|stats| stats.memory_used_mb.zip(stats.memory_total_mb));
+        if let Some((used_mb, total_mb)) = memory {
+            gpu.vram_used_mb = Some(used_mb);
+            gpu.vram_free_mb = Some(total_mb.saturating_sub(used_mb));
+        }
+    }
+    gpus
+}
+
+/// The primary GPU from the cached list
+fn cached_gpu_info(state: &HardwareState) -> Option<GpuInfo> {
+    cached_gpus(state).into_iter().next()
+}
+
+/// Share of free memory a model's weights may use; the rest is headroom
+/// for the KV cache, runtime buffers, and the OS
+const MODEL_MEMORY_SAFETY_FACTOR: f64 = 0.75;
+
+/// Estimate the largest model weight file (in MB) that can load
+///
+/// Conservative: uses currently available RAM (not total) and, when a GPU
+/// is present, its free VRAM, then applies a safety overhead factor.
+/// The model picker uses this as a hard ceiling.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)] // Tauri State is designed to be passed by value
+#[allow(clippy::unnecessary_wraps)] // Tauri commands require Result return type
+pub fn max_loadable_model_mb(state: State<'_, HardwareState>) -> Result<u64, String> {
+    let mut sys = System::new();
+    sys.refresh_memory();
+    let ram_available_mb = sys.available_memory() / 1024 / 1024;
+
+    // Other apps' allocations count against the budget when nvidia-smi reports them
+    let vram_mb = cached_gpu_info(&state).map(|gpu| gpu.vram_free_mb.unwrap_or(gpu.vram_mb));
+
+    Ok(estimate_max_model_mb(ram_available_mb, vram_mb))
+}
+
+/// Apply the safety factor to the larger of the RAM and VRAM budgets
+///
+/// Weights are offloaded entirely to the GPU when one is present, so the
+/// budgets don't add up; whichever pool is bigger bounds the model size.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // MB values fit in f64
+#[allow(clippy::cast_precision_loss)]
+fn estimate_max_model_mb(ram_available_mb: u64, vram_mb: Option<u64>) -> u64 {
+    let budget_mb = vram_mb.map_or(ram_available_mb, |vram| vram.max(ram_available_mb));
+    (budget_mb as f64 * MODEL_MEMORY_SAFETY_FACTOR) as u64
+}
+
+/// Check which candidate models this machine can run
+///
+/// Uses the cached system and GPU info. Each candidate comes back with
+/// `fits`, a `reason`, and `recommended` set on the largest one that fits
+/// with at least 20% headroom, so the download UI can gray out the rest.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)] // Tauri State is designed to be passed by value
+#[allow(clippy::unnecessary_wraps)] // Tauri commands require Result return type
+pub fn recommend_models(
+    state: State<'_, HardwareState>,
+    candidates: Vec<ModelRequirement>,
+) -> Result<Vec<ModelRecommendation>, String> {
+    let ram_mb = cached_system_info(&state).ram_mb;
+    let vram_mb = cached_gpu_info(&state).map(|gpu| gpu.vram_free_mb.unwrap_or(gpu.vram_mb));
+
+    Ok(recommend::recommend(candidates, ram_mb, vram_mb))
+}
+
+/// Get info about the disk holding the models directory
+///
+/// Reports the drive kind so the UI can warn that loading from an HDD is slow.
+///
+/// # Returns
+/// - `Some(DiskInfo)`: Mount point, capacity, free space, and `is_ssd`
+/// - `None`: The models directory couldn't be matched to a mounted disk
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)] // Tauri State is designed to be passed by value
+#[allow(clippy::unnecessary_wraps)] // Tauri commands require Result return type
+pub fn get_models_disk_info(
+    download_state: State<'_, DownloadState>,
+) -> Result<Option<DiskInfo>, String> {
+    let disks = Disks::new_with_refreshed_list();
+    Ok(
+        disk_for_path(&disks, download_state.models_dir()).map(|disk| DiskInfo {
+            mount_point: disk.mount_point().to_string_lossy().to_string(),
+            total_mb: disk.total_space() / 1024 / 1024,
+            available_mb: disk.available_space() / 1024 / 1024,
+            is_ssd: is_ssd(disk.kind()),
+        }),
+    )
+}
+
+/// Find the mounted disk containing `path` (longest matching mount point)
+pub fn disk_for_path<'a>(disks: &'a Disks, path: &Path) -> Option<&'a Disk> {
+    let mount = mount_for_path(path, disks.iter().map(Disk::mount_point))?;
+    disks.iter().find(|d| d.mount_point() == mount)
+}
+
+/// Pick the mount point among `mount_points` that holds `path`
+pub fn mount_for_path<'a>(
+    path: &Path,
+    mount_points: impl Iterator<Item = &'a Path>,
+) -> Option<&'a Path> {
+    // Resolve symlinks so the path lines up with real mount points; dunce
+    // keeps Windows paths as `C:\...` rather than the `\\?\C:\...` form
+    // that no mount point starts with
+    let path = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    longest_mount_match(&path, mount_points)
+}
+
+/// Pick the most specific mount point that is a prefix of `path`
+fn longest_mount_match<'a>(
+    path: &Path,
+    mount_points: impl Iterator<Item = &'a Path>,
+) -> Option<&'a Path> {
+    mount_points
+        .filter(|mount| path.starts_with(mount))
+        .max_by_key(|mount| mount.components().count())
+}
+
+/// Map sysinfo's disk kind to SSD/HDD, `None` when undetermined
+const fn is_ssd(kind: DiskKind) -> Option<bool> {
+    match kind {
+        DiskKind::SSD => Some(true),
+        DiskKind::HDD => Some(false),
+        DiskKind::Unknown(_) => None,
+    }
+}
+
+/// Start background hardware polling
+///
+/// Emits `hardware:changed` only when RAM availability, GPU presence, or
+/// free storage changes meaningfully. Replaces any monitor already running.
+///
+/// # Arguments
+/// * `interval_ms` - Polling interval (clamped to at least 1000ms)
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)] // Tauri State is designed to be passed by value
+#[allow(clippy::unused_async)] // Async so the monitor task spawns on the Tauri runtime
+pub async fn start_hardware_monitoring(
+    app: AppHandle,
+    state: State<'_, HardwareState>,
+    interval_ms: u64,
+) -> Result<(), String> {
+    monitor::start(&app, &state, interval_ms);
+    Ok(())
+}
+
+/// Stop background hardware polling
+///
+/// # Returns
+/// * `true` if a monitor was running
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)] // Tauri State is designed to be passed by value
+#[allow(clippy::unnecessary_wraps)] // Tauri commands require Result return type
+pub fn stop_hardware_monitoring(state: State<'_, HardwareState>) -> Result<bool, String> {
+    Ok(monitor::stop(&state))
+}
+
+/// Drop cached hardware info so the next query re-detects it
+///
+/// For when the user plugs in an eGPU or frees RAM and wants fresh numbers
+/// now. Doesn't change the cache duration or auto-expiry.
+///
+/// # Arguments
+/// * `clear_gpu` - Expire `get_gpu_info`/`get_all_gpus` (VRAM usage included)
+/// * `clear_system` - Expire `get_system_info`
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)] // Tauri State is designed to be passed by value
+#[allow(clippy::unnecessary_wraps)] // Tauri commands require Result return type
+pub fn refresh_hardware(
+    state: State<'_, HardwareState>,
+    clear_gpu: bool,
+    clear_system: bool,
+) -> Result<(), String> {
+    if clear_gpu {
+        state.invalidate_gpus();
+    }
+    if clear_system {
+        state.invalidate_system();
+    }
+    Ok(())
+}
+
+/// Set how long system and GPU info stays cached
+///
+/// Short durations give live-ish data; long ones avoid repeated nvidia-smi
+/// spawns. Default is 30s, and 0 disables caching.
+///
+/// # Arguments
+/// * `duration_ms` - Cache lifetime in milliseconds
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)] // Tauri State is designed to be passed by value
+#[allow(clippy::unnecessary_wraps)] // Tauri commands require Result return type
+pub fn set_hardware_cache_duration(
+    state: State<'_, HardwareState>,
+    duration_ms: u64,
+) -> Result<(), String> {
+    state.set_cache_duration(std::time::Duration::from_millis(duration_ms));
+    Ok(())
+}
+
+/// Detect GPUs, trying nvidia-smi first and then platform-specific paths
+pub(super) fn detect_gpus() -> Vec<GpuInfo> {
+    let gpus = detect_nvidia_gpus();
+    #[cfg(target_os = "macos")]
+    let gpus = if gpus.is_empty() {
+        detect_apple_gpu().into_iter().collect()
+    } else {
+        gpus
+    };
+    gpus
+}
+
+/// Detect an Apple Silicon GPU via `system_profiler SPDisplaysDataType`
+///
+/// The GPU shares the system's unified memory, so that is reported as VRAM.
+#[cfg(target_os = "macos")]
+fn detect_apple_gpu() -> Option<GpuInfo> {
+    let output = std::process::Command::new("system_profiler")
+        .args(["SPDisplaysDataType", "-json"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
     }
 
-    // Try nvidia-smi for NVIDIA GPUs
-    let gpu_info = detect_nvidia_gpu();
+    let mut sys = System::new();
+    sys.refresh_memory();
+    parse_apple_displays(
+        &String::from_utf8_lossy(&output.stdout),
+        sys.total_memory() / 1024 / 1024,
+    )
+}
 
-    // Cache the result (including None)
-    state.cache_gpu(gpu_info.clone());
+/// Find the Apple GPU in `system_profiler SPDisplaysDataType -json` output
+///
+/// Intel Macs list Intel/AMD GPUs here instead, which are skipped.
+#[cfg(any(target_os = "macos", test))]
+fn parse_apple_displays(json: &str, unified_memory_mb: u64) -> Option<GpuInfo> {
+    let report: serde_json::Value = serde_json::from_str(json).ok()?;
+    let name = report["SPDisplaysDataType"]
+        .as_array()?
+        .iter()
+        .filter_map(|display| display["sppci_model"].as_str())
+        .find(|model| model.starts_with("Apple"))?;
 
-    Ok(gpu_info)
+    Some(GpuInfo {
+        index: 0,
+        name: name.to_string(),
+        vram_mb: unified_memory_mb,
+        compute_capable: true, // Apple Silicon = Metal capable
+        vendor: GpuVendor::Apple,
+        driver_version: None,
+        cuda_version: None,
+        vram_used_mb: None,
+        vram_free_mb: None,
+    })
 }
 
-/// Detect NVIDIA GPU via nvidia-smi command
+/// Detect NVIDIA GPUs via nvidia-smi command
 ///
-/// Returns None if:
+/// Returns an empty list if:
 /// - nvidia-smi is not installed
 /// - Command fails to execute
 /// - No NVIDIA GPU detected
-fn detect_nvidia_gpu() -> Option<GpuInfo> {
-    let output = std::process::Command::new("nvidia-smi")
+pub(super) fn detect_nvidia_gpus() -> Vec<GpuInfo> {
+    let Ok(output) = std::process::Command::new("nvidia-smi")
         .args([
-            "--query-gpu=name,memory.total",
+            "--query-gpu=index,name,memory.total,driver_version",
             "--format=csv,noheader,nounits",
         ])
         .output()
-        .ok()?;
+    else {
+        return Vec::new();
+    };
 
     if !output.status.success() {
+        return Vec::new();
+    }
+
+    let mut gpus = parse_nvidia_smi_output(&String::from_utf8_lossy(&output.stdout));
+    if !gpus.is_empty() {
+        // One driver serves every GPU, so the banner is read once
+        let cuda_version = detect_cuda_version();
+        for gpu in &mut gpus {
+            gpu.cuda_version.clone_from(&cuda_version);
+        }
+    }
+    gpus
+}
+
+/// Parse every GPU line; a malformed line is skipped without shifting the rest
+fn parse_nvidia_smi_output(stdout: &str) -> Vec<GpuInfo> {
+    stdout
+        .lines()
+        .filter_map(|line| parse_nvidia_smi_line(line.trim()))
+        .collect()
+}
+
+/// Get live utilization, temperature, memory, and power for each GPU
+///
+/// Deliberately bypasses the hardware cache since this is live telemetry.
+/// Returns an empty list when no NVIDIA GPU (or nvidia-smi) is available.
+#[tauri::command]
+pub async fn get_gpu_stats() -> Result<Vec<GpuStats>, String> {
+    // nvidia-smi can take a few hundred ms; keep it off the async runtime
+    tokio::task::spawn_blocking(query_gpu_stats)
+        .await
+        .map_err(|e| format!("GPU stats query failed: {e}"))
+}
+
+/// Query per-GPU telemetry from nvidia-smi
+fn query_gpu_stats() -> Vec<GpuStats> {
+    let Ok(output) = std::process::Command::new("nvidia-smi")
+        .args([
+            "--query-gpu=index,utilization.gpu,temperature.gpu,memory.used,memory.total,power.draw",
+            "--format=csv,noheader,nounits",
+        ])
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    if !output.status.success() {
+        return Vec::new();
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_gpu_stats_line)
+        .collect()
+}
+
+/// Get current utilization, VRAM use, and temperature of the primary GPU
+///
+/// Meant to be polled every second, so it runs nvidia-smi on each call and
+/// never touches the hardware cache. The same reading as `get_gpu_stats`,
+/// for GPU 0 only. Returns None without an NVIDIA GPU.
+#[tauri::command]
+pub async fn get_gpu_utilization() -> Result<Option<GpuStats>, String> {
+    tokio::task::spawn_blocking(|| query_gpu_stats().into_iter().find(|gpu| gpu.index == 0))
+        .await
+        .map_err(|e| format!("GPU utilization query failed: {e}"))
+}
+
+/// Resident memory of this process in megabytes, `None` if it can't be read
+pub fn process_rss_mb() -> Option<u64> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_memory(),
+    );
+    sys.process(pid).map(|p| p.memory() / 1024 / 1024)
+}
+
+/// Memory in use across all NVIDIA GPUs in megabytes
+///
+/// This is device-wide (other processes included). `None` without nvidia-smi.
+pub fn gpu_memory_used_mb() -> Option<u64> {
+    let used: Vec<u64> = query_gpu_stats()
+        .iter()
+        .filter_map(|gpu| gpu.memory_used_mb)
+        .collect();
+    (!used.is_empty()).then(|| used.iter().sum())
+}
+
+/// Parse one "index, utilization, temperature, memory.used, memory.total,
+/// power.draw" line
+///
+/// e.g., "0, 87, 71, 20312, 24576, 318.45" or "1, [N/A], 45, 1024, 8192, [N/A]"
+fn parse_gpu_stats_line(line: &str) -> Option<GpuStats> {
+    let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+    if parts.len() < 6 {
+        if !line.trim().is_empty() {
+            warn!("nvidia-smi stats output malformed: {line}");
+        }
         return None;
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let line = stdout.trim();
+    // "[N/A]" / "[Not Supported]" fail to parse and become None
+    Some(GpuStats {
+        index: parts[0].parse().ok()?,
+        utilization_percent: parts[1].parse().ok(),
+        temperature_c: parts[2].parse().ok(),
+        memory_used_mb: parts[3].parse().ok(),
+        memory_total_mb: parts[4].parse().ok(),
+        power_watts: parts[5].parse().ok(),
+    })
+}
 
+/// Parse one "index, name, memory.total, driver_version" line from nvidia-smi
+///
+/// e.g., "0, NVIDIA GeForce RTX 4090, 24576, 550.54.14"
+fn parse_nvidia_smi_line(line: &str) -> Option<GpuInfo> {
     if line.is_empty() {
         return None;
     }
 
-    // Parse "GPU Name, VRAM" format
-    // e.g., "NVIDIA GeForce RTX 4090, 24576"
     let parts: Vec<&str> = line.split(',').collect();
-    if parts.len() < 2 {
-        warn!("nvidia-smi output malformed: expected 'name,vram' but got: {line}");
+    if parts.len() < 3 {
+        warn!("nvidia-smi output malformed: expected 'index,name,vram' but got: {line}");
         return None;
     }
+    let Ok(index) = parts[0].trim().parse() else {
+        warn!("nvidia-smi index parse failed: {line}");
+        return None;
+    };
 
-    let name = parts[0].trim().to_string();
-    let vram_mb: u64 = match parts[1].trim().parse() {
+    let name = parts[1].trim().to_string();
+    let vram_mb: u64 = match parts[2].trim().parse() {
         Ok(v) => v,
         Err(e) => {
             warn!(
                 "nvidia-smi VRAM parse failed for '{}': {} - using 0",
-                parts[1].trim(),
+                parts[2].trim(),
                 e
             );
             0
         },
     };
+    let driver_version = parts
+        .get(3)
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
 
     Some(GpuInfo {
+        index,
         name,
         vram_mb,
         compute_capable: true, // NVIDIA = CUDA capable
+        vendor: GpuVendor::Nvidia,
+        driver_version,
+        cuda_version: None,
+        vram_used_mb: None,
+        vram_free_mb: None,
     })
 }
 
+/// Read the CUDA version from the plain `nvidia-smi` header
+///
+/// The query interface doesn't expose it, so this parses the banner line.
+fn detect_cuda_version() -> Option<String> {
+    let output = std::process::Command::new("nvidia-smi").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_cuda_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Extract "12.4" from a header containing "CUDA Version: 12.4"
+fn parse_cuda_version(header: &str) -> Option<String> {
+    let (_, rest) = header.split_once("CUDA Version:")?;
+    let version: String = rest
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    if version.is_empty() {
+        None
+    } else {
+        Some(version)
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_gpu_stats_line() {
+        assert_eq!(
+            parse_gpu_stats_line("0, 87, 71, 20312, 24576, 318.45"),
+            Some(GpuStats {
+                index: 0,
+                utilization_percent: Some(87),
+                temperature_c: Some(71),
+                memory_used_mb: Some(20312),
+                memory_total_mb: Some(24576),
+                power_watts: Some(318.45),
+            })
+        );
+
+        let partial = parse_gpu_stats_line("1, [N/A], 45, 1024, 8192, [Not Supported]").unwrap();
+        assert_eq!(partial.index, 1);
+        assert_eq!(partial.utilization_percent, None);
+        assert_eq!(partial.memory_total_mb, Some(8192));
+        assert_eq!(partial.power_watts, None);
+
+        assert_eq!(parse_gpu_stats_line(""), None);
+        assert_eq!(parse_gpu_stats_line("0, 87"), None);
+    }
+
     #[test]
     fn test_sysinfo_returns_valid_values() {
         // Test sysinfo crate directly (command wrapper tested via integration tests)
@@ -155,14 +727,157 @@ mod tests {
         assert!(cpu_cores > 0, "CPU cores should be positive");
     }
 
+    #[test]
+    fn test_physical_core_count_falls_back_to_logical() {
+        assert_eq!(physical_core_count(Some(8), 16), (8, false));
+        assert_eq!(physical_core_count(None, 16), (16, true));
+    }
+
+    #[test]
+    fn test_cpu_features_are_known_names() {
+        let features = detect_cpu_features();
+        let known = [
+            "sse4.2",
+            "avx",
+            "avx2",
+            "fma",
+            "f16c",
+            "avx512f",
+            "avx512vnni",
+            "neon",
+            "dotprod",
+            "i8mm",
+            "sve",
+        ];
+        assert!(features.iter().all(|f| known.contains(&f.as_str())));
+
+        // NEON is mandatory on aarch64
+        #[cfg(target_arch = "aarch64")]
+        assert!(features.iter().any(|f| f == "neon"));
+    }
+
+    #[test]
+    fn test_process_rss_is_measurable() {
+        assert!(process_rss_mb().is_some());
+    }
+
     #[test]
     fn test_nvidia_gpu_detection_does_not_panic() {
         // Should not panic even if nvidia-smi is not available
-        let result = detect_nvidia_gpu();
+        let result = detect_nvidia_gpus();
         // Result can be Some or None depending on system - just verify no panic
         let _ = result;
     }
 
+    #[test]
+    fn test_parse_nvidia_smi_line_with_driver() {
+        let gpu = parse_nvidia_smi_line("1, NVIDIA GeForce RTX 4090, 24576, 550.54.14").unwrap();
+        assert_eq!(gpu.index, 1);
+        assert_eq!(gpu.name, "NVIDIA GeForce RTX 4090");
+        assert_eq!(gpu.vram_mb, 24576);
+        assert_eq!(gpu.driver_version.as_deref(), Some("550.54.14"));
+        assert!(gpu.cuda_version.is_none());
+    }
+
+    #[test]
+    fn test_parse_apple_displays() {
+        let json = r#"{"SPDisplaysDataType": [{
+            "_name": "Apple M2 Pro",
+            "sppci_cores": "19",
+            "sppci_model": "Apple M2 Pro",
+            "sppci_vendor": "sppci_vendor_Apple"
+        }]}"#;
+        let gpu = parse_apple_displays(json, 32_768).unwrap();
+        assert_eq!(gpu.name, "Apple M2 Pro");
+        assert_eq!(gpu.vram_mb, 32_768);
+        assert!(gpu.compute_capable);
+        assert_eq!(gpu.vendor, GpuVendor::Apple);
+
+        // Intel Macs report discrete/integrated GPUs from other vendors
+        let intel = r#"{"SPDisplaysDataType": [{"sppci_model": "Intel Iris Plus Graphics"}]}"#;
+        assert!(parse_apple_displays(intel, 16_384).is_none());
+        assert!(parse_apple_displays("not json", 16_384).is_none());
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_output_lists_every_gpu() {
+        let stdout = "0, NVIDIA RTX A6000, 49140, 550.54.14\n\
+                      1, NVIDIA RTX A6000, 49140, 550.54.14\n\
+                      2, NVIDIA GeForce RTX 3060, 12288, 550.54.14\n";
+        let gpus = parse_nvidia_smi_output(stdout);
+        assert_eq!(gpus.len(), 3);
+        assert_eq!(gpus.iter().map(|g| g.index).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(gpus[2].name, "NVIDIA GeForce RTX 3060");
+        assert_eq!(gpus[2].vram_mb, 12288);
+
+        // An unreadable line doesn't shift the GPUs after it
+        let gpus = parse_nvidia_smi_output("garbled\n1, RTX 3060, 12288, 550.54.14\n");
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].index, 1);
+
+        assert!(parse_nvidia_smi_output("").is_empty());
+    }
+
+    #[test]
+    fn test_memory_usage_fills_free_vram() {
+        let gpus = parse_nvidia_smi_output(
+            "0, RTX 4090, 24576, 550.54.14\n1, RTX 3060, 12288, 550.54.14\n",
+        );
+        // GPU 0's line is unreadable; GPU 1 must not pick up another card's numbers
+        let stats: Vec<GpuStats> = "garbled\n1, 5, 40, 2048, 12288, 30.5\n"
+            .lines()
+            .filter_map(parse_gpu_stats_line)
+            .collect();
+
+        let gpus = with_memory_usage(gpus, &stats);
+        assert_eq!(gpus[0].vram_free_mb, None);
+        assert_eq!(gpus[1].vram_used_mb, Some(2048));
+        assert_eq!(gpus[1].vram_free_mb, Some(10240));
+    }
+
+    #[test]
+    fn test_parse_cuda_version_from_header() {
+        let header =
+            "| NVIDIA-SMI 550.54.14    Driver Version: 550.54.14    CUDA Version: 12.4     |";
+        assert_eq!(parse_cuda_version(header).as_deref(), Some("12.4"));
+        assert!(parse_cuda_version("No devices were found").is_none());
+    }
+
+    #[test]
+    fn test_longest_mount_match_prefers_most_specific() {
+        let mounts = [Path::new("/"), Path::new("/home"), Path::new("/mnt/models")];
+        let path = Path::new("/home/user/.local/share/continuum/models");
+        assert_eq!(
+            longest_mount_match(path, mounts.iter().copied()),
+            Some(Path::new("/home"))
+        );
+
+        let external = Path::new("/mnt/models/phi-3-mini");
+        assert_eq!(
+            longest_mount_match(external, mounts.iter().copied()),
+            Some(Path::new("/mnt/models"))
+        );
+    }
+
+    #[test]
+    fn test_is_ssd_mapping() {
+        assert_eq!(is_ssd(DiskKind::SSD), Some(true));
+        assert_eq!(is_ssd(DiskKind::HDD), Some(false));
+        assert_eq!(is_ssd(DiskKind::Unknown(-1)), None);
+    }
+
+    #[test]
+    fn test_estimate_max_model_mb_ram_only() {
+        assert_eq!(estimate_max_model_mb(8000, None), 6000);
+        assert_eq!(estimate_max_model_mb(0, None), 0);
+    }
+
+    #[test]
+    fn test_estimate_max_model_mb_uses_larger_pool() {
+        assert_eq!(estimate_max_model_mb(8000, Some(24000)), 18000);
+        assert_eq!(estimate_max_model_mb(16000, Some(4000)), 12000);
+    }
+
     #[test]
     fn test_hardware_state_caching() {
         let state = HardwareState::new();
@@ -174,26 +889,40 @@ mod tests {
         let info = SystemInfo {
             ram_mb: 16384,
             cpu_cores: 8,
+            physical_cores: 4,
+            physical_cores_estimated: false,
+            cpu_brand: "Test CPU".to_string(),
+            cpu_features: vec!["avx2".to_string()],
             storage_available_mb: 512_000,
+            swap_total_mb: 4096,
+            swap_used_mb: 1024,
         };
         state.cache_system(info);
 
         // Cache should now be populated
         let cached = state.get_cached_system();
         assert!(cached.is_some());
-        assert_eq!(cached.unwrap().ram_mb, 16384);
+        let cached = cached.unwrap();
+        assert_eq!(cached.ram_mb, 16384);
+        assert_eq!(cached.swap_total_mb, 4096);
+        assert_eq!(cached.swap_used_mb, 1024);
+        assert_eq!(cached.physical_cores, 4);
+        assert!(!cached.physical_cores_estimated);
+        assert_eq!(cached.cpu_brand, "Test CPU");
+        assert_eq!(cached.cpu_features, ["avx2"]);
     }
 
     #[test]
-    fn test_gpu_cache_stores_none() {
+    fn test_gpu_cache_stores_empty_list() {
         let state = HardwareState::new();
 
-        // Cache None (no GPU)
-        state.cache_gpu(None);
+        // Cache an empty list (no GPU)
+        state.cache_gpus(Vec::new());
 
-        // Should retrieve Some(None) - cache hit with no GPU
-        let cached = state.get_cached_gpu();
+        // Should retrieve Some(empty) - cache hit with no GPU
+        let cached = state.get_cached_gpus();
         assert!(cached.is_some());
-        assert!(cached.unwrap().is_none());
+        assert!(cached.unwrap().is_empty());
+        assert!(cached_gpu_info(&state).is_none());
     }
 }
@@ -4,11 +4,15 @@
 //! - System RAM, CPU, and storage detection (AC1, AC3)
 //! - GPU detection via nvidia-smi (AC2)
 //! - Caching to avoid repeated system queries
+//! - Background polling with `hardware:changed` events
+//! - Model recommendations from RAM and VRAM
 //!
 //! Story 2.1: Hardware Capability Detection
 //! ADR-HARDWARE-002: Uses sysinfo crate for cross-platform detection
 
 mod commands;
+mod monitor;
+mod recommend;
 mod state;
 
 pub use commands::*;
@@ -0,0 +1,174 @@
+//! Background hardware monitoring
+//!
+//! Polls hardware at a configurable interval and emits `hardware:changed`
+//! only when values meaningfully differ from the last reported snapshot
+//! (RAM availability shifts, a GPU appears/disappears, storage drops).
+
+use super::commands::{detect_gpus, query_system_info};
+use super::state::{GpuInfo, HardwareState, SystemInfo};
+use log::{info, warn};
+use serde::Serialize;
+use std::time::Duration;
+use sysinfo::System;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Smallest allowed polling interval (nvidia-smi spawns are not free)
+pub const MIN_POLL_INTERVAL_MS: u64 = 1000;
+
+/// Change in available RAM that counts as meaningful
+const RAM_CHANGE_THRESHOLD_MB: u64 = 512;
+
+/// Drop in available storage that counts as meaningful
+const STORAGE_DROP_THRESHOLD_MB: u64 = 1024;
+
+/// Payload of the `hardware:changed` event
+#[derive(Clone, Serialize)]
+pub struct HardwareChangedEvent {
+    pub system: SystemInfo,
+    pub gpu: Option<GpuInfo>,
+    pub ram_available_mb: u64,
+}
+
+/// Take a fresh (uncached) hardware snapshot, plus every detected GPU
+fn take_snapshot() -> (HardwareChangedEvent, Vec<GpuInfo>) {
+    let mut sys = System::new();
+    sys.refresh_memory();
+
+    let gpus = detect_gpus();
+    let snapshot = HardwareChangedEvent {
+        system: query_system_info(),
+        gpu: gpus.first().cloned(),
+        ram_available_mb: sys.available_memory() / 1024 / 1024,
+    };
+    (snapshot, gpus)
+}
+
+/// Whether `next` differs enough from `prev` to notify the frontend
+pub fn has_meaningful_change(prev: &HardwareChangedEvent, next: &HardwareChangedEvent) -> bool {
+    let ram_changed =
+        prev.ram_available_mb.abs_diff(next.ram_available_mb) >= RAM_CHANGE_THRESHOLD_MB;
+    let gpu_changed = prev.gpu.as_ref().map(|g| &g.name) != next.gpu.as_ref().map(|g| &g.name);
+    let storage_dropped = prev
+        .system
+        .storage_available_mb
+        .saturating_sub(next.system.storage_available_mb)
+        >= STORAGE_DROP_THRESHOLD_MB;
+
+    ram_changed || gpu_changed || storage_dropped
+}
+
+/// Start polling hardware, replacing any monitor already running
+pub fn start(app: &AppHandle, state: &HardwareState, interval_ms: u64) {
+    let interval = Duration::from_millis(interval_ms.max(MIN_POLL_INTERVAL_MS));
+    let app = app.clone();
+
+    let handle = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // Baseline is the last reported snapshot so gradual drift still triggers
+        let mut baseline: Option<HardwareChangedEvent> = None;
+
+        loop {
+            ticker.tick().await;
+
+            let (snapshot, gpus) = match tokio::task::spawn_blocking(take_snapshot).await {
+                Ok(polled) => polled,
+                Err(e) => {
+                    warn!("Hardware poll failed: {e}");
+                    continue;
+                },
+            };
+
+            // Keep the command caches warm with the fresh values
+            let state = app.state::<HardwareState>();
+            state.cache_system(snapshot.system.clone());
+            state.cache_gpus(gpus);
+
+            match &baseline {
+                None => baseline = Some(snapshot),
+                Some(prev) if has_meaningful_change(prev, &snapshot) => {
+                    let _ = app.emit("hardware:changed", snapshot.clone());
+                    baseline = Some(snapshot);
+                },
+                Some(_) => {},
+            }
+        }
+    });
+
+    state.set_monitor(handle);
+    info!("Hardware monitoring started ({}ms)", interval.as_millis());
+}
+
+/// Stop the background monitor, returning whether one was running
+pub fn stop(state: &HardwareState) -> bool {
+    let stopped = state.stop_monitor();
+    if stopped {
+        info!("Hardware monitoring stopped");
+    }
+    stopped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::GpuVendor;
+
+    fn snapshot(ram_available_mb: u64, storage_mb: u64, gpu: Option<&str>) -> HardwareChangedEvent {
+        HardwareChangedEvent {
+            system: SystemInfo {
+                ram_mb: 16384,
+                cpu_cores: 8,
+                physical_cores: 4,
+                physical_cores_estimated: false,
+                cpu_brand: String::new(),
+                cpu_features: Vec::new(),
+                storage_available_mb: storage_mb,
+                swap_total_mb: 0,
+                swap_used_mb: 0,
+            },
+            gpu: gpu.map(|name| GpuInfo {
+                index: 0,
+                name: name.to_string(),
+                vram_mb: 8192,
+                compute_capable: true,
+                vendor: GpuVendor::Nvidia,
+                driver_version: None,
+                cuda_version: None,
+                vram_used_mb: None,
+                vram_free_mb: None,
+            }),
+            ram_available_mb,
+        }
+    }
+
+    #[test]
+    fn test_small_fluctuations_are_ignored() {
+        let prev = snapshot(8000, 100_000, None);
+        let next = snapshot(8100, 99_900, None);
+        assert!(!has_meaningful_change(&prev, &next));
+    }
+
+    #[test]
+    fn test_ram_threshold_crossing_is_reported() {
+        let prev = snapshot(8000, 100_000, None);
+        assert!(has_meaningful_change(&prev, &snapshot(7000, 100_000, None)));
+        assert!(has_meaningful_change(&prev, &snapshot(9000, 100_000, None)));
+    }
+
+    #[test]
+    fn test_gpu_hotplug_is_reported() {
+        let prev = snapshot(8000, 100_000, None);
+        let next = snapshot(8000, 100_000, Some("NVIDIA GeForce RTX 4090"));
+        assert!(has_meaningful_change(&prev, &next));
+        assert!(has_meaningful_change(&next, &prev));
+    }
+
+    #[test]
+    fn test_storage_drop_is_reported_but_growth_is_not() {
+        let prev = snapshot(8000, 100_000, None);
+        assert!(has_meaningful_change(&prev, &snapshot(8000, 98_000, None)));
+        assert!(!has_meaningful_change(
+            &prev,
+            &snapshot(8000, 102_000, None)
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+//! Model recommendations from detected hardware
+//!
+//! Compares each candidate's memory requirements against RAM and VRAM so the
+//! download UI can gray out models that won't run and highlight the best fit.
+
+use serde::{Deserialize, Serialize};
+
+/// Share of memory a model may need and still fit comfortably
+const COMFORTABLE_SHARE: f64 = 0.8;
+
+/// Memory a model needs to run (sent by the download UI)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRequirement {
+    pub model_id: String,
+    pub min_ram_mb: u64,
+    /// 0 for models that run on the CPU alone
+    pub min_vram_mb: u64,
+}
+
+/// A candidate annotated with whether it fits this machine
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ModelRecommendation {
+    #[serde(flatten)]
+    pub requirement: ModelRequirement,
+    pub fits: bool,
+    /// Human-readable explanation of `fits`
+    pub reason: String,
+    /// Set on the largest candidate that fits comfortably, at most one
+    pub recommended: bool,
+}
+
+/// Annotate candidates against `ram_mb` and, when a GPU exists, `vram_mb`
+pub fn recommend(
+    candidates: Vec<ModelRequirement>,
+    ram_mb: u64,
+    vram_mb: Option<u64>,
+) -> Vec<ModelRecommendation> {
+    let mut recommendations: Vec<ModelRecommendation> = candidates
+        .into_iter()
+        .map(|requirement| {
+            let (fits, reason) = assess(&requirement, ram_mb, vram_mb);
+            ModelRecommendation {
+                requirement,
+                fits,
+                reason,
+                recommended: false,
+            }
+        })
+        .collect();
+
+    // Largest by requirements; the first wins a tie
+    let best = recommendations
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.fits && is_comfortable(&r.requirement, ram_mb, vram_mb))
+        .max_by_key(|(i, r)| {
+            (
+                r.requirement.min_vram_mb,
+                r.requirement.min_ram_mb,
+                std::cmp::Reverse(*i),
+            )
+        })
+        .map(|(i, _)| i);
+    if let Some(best) = best {
+        recommendations[best].recommended = true;
+    }
+
+    recommendations
+}
+
+/// Whether the requirement fits, and why
+fn assess(requirement: &ModelRequirement, ram_mb: u64, vram_mb: Option<u64>) -> (bool, String) {
+    if requirement.min_ram_mb > ram_mb {
+        return (
+            false,
+            format!(
+                "Needs {} MB of RAM; this machine has {ram_mb} MB",
+                requirement.min_ram_mb
+            ),
+        );
+    }
+    if requirement.min_vram_mb > 0 {
+        match vram_mb {
+            None => {
+                return (
+                    false,
+                    format!(
+                        "Needs {} MB of VRAM; no GPU detected",
+                        requirement.min_vram_mb
+                    ),
+                );
+            },
+            Some(vram) if requirement.min_vram_mb > vram => {
+                return (
+                    false,
+                    format!(
+                        "Needs {} MB of VRAM; {vram} MB available",
+                        requirement.min_vram_mb
+                    ),
+                );
+            },
+            Some(_) => {},
+        }
+    }
+
+    if is_comfortable(requirement, ram_mb, vram_mb) {
+        (true, "Fits with room to spare".to_string())
+    } else {
+        (true, "Fits, but leaves little memory headroom".to_string())
+    }
+}
+
+/// Whether the requirement stays within the comfortable share of each pool
+#[allow(clippy::cast_precision_loss)] // MB values fit in f64
+fn is_comfortable(requirement: &ModelRequirement, ram_mb: u64, vram_mb: Option<u64>) -> bool {
+    let within =
+        |needed: u64, available: u64| needed as f64 <= available as f64 * COMFORTABLE_SHARE;
+    within(requirement.min_ram_mb, ram_mb)
+        && (requirement.min_vram_mb == 0
+            || vram_mb.is_some_and(|vram| within(requirement.min_vram_mb, vram)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(model_id: &str, min_ram_mb: u64, min_vram_mb: u64) -> ModelRequirement {
+        ModelRequirement {
+            model_id: model_id.to_string(),
+            min_ram_mb,
+            min_vram_mb,
+        }
+    }
+
+    #[test]
+    fn test_largest_comfortable_model_is_recommended() {
+        let results = recommend(
+            vec![
+                candidate("q4", 4_000, 4_000),
+                candidate("q8", 7_000, 7_000),
+                candidate("f16", 15_000, 15_000),
+            ],
+            16_000,
+            Some(8_000),
+        );
+
+        assert!(results[0].fits && results[0].recommended);
+        // Fits, but 7000 of 8000 MB VRAM is past the comfortable share
+        assert!(results[1].fits && !results[1].recommended);
+        assert!(!results[2].fits && !results[2].recommended);
+        assert!(results[2].reason.contains("VRAM"));
+    }
+
+    #[test]
+    fn test_vram_requirement_without_gpu_does_not_fit() {
+        let results = recommend(
+            vec![candidate("cpu", 2_000, 0), candidate("gpu", 2_000, 4_000)],
+            16_000,
+            None,
+        );
+
+        assert!(results[0].fits && results[0].recommended);
+        assert!(!results[1].fits);
+        assert!(results[1].reason.contains("no GPU"));
+    }
+
+    #[test]
+    fn test_nothing_recommended_when_nothing_is_comfortable() {
+        let results = recommend(vec![candidate("big", 15_000, 0)], 16_000, None);
+
+        assert!(results[0].fits);
+        assert!(!results[0].recommended);
+        assert!(recommend(Vec::new(), 16_000, None).is_empty());
+    }
+}
@@ -3,33 +3,95 @@
 //! Caches hardware detection results to avoid re-querying every call.
 //! Story 2.1: Hardware Capability Detection
 
-// Option<Option<T>> is intentional: outer Option = cache miss, inner Option = no GPU detected
-#![allow(clippy::option_option)]
-
 use log::warn;
 use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
 
 /// System information from sysinfo crate
 #[derive(Clone, Serialize)]
 pub struct SystemInfo {
     pub ram_mb: u64,
+    /// Logical cores, hyperthreads included
     pub cpu_cores: usize,
+    /// Physical cores, the better guide to inference parallelism
+    pub physical_cores: usize,
+    /// `true` when the physical count was unavailable and is `cpu_cores` instead
+    pub physical_cores_estimated: bool,
+    /// CPU brand string, e.g. "AMD Ryzen 9 7950X 16-Core Processor" (empty if unknown)
+    pub cpu_brand: String,
+    /// SIMD extensions that speed up GGUF inference, e.g. `["avx2", "fma"]`
+    pub cpu_features: Vec<String>,
     pub storage_available_mb: u64,
+    /// Total swap space in MB (0 when swap is disabled or unreported)
+    pub swap_total_mb: u64,
+    /// Swap currently in use in MB
+    pub swap_used_mb: u64,
+}
+
+/// Who made the GPU, so the frontend can tailor its messaging
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuVendor {
+    Nvidia,
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))] // Only detected on macOS
+    Apple,
 }
 
-/// GPU information (NVIDIA via nvidia-smi)
+/// GPU information (NVIDIA via nvidia-smi, Apple Silicon via system_profiler)
 #[derive(Clone, Serialize)]
 pub struct GpuInfo {
+    /// Position among the detected GPUs (nvidia-smi's index; 0 = primary)
+    pub index: u32,
     pub name: String,
+    /// Dedicated VRAM, or the unified memory shared with the CPU on Apple Silicon
     pub vram_mb: u64,
     pub compute_capable: bool,
+    pub vendor: GpuVendor,
+    /// Driver version reported by nvidia-smi (None for non-NVIDIA backends)
+    pub driver_version: Option<String>,
+    /// Highest CUDA runtime version the driver supports (None if unavailable)
+    pub cuda_version: Option<String>,
+    /// VRAM in use by all processes (None for non-NVIDIA backends)
+    pub vram_used_mb: Option<u64>,
+    /// VRAM still available to load a model (None for non-NVIDIA backends)
+    pub vram_free_mb: Option<u64>,
+}
+
+/// Live GPU telemetry
+///
+/// `get_gpu_stats` always queries afresh; `get_gpu_info` takes its VRAM
+/// usage from the same reading, cached for a few seconds.
+/// Fields are `None` when the backend can't report them (e.g. "[N/A]").
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GpuStats {
+    pub index: u32,
+    pub utilization_percent: Option<u32>,
+    pub temperature_c: Option<u32>,
+    pub memory_used_mb: Option<u64>,
+    pub memory_total_mb: Option<u64>,
+    pub power_watts: Option<f32>,
 }
 
-/// Cache duration for hardware info (30 seconds)
+/// Disk hosting a given path (e.g. the models directory)
+#[derive(Clone, Serialize)]
+pub struct DiskInfo {
+    pub mount_point: String,
+    pub total_mb: u64,
+    pub available_mb: u64,
+    /// `true` for SSD, `false` for HDD, `None` when the kind is unknown
+    pub is_ssd: Option<bool>,
+}
+
+/// Default cache duration for hardware info (30 seconds)
 /// Lower than polling interval (60s) to ensure fresh data on demand
-const CACHE_DURATION: Duration = Duration::from_secs(30);
+const DEFAULT_CACHE_DURATION_MS: u64 = 30_000;
+
+/// Upper bound on how long VRAM usage stays cached (5 seconds)
+/// Free VRAM shifts as other apps allocate, so it can't share the 30s cache
+const GPU_MEMORY_CACHE_DURATION_MS: u64 = 5_000;
 
 /// Cached hardware information
 struct CachedInfo<T> {
@@ -45,19 +107,24 @@ impl<T> CachedInfo<T> {
         }
     }
 
-    fn is_valid(&self) -> bool {
-        self.timestamp.is_some_and(|t| t.elapsed() < CACHE_DURATION)
+    fn is_valid(&self, ttl: Duration) -> bool {
+        self.timestamp.is_some_and(|t| t.elapsed() < ttl)
     }
 
     fn set(&mut self, data: T) {
         self.data = Some(data);
         self.timestamp = Some(Instant::now());
     }
+
+    /// Expire the entry now so the next read re-queries
+    const fn invalidate(&mut self) {
+        self.timestamp = None;
+    }
 }
 
 impl<T: Clone> CachedInfo<T> {
-    fn get(&self) -> Option<T> {
-        if self.is_valid() {
+    fn get(&self, ttl: Duration) -> Option<T> {
+        if self.is_valid(ttl) {
             self.data.clone()
         } else {
             None
@@ -68,7 +135,14 @@ impl<T: Clone> CachedInfo<T> {
 /// Hardware state for caching detection results
 pub struct HardwareState {
     system_cache: Mutex<CachedInfo<SystemInfo>>,
-    gpu_cache: Mutex<CachedInfo<Option<GpuInfo>>>,
+    /// Every detected GPU (empty when there is none)
+    gpu_cache: Mutex<CachedInfo<Vec<GpuInfo>>>,
+    /// Telemetry for the VRAM usage in `gpu_cache`, matched by index
+    gpu_memory_cache: Mutex<CachedInfo<Vec<GpuStats>>>,
+    /// How long cached entries stay valid, in milliseconds (runtime adjustable)
+    cache_duration_ms: AtomicU64,
+    /// Background polling task (see `monitor`)
+    monitor: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl HardwareState {
@@ -76,13 +150,64 @@ impl HardwareState {
         Self {
             system_cache: Mutex::new(CachedInfo::new()),
             gpu_cache: Mutex::new(CachedInfo::new()),
+            gpu_memory_cache: Mutex::new(CachedInfo::new()),
+            cache_duration_ms: AtomicU64::new(DEFAULT_CACHE_DURATION_MS),
+            monitor: Mutex::new(None),
+        }
+    }
+
+    /// How long cached system and GPU info stays valid
+    pub fn cache_duration(&self) -> Duration {
+        Duration::from_millis(self.cache_duration_ms.load(Ordering::Relaxed))
+    }
+
+    /// How long cached VRAM usage stays valid (never longer than the main cache)
+    pub fn gpu_memory_cache_duration(&self) -> Duration {
+        self.cache_duration()
+            .min(Duration::from_millis(GPU_MEMORY_CACHE_DURATION_MS))
+    }
+
+    /// Change the cache lifetime; applies to entries already cached too
+    ///
+    /// Zero disables caching, so every query hits sysinfo/nvidia-smi.
+    pub fn set_cache_duration(&self, duration: Duration) {
+        let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        self.cache_duration_ms.store(ms, Ordering::Relaxed);
+    }
+
+    /// Store the running monitor task, aborting any previous one
+    pub fn set_monitor(&self, handle: JoinHandle<()>) {
+        match self.monitor.lock() {
+            Ok(mut monitor) => {
+                if let Some(previous) = monitor.replace(handle) {
+                    previous.abort();
+                }
+            },
+            Err(e) => {
+                warn!("Hardware monitor mutex poisoned: {e}");
+                handle.abort();
+            },
+        }
+    }
+
+    /// Abort the running monitor task, returning whether one existed
+    pub fn stop_monitor(&self) -> bool {
+        match self.monitor.lock() {
+            Ok(mut monitor) => monitor.take().is_some_and(|handle| {
+                handle.abort();
+                true
+            }),
+            Err(e) => {
+                warn!("Hardware monitor mutex poisoned: {e}");
+                false
+            },
         }
     }
 
     /// Get cached system info or return None if cache expired
     pub fn get_cached_system(&self) -> Option<SystemInfo> {
         match self.system_cache.lock() {
-            Ok(cache) => cache.get(),
+            Ok(cache) => cache.get(self.cache_duration()),
             Err(e) => {
                 warn!("Hardware cache mutex poisoned (system): {e}");
                 None
@@ -98,10 +223,10 @@ impl HardwareState {
         }
     }
 
-    /// Get cached GPU info or return None if cache expired
-    pub fn get_cached_gpu(&self) -> Option<Option<GpuInfo>> {
+    /// Get the cached GPU list or return None if cache expired
+    pub fn get_cached_gpus(&self) -> Option<Vec<GpuInfo>> {
         match self.gpu_cache.lock() {
-            Ok(cache) => cache.get(),
+            Ok(cache) => cache.get(self.cache_duration()),
             Err(e) => {
                 warn!("Hardware cache mutex poisoned (gpu): {e}");
                 None
@@ -109,13 +234,52 @@ impl HardwareState {
         }
     }
 
-    /// Cache GPU info (including None for no GPU)
-    pub fn cache_gpu(&self, info: Option<GpuInfo>) {
+    /// Cache the GPU list (including an empty one for no GPU)
+    pub fn cache_gpus(&self, gpus: Vec<GpuInfo>) {
         match self.gpu_cache.lock() {
-            Ok(mut cache) => cache.set(info),
+            Ok(mut cache) => cache.set(gpus),
             Err(e) => warn!("Hardware cache mutex poisoned (gpu write): {e}"),
         }
     }
+
+    /// Expire cached system info so the next query hits sysinfo
+    pub fn invalidate_system(&self) {
+        match self.system_cache.lock() {
+            Ok(mut cache) => cache.invalidate(),
+            Err(e) => warn!("Hardware cache mutex poisoned (system invalidate): {e}"),
+        }
+    }
+
+    /// Expire cached GPU info, VRAM usage included, so the next query re-detects
+    pub fn invalidate_gpus(&self) {
+        match self.gpu_cache.lock() {
+            Ok(mut cache) => cache.invalidate(),
+            Err(e) => warn!("Hardware cache mutex poisoned (gpu invalidate): {e}"),
+        }
+        match self.gpu_memory_cache.lock() {
+            Ok(mut cache) => cache.invalidate(),
+            Err(e) => warn!("Hardware cache mutex poisoned (gpu memory invalidate): {e}"),
+        }
+    }
+
+    /// Get cached VRAM usage or return None if cache expired
+    pub fn get_cached_gpu_memory(&self) -> Option<Vec<GpuStats>> {
+        match self.gpu_memory_cache.lock() {
+            Ok(cache) => cache.get(self.gpu_memory_cache_duration()),
+            Err(e) => {
+                warn!("Hardware cache mutex poisoned (gpu memory): {e}");
+                None
+            },
+        }
+    }
+
+    /// Cache VRAM usage
+    pub fn cache_gpu_memory(&self, memory: Vec<GpuStats>) {
+        match self.gpu_memory_cache.lock() {
+            Ok(mut cache) => cache.set(memory),
+            Err(e) => warn!("Hardware cache mutex poisoned (gpu memory write): {e}"),
+        }
+    }
 }
 
 impl Default for HardwareState {
@@ -123,3 +287,94 @@ impl Default for HardwareState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+
+    fn system_info() -> SystemInfo {
+        SystemInfo {
+            ram_mb: 16_384,
+            cpu_cores: 8,
+            physical_cores: 4,
+            physical_cores_estimated: false,
+            cpu_brand: String::new(),
+            cpu_features: Vec::new(),
+            storage_available_mb: 100_000,
+            swap_total_mb: 0,
+            swap_used_mb: 0,
+        }
+    }
+
+    #[test]
+    fn test_cache_expiry_honors_configured_duration() {
+        let state = HardwareState::new();
+        assert_eq!(
+            state.cache_duration(),
+            Duration::from_millis(DEFAULT_CACHE_DURATION_MS)
+        );
+
+        state.cache_system(system_info());
+        state.cache_gpus(Vec::new());
+        assert!(state.get_cached_system().is_some());
+        assert!(state.get_cached_gpus().is_some_and(|gpus| gpus.is_empty()));
+
+        // Shortening the TTL expires entries that are already cached
+        state.set_cache_duration(Duration::from_millis(20));
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(state.get_cached_system().is_none());
+        assert!(state.get_cached_gpus().is_none());
+
+        // Zero disables caching entirely
+        state.set_cache_duration(Duration::ZERO);
+        state.cache_system(system_info());
+        assert!(state.get_cached_system().is_none());
+        state.cache_gpu_memory(Vec::new());
+        assert!(state.get_cached_gpu_memory().is_none());
+    }
+
+    #[test]
+    fn test_invalidate_clears_only_the_requested_cache() {
+        let state = HardwareState::new();
+        state.cache_system(system_info());
+        state.cache_gpus(Vec::new());
+        state.cache_gpu_memory(Vec::new());
+
+        state.invalidate_gpus();
+        assert!(state.get_cached_gpus().is_none());
+        assert!(state.get_cached_gpu_memory().is_none());
+        assert!(state.get_cached_system().is_some());
+
+        state.invalidate_system();
+        assert!(state.get_cached_system().is_none());
+
+        // Caching again works as before
+        state.cache_system(system_info());
+        assert!(state.get_cached_system().is_some());
+    }
+
+    #[test]
+    fn test_gpu_memory_cache_is_shorter_than_static_cache() {
+        let state = HardwareState::new();
+        assert_eq!(
+            state.gpu_memory_cache_duration(),
+            Duration::from_millis(GPU_MEMORY_CACHE_DURATION_MS)
+        );
+
+        let stats = GpuStats {
+            index: 0,
+            utilization_percent: None,
+            temperature_c: None,
+            memory_used_mb: Some(2_048),
+            memory_total_mb: Some(24_576),
+            power_watts: None,
+        };
+        state.cache_gpu_memory(vec![stats.clone()]);
+        assert_eq!(state.get_cached_gpu_memory(), Some(vec![stats]));
+
+        // A shorter main cache also caps the memory cache
+        state.set_cache_duration(Duration::from_secs(1));
+        assert_eq!(state.gpu_memory_cache_duration(), Duration::from_secs(1));
+    }
+}
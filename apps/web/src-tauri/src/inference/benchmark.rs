@@ -0,0 +1,76 @@
+//! Throughput benchmark helpers for `benchmark_model`
+//!
+//! Uses a fixed prompt and seed so runs are comparable across models and
+//! quantizations on the same hardware.
+
+use std::time::Duration;
+
+/// Sentence repeated to build the benchmark prompt
+const BENCHMARK_SENTENCE: &str = "The quick brown fox jumps over the lazy dog. ";
+
+/// Fixed sampler seed so every benchmark run generates the same way
+pub const BENCHMARK_SEED: u64 = 42;
+
+/// Aggregate numbers from one benchmark run
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct BenchmarkResult {
+    pub model_id: String,
+    /// Tokens in the prompt actually evaluated
+    pub prompt_tokens: usize,
+    /// Tokens generated (may be fewer than requested if the model stops early)
+    pub generated_tokens: usize,
+    /// Prompt evaluation speed, measured up to the first generated token
+    pub prompt_tokens_per_sec: f64,
+    /// Generation speed after the first token
+    pub generation_tokens_per_sec: f64,
+    /// Wall time for the whole run
+    pub total_ms: u64,
+}
+
+/// Build a prompt of at least `target_tokens` tokens
+///
+/// Repeats a standard sentence, using `count_tokens` (the model's tokenizer)
+/// to stop as soon as the target is reached.
+pub fn build_prompt<E>(
+    target_tokens: usize,
+    mut count_tokens: impl FnMut(&str) -> Result<usize, E>,
+) -> Result<String, E> {
+    let mut prompt = String::new();
+    while count_tokens(&prompt)? < target_tokens {
+        prompt.push_str(BENCHMARK_SENTENCE);
+    }
+    Ok(prompt)
+}
+
+/// Tokens per second, 0 when nothing was measured
+#[allow(clippy::cast_precision_loss)] // Token counts are far below 2^52
+pub fn tokens_per_sec(tokens: usize, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if tokens == 0 || secs <= 0.0 {
+        0.0
+    } else {
+        tokens as f64 / secs
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_prompt_reaches_target() {
+        let words = |text: &str| Ok::<_, ()>(text.split_whitespace().count());
+        let prompt = build_prompt(20, words).unwrap();
+        let count = words(&prompt).unwrap();
+        assert!((20..20 + words(BENCHMARK_SENTENCE).unwrap()).contains(&count));
+        assert_eq!(build_prompt(20, words).unwrap(), prompt);
+    }
+
+    #[test]
+    fn test_tokens_per_sec() {
+        assert!((tokens_per_sec(50, Duration::from_millis(500)) - 100.0).abs() < f64::EPSILON);
+        assert!(tokens_per_sec(0, Duration::from_secs(1)).abs() < f64::EPSILON);
+        assert!(tokens_per_sec(10, Duration::ZERO).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,122 @@
+//! Multi-turn chat messages for `send_chat_message`
+//!
+//! The session itself is a Kalosm `Chat` held in `InferenceState`; it keeps
+//! the conversation history and applies the model's chat template, so callers
+//! only send the new turn.
+//! Reference: stack-knowledge/kalosm/language-model/docs/chat.md
+
+use super::commands::InferenceError;
+
+/// One message in a chat turn (matches `ChatMessage` in TypeScript)
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct ChatMessage {
+    /// Only `"user"` is accepted; assistant replies come from the session
+    pub role: String,
+    pub content: String,
+}
+
+/// Treat a blank system prompt as none, so `""` adds no empty system turn
+pub fn normalize_system_prompt(prompt: Option<String>) -> Option<String> {
+    prompt.filter(|p| !p.trim().is_empty())
+}
+
+/// Refuse to change the system prompt of a running session
+///
+/// Passing no prompt, or the one the session started with, is allowed.
+pub fn check_system_prompt_unchanged(
+    active: Option<&str>,
+    requested: Option<&str>,
+) -> Result<(), InferenceError> {
+    match requested {
+        Some(requested) if Some(requested) != active => Err(InferenceError::system_prompt_locked()),
+        _ => Ok(()),
+    }
+}
+
+/// Combine the messages of one turn into the text sent to the session
+///
+/// Several user messages are joined with blank lines. Assistant messages are
+/// rejected because the session records its own replies, and system messages
+/// because the system prompt is fixed when the session starts.
+pub fn user_turn(messages: &[ChatMessage]) -> Result<String, InferenceError> {
+    if messages.is_empty() {
+        return Err(InferenceError::invalid_parameters(
+            "messages must contain at least one user message",
+        ));
+    }
+    if messages.iter().any(|m| m.role == "system") {
+        return Err(InferenceError::system_prompt_locked());
+    }
+    if let Some(message) = messages.iter().find(|m| m.role != "user") {
+        return Err(InferenceError::invalid_parameters(&format!(
+            "unsupported chat role '{}'; only 'user' messages can be sent",
+            message.role
+        )));
+    }
+
+    Ok(messages
+        .iter()
+        .map(|m| m.content.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_user_turn_joins_user_messages() {
+        let turn = user_turn(&[message("user", "Hi"), message("user", "Still there?")]).unwrap();
+        assert_eq!(turn, "Hi\n\nStill there?");
+    }
+
+    #[test]
+    fn test_user_turn_rejects_other_roles_and_empty_turns() {
+        assert!(user_turn(&[]).is_err());
+        assert!(user_turn(&[message("user", "Hi"), message("assistant", "Hello")]).is_err());
+        assert!(user_turn(&[message("system", "Be terse")]).is_err());
+    }
+
+    #[test]
+    fn test_empty_system_prompt_is_none() {
+        assert_eq!(normalize_system_prompt(Some(String::new())), None);
+        assert_eq!(normalize_system_prompt(Some(" \n ".to_string())), None);
+        assert_eq!(normalize_system_prompt(None), None);
+
+        // An empty prompt mid-session doesn't count as a change
+        let requested = normalize_system_prompt(Some(String::new()));
+        assert!(check_system_prompt_unchanged(Some("Be terse"), requested.as_deref()).is_ok());
+    }
+
+    #[test]
+    fn test_long_system_prompt_is_kept_whole() {
+        let long = "You are a terse assistant. ".repeat(10_000);
+        assert_eq!(
+            normalize_system_prompt(Some(long.clone())),
+            Some(long.clone())
+        );
+        assert!(check_system_prompt_unchanged(Some(&long), Some(&long)).is_ok());
+
+        // Any difference, even at the very end, is a change
+        let edited = format!("{long}!");
+        assert!(check_system_prompt_unchanged(Some(&long), Some(&edited)).is_err());
+    }
+
+    #[test]
+    fn test_new_system_prompt_mid_session_is_rejected() {
+        assert!(check_system_prompt_unchanged(None, None).is_ok());
+        assert!(check_system_prompt_unchanged(Some("Be terse"), None).is_ok());
+        assert!(check_system_prompt_unchanged(Some("Be terse"), Some("Be terse")).is_ok());
+        assert!(check_system_prompt_unchanged(Some("Be terse"), Some("Be chatty")).is_err());
+        assert!(check_system_prompt_unchanged(None, Some("Be terse")).is_err());
+    }
+}
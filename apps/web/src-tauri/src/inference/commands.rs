@@ -6,17 +6,278 @@
 //! Story 2.4: Updated to load models from local downloads directory
 //! using FileSource::Local instead of hardcoded Llama::phi_3().
 
-use super::state::{InferenceState, ModelStatus};
-use crate::downloads::DownloadState;
-use futures_util::StreamExt;
-use kalosm::language::{FileSource, Llama, LlamaSource, TextCompletionModelExt};
+use super::benchmark::{self, BenchmarkResult};
+use super::chat::{self, ChatMessage};
+use super::gguf::{self, GgufError, GgufMetadata};
+use super::logprobs::{LogprobRecorder, LogprobSampler, SampledToken};
+use super::params::GenerationParams;
+use super::presets::{self, GenerationPresets};
+use super::state::{
+    ChatSession, GenerationInfo, InferenceState, LatencyStats, MemoryUsage, ModelStatus,
+    STUCK_GENERATION_THRESHOLD,
+};
+use super::stop::{StopScan, StopSequences};
+use super::structured;
+use crate::downloads::{
+    stored_hash, weights_path, weights_paths, DownloadState, ModelMetadata, DEFAULT_WEIGHTS_FILE,
+};
+use crate::settings::AppSettings;
+use crate::verification::{self, HashAlgorithm, VerificationProgress};
+use futures_util::{Stream, StreamExt};
+use kalosm::language::{
+    ChatModelExt, FileSource, GenerationParameters, Llama, LlamaSource, RegexParser,
+    TextCompletionModelExt,
+};
+use std::path::Path;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, State};
+use std::time::{Duration, Instant};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Emitter, Manager, State};
 
 /// Token payload for streaming events
+///
+/// Stays the lean `{ text, request_id }` unless log-probabilities or detailed
+/// timing were requested.
 #[derive(Clone, serde::Serialize)]
 pub struct TokenPayload {
     pub text: String,
+    /// The generation this token belongs to
+    pub request_id: String,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<TokenLogprobs>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<TokenTiming>,
+}
+
+/// Per-token log-probability info (only sent when `logprobs` is enabled)
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct TokenLogprobs {
+    /// Log-probability of the emitted text (summed if it spans several tokens)
+    pub logprob: f32,
+    /// Most likely tokens at that position with their log-probabilities
+    pub top_alternatives: Vec<(String, f32)>,
+}
+
+impl TokenLogprobs {
+    /// Turn the recorded token IDs into text with `decode`
+    fn decode(sampled: SampledToken, decode: impl Fn(u32) -> String) -> Self {
+        Self {
+            logprob: sampled.logprob,
+            top_alternatives: sampled
+                .top
+                .into_iter()
+                .map(|(id, logprob)| (decode(id), logprob))
+                .collect(),
+        }
+    }
+}
+
+/// When a token arrived (only sent when `detailed_timing` is enabled)
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct TokenTiming {
+    /// Zero-based position of the token in the generation
+    pub token_index: u64,
+    /// Time since the previous token, or since the request for the first
+    pub ms_since_last: u64,
+    /// Time since the request started
+    pub cumulative_ms: u64,
+}
+
+/// Stamps each emitted token with a `TokenTiming`
+struct TokenTimer {
+    started: Instant,
+    last: Instant,
+    tokens: u64,
+}
+
+impl TokenTimer {
+    const fn new(started: Instant) -> Self {
+        Self {
+            started,
+            last: started,
+            tokens: 0,
+        }
+    }
+
+    /// Timing for a token emitted at `now`
+    fn tick(&mut self, now: Instant) -> TokenTiming {
+        let to_ms = |d: Duration| u64::try_from(d.as_millis()).unwrap_or(u64::MAX);
+        let timing = TokenTiming {
+            token_index: self.tokens,
+            ms_since_last: to_ms(now.duration_since(self.last)),
+            cumulative_ms: to_ms(now.duration_since(self.started)),
+        };
+        self.tokens += 1;
+        self.last = now;
+        timing
+    }
+}
+
+impl TokenPayload {
+    /// Build the payload for one emitted chunk of text
+    fn new(text: String, request_id: &str) -> Self {
+        Self {
+            text,
+            request_id: request_id.to_string(),
+            logprobs: None,
+            timing: None,
+        }
+    }
+}
+
+/// Why a generation stream ended (matches `InferenceFinishReason` in TypeScript)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FinishReason {
+    /// The model finished on its own
+    Completed,
+    /// `abort_inference` stopped generation early
+    Aborted,
+    /// The `max_tokens` cap was reached
+    #[serde(rename = "max_tokens")]
+    MaxTokens,
+    /// One of `params.stop_sequences` was generated
+    #[serde(rename = "stop_sequence")]
+    StopSequence,
+}
+
+/// Payload for the `inference:token_batch` event
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct TokenBatchPayload {
+    /// The generation these tokens belong to
+    pub request_id: String,
+    /// Consecutive tokens, in generation order
+    pub tokens: Vec<String>,
+}
+
+/// Groups streamed tokens into fixed-size batches
+struct TokenBatcher {
+    size: usize,
+    request_id: String,
+    pending: Vec<String>,
+}
+
+impl TokenBatcher {
+    fn new(size: usize, request_id: &str) -> Self {
+        Self {
+            size,
+            request_id: request_id.to_string(),
+            pending: Vec::with_capacity(size),
+        }
+    }
+
+    /// Add a token, returning a full batch once `size` tokens are pending
+    fn push(&mut self, token: String) -> Option<TokenBatchPayload> {
+        self.pending.push(token);
+        (self.pending.len() >= self.size)
+            .then(|| self.flush())
+            .flatten()
+    }
+
+    /// Take whatever is pending (e.g. at the end of the stream)
+    fn flush(&mut self) -> Option<TokenBatchPayload> {
+        (!self.pending.is_empty()).then(|| TokenBatchPayload {
+            request_id: self.request_id.clone(),
+            tokens: std::mem::replace(&mut self.pending, Vec::with_capacity(self.size)),
+        })
+    }
+}
+
+/// How often `generate` emits `inference:stats` while tokens are streaming
+const STATS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Payload for the `inference:stats` event, also the final summary (AC5)
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct GenerationStats {
+    pub tokens_generated: usize,
+    /// Time since `generate` started, including prompt evaluation
+    pub elapsed_ms: u64,
+    pub tokens_per_second: f64,
+}
+
+/// Counts streamed tokens and decides when the next stats report is due
+struct ThroughputTracker {
+    started: Instant,
+    last_report: Instant,
+    tokens: usize,
+}
+
+impl ThroughputTracker {
+    const fn new(started: Instant) -> Self {
+        Self {
+            started,
+            last_report: started,
+            tokens: 0,
+        }
+    }
+
+    /// Count a token, returning stats once `STATS_INTERVAL` has passed
+    fn record_token(&mut self, now: Instant) -> Option<GenerationStats> {
+        self.tokens += 1;
+        if now.duration_since(self.last_report) < STATS_INTERVAL {
+            return None;
+        }
+        self.last_report = now;
+        Some(self.stats(now))
+    }
+
+    /// Totals at `now`
+    fn stats(&self, now: Instant) -> GenerationStats {
+        let elapsed = now.duration_since(self.started);
+        GenerationStats {
+            tokens_generated: self.tokens,
+            elapsed_ms: u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+            tokens_per_second: benchmark::tokens_per_sec(self.tokens, elapsed),
+        }
+    }
+}
+
+/// Payload for the `inference:complete` event
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CompletePayload {
+    pub finish_reason: FinishReason,
+    /// Shorthand for `finish_reason == "aborted"`
+    pub aborted: bool,
+    /// Everything emitted before an abort, so the UI can keep it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial_text: Option<String>,
+    /// Time from `generate` start to the first emitted token (AC2)
+    pub first_token_ms: Option<u64>,
+    /// First generation since the model was loaded (cold) vs. later (warm)
+    pub cold: bool,
+    /// Final throughput summary (set by `generate`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<GenerationStats>,
+    /// The generation that finished
+    pub request_id: String,
+}
+
+impl CompletePayload {
+    /// Payload for a generation that ended for `finish_reason`
+    const fn finished(finish_reason: FinishReason, first_token_ms: Option<u64>) -> Self {
+        Self {
+            finish_reason,
+            aborted: false,
+            partial_text: None,
+            first_token_ms,
+            cold: false,
+            stats: None,
+            request_id: String::new(),
+        }
+    }
+
+    /// Payload for a generation stopped by `abort_inference`
+    const fn aborted(partial_text: String, first_token_ms: Option<u64>) -> Self {
+        Self {
+            finish_reason: FinishReason::Aborted,
+            aborted: true,
+            partial_text: Some(partial_text),
+            first_token_ms,
+            cold: false,
+            stats: None,
+            request_id: String::new(),
+        }
+    }
 }
 
 /// Error codes for user-friendly messages
@@ -31,6 +292,11 @@ pub enum InferenceErrorCode {
     InferenceTimeout,
     GenerationAborted,
     ModelLoadFailed,
+    InvalidParameters,
+    ContextOverflow,
+    IntegrityFailed,
+    Busy,
+    EmbeddingsUnsupported,
     UnknownError,
 }
 
@@ -40,6 +306,17 @@ pub struct InferenceError {
     pub code: InferenceErrorCode,
     pub message: String,
     pub details: Option<String>,
+    /// Token counts behind a `CONTEXT_OVERFLOW`, so the UI can show them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overflow: Option<ContextOverflowDetails>,
+}
+
+/// Numbers that didn't fit the context window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct ContextOverflowDetails {
+    pub prompt_tokens: u64,
+    pub max_tokens: u64,
+    pub context_length: u64,
 }
 
 impl InferenceError {
@@ -48,6 +325,7 @@ impl InferenceError {
             code: InferenceErrorCode::ModelLoadFailed,
             message: "Couldn't load the model. Please restart and try again.".to_string(),
             details: Some(details.to_string()),
+            overflow: None,
         }
     }
 
@@ -56,6 +334,7 @@ impl InferenceError {
             code: InferenceErrorCode::ModelNotFound,
             message: "Model not loaded. Please wait for it to load first.".to_string(),
             details: None,
+            overflow: None,
         }
     }
 
@@ -64,6 +343,7 @@ impl InferenceError {
             code: InferenceErrorCode::ModelNotFound,
             message: format!("Model '{model_id}' not found. Please download it first."),
             details: Some(format!("Model file not found for ID: {model_id}")),
+            overflow: None,
         }
     }
 
@@ -72,15 +352,138 @@ impl InferenceError {
             code: InferenceErrorCode::OomError,
             message: "Not enough memory for this model. Try a smaller model.".to_string(),
             details: Some(details.to_string()),
+            overflow: None,
+        }
+    }
+
+    pub fn model_busy() -> Self {
+        Self {
+            code: InferenceErrorCode::ModelLoadFailed,
+            message: "A model is already loading. Please wait for it to finish.".to_string(),
+            details: Some("load_model called while another load is in progress".to_string()),
+            overflow: None,
+        }
+    }
+
+    pub fn generation_busy() -> Self {
+        Self {
+            code: InferenceErrorCode::Busy,
+            message: "Another response is still being generated. Please wait for it to finish."
+                .to_string(),
+            details: Some(
+                "generation requested with queue: false while one is running".to_string(),
+            ),
+            overflow: None,
+        }
+    }
+
+    pub fn invalid_parameters(details: &str) -> Self {
+        Self {
+            code: InferenceErrorCode::InvalidParameters,
+            message: "Invalid generation settings. Please adjust them and try again.".to_string(),
+            details: Some(details.to_string()),
+            overflow: None,
+        }
+    }
+
+    pub fn context_overflow(prompt_tokens: u64, max_tokens: u64, context_length: u64) -> Self {
+        Self {
+            code: InferenceErrorCode::ContextOverflow,
+            message: "This conversation is too long for the model. Shorten it and try again."
+                .to_string(),
+            details: Some(format!(
+                "{prompt_tokens} prompt tokens + {max_tokens} max tokens exceeds the \
+                 {context_length}-token context window"
+            )),
+            overflow: Some(ContextOverflowDetails {
+                prompt_tokens,
+                max_tokens,
+                context_length,
+            }),
+        }
+    }
+
+    pub fn no_chat_session() -> Self {
+        Self {
+            code: InferenceErrorCode::InvalidParameters,
+            message: "No chat is active. Start a new chat and try again.".to_string(),
+            details: Some("send_chat_message called before start_chat_session".to_string()),
+            overflow: None,
+        }
+    }
+
+    pub fn system_prompt_locked() -> Self {
+        Self {
+            code: InferenceErrorCode::InvalidParameters,
+            message:
+                "The system prompt can't change during a chat. Reset the chat to use a new one."
+                    .to_string(),
+            details: Some("system prompt sent to an active chat session".to_string()),
+            overflow: None,
+        }
+    }
+
+    pub fn invalid_model_file(error: &GgufError) -> Self {
+        Self {
+            code: InferenceErrorCode::ModelLoadFailed,
+            message:
+                "This model file is damaged or isn't a supported model. Please re-download it."
+                    .to_string(),
+            details: Some(error.to_string()),
+            overflow: None,
+        }
+    }
+
+    pub fn integrity_failed(model_id: &str, details: &str) -> Self {
+        Self {
+            code: InferenceErrorCode::IntegrityFailed,
+            message: format!(
+                "The files for '{model_id}' are damaged or changed on disk. Please re-download it."
+            ),
+            details: Some(details.to_string()),
+            overflow: None,
+        }
+    }
+
+    pub fn embeddings_unsupported(model_id: &str) -> Self {
+        Self {
+            code: InferenceErrorCode::EmbeddingsUnsupported,
+            message: "The loaded model can't produce embeddings.".to_string(),
+            details: Some(format!(
+                "{model_id} is loaded as a Llama text model, which has no embedding output"
+            )),
+            overflow: None,
+        }
+    }
+
+    pub fn split_model_unsupported(model_id: &str, shards: u64) -> Self {
+        Self {
+            code: InferenceErrorCode::ModelLoadFailed,
+            message: "Models split across several files must be downloaded in the app to load."
+                .to_string(),
+            details: Some(format!(
+                "{model_id} is one of {shards} GGUF files; only downloaded split models are merged"
+            )),
+            overflow: None,
+        }
+    }
+
+    pub fn structured_output_invalid(details: &str) -> Self {
+        Self {
+            code: InferenceErrorCode::UnknownError,
+            message: "The model's reply didn't match the requested format. Please try again."
+                .to_string(),
+            details: Some(details.to_string()),
+            overflow: None,
         }
     }
 
-    #[allow(dead_code)]
     pub fn generation_aborted() -> Self {
         Self {
             code: InferenceErrorCode::GenerationAborted,
             message: "Generation stopped.".to_string(),
             details: None,
+            overflow: None,
         }
     }
 
@@ -90,6 +493,7 @@ impl InferenceError {
             code: InferenceErrorCode::UnknownError,
             message: "Something went wrong. Please try again.".to_string(),
             details: Some(details.to_string()),
+            overflow: None,
         }
     }
 }
@@ -100,42 +504,176 @@ impl InferenceError {
 ///
 /// # Arguments
 /// * `model_id` - The model identifier (e.g., "phi-3-mini")
+/// * `context_length` - Optional smaller context window in tokens; capped at
+///   the model's trained context length
+/// * `verify_before_load` - Check the weights against the hash stored at
+///   download time first, failing with `INTEGRITY_FAILED` on a mismatch
+/// * `on_verify_progress` - Progress of that check (sent for files >500MB)
+/// * `warmup` - Generate one throwaway token after loading so the first real
+///   generation is warm (default `true`); `model:ready` is emitted once it's
+///   done. Pass `false` for the fastest load.
 ///
 /// File structure:
 /// ```
 /// models/{model_id}/
-///   model.gguf       <- main model weights
+///   model.json       <- metadata naming the weights file
+///   model.gguf       <- main model weights (or the imported filename)
 ///   tokenizer.json   <- tokenizer for the model
 /// ```
 /// Both files are downloaded together by the download manager (Story 2.3).
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn load_model(
+    app: AppHandle,
     state: State<'_, Arc<InferenceState>>,
     download_state: State<'_, DownloadState>,
     model_id: String,
+    context_length: Option<u64>,
+    verify_before_load: Option<bool>,
+    on_verify_progress: Option<Channel<VerificationProgress>>,
+    warmup: Option<bool>,
 ) -> Result<(), InferenceError> {
-    // Unload any existing model first (ADR-MODEL-002)
-    {
-        let mut model_guard = state.model.write().await;
-        if model_guard.is_some() {
-            *model_guard = None;
-            log::info!("Unloaded previous model before loading new one");
-        }
+    if verify_before_load.unwrap_or(false) {
+        // Runs before anything is unloaded, so a bad file leaves the current model in place
+        let model_dir = download_state.models_dir().join(&model_id);
+        let id = model_id.clone();
+        tokio::task::spawn_blocking(move || {
+            verify_model_weights(&model_dir, &id, on_verify_progress.as_ref())
+        })
+        .await
+        .map_err(|e| {
+            InferenceError::model_load_failed(&format!("Verification task failed: {e}"))
+        })??;
     }
 
-    state.set_status(ModelStatus::Loading).await;
+    load_model_from_disk(
+        &app,
+        &state,
+        &download_state,
+        &model_id,
+        context_length,
+        warmup.unwrap_or(true),
+    )
+    .await
+}
 
-    // Resolve model directory path
-    let model_dir = download_state.models_dir().join(&model_id);
+/// Prompt for the throwaway warm-up generation
+const WARMUP_PROMPT: &str = "Hello";
 
-    // Resolve model file path (Task 1.3)
-    let model_path = model_dir.join("model.gguf");
+/// Payload of `model:ready`, emitted once a loaded model is warmed up
+#[derive(Clone, serde::Serialize)]
+pub struct ModelReadyPayload {
+    pub model_id: String,
+    /// How long the warm-up took, `None` if skipped, failed, or not needed
+    pub warmup_ms: Option<u64>,
+}
 
-    // Verify model exists before loading (Task 1.6)
-    if !model_path.exists() {
-        state.set_status(ModelStatus::Error).await;
-        log::error!("Model file not found: {}", model_path.display());
-        return Err(InferenceError::model_not_found(&model_id));
+/// Warm up the newly active model if asked, then emit `model:ready`
+///
+/// Every way of loading or switching models ends here. Models brought back
+/// from standby have generated before, so their callers skip the warm-up.
+async fn announce_ready(app: &AppHandle, state: &InferenceState, model_id: &str, warmup: bool) {
+    let warmup_ms = if warmup { warm_up(state).await } else { None };
+    let payload = ModelReadyPayload {
+        model_id: model_id.to_string(),
+        warmup_ms,
+    };
+    if let Err(e) = app.emit("model:ready", payload) {
+        log::error!("Failed to emit model:ready event: {e}");
+    }
+}
+
+/// Generate one token from a fixed prompt to prime caches and GPU kernels
+///
+/// Clears the cold flag, since the first real generation after it is warm,
+/// but its time isn't recorded in the latency stats. Takes a generation turn
+/// so it never runs alongside a streaming one. Returns its duration.
+async fn warm_up(state: &InferenceState) -> Option<u64> {
+    let _slot = state.generation_slot(true).await;
+    let started = Instant::now();
+    let model_guard = state.model.read().await;
+    let model = model_guard.as_ref()?;
+
+    let sampler = GenerationParameters::default().with_max_length(1);
+    let mut stream = std::pin::pin!(model.complete(WARMUP_PROMPT).with_sampler(sampler));
+    if stream.next().await.is_none() {
+        log::warn!("Warm-up generation produced no token");
+        return None;
+    }
+
+    let warmup_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+    state.take_cold().await;
+    log::info!("Model warmed up in {warmup_ms}ms");
+    Some(warmup_ms)
+}
+
+/// Check a model's weights against the SHA-256 stored when it was verified
+///
+/// Models without a stored hash (e.g. imported without one) pass unchecked.
+fn verify_model_weights(
+    model_dir: &Path,
+    model_id: &str,
+    on_progress: Option<&Channel<VerificationProgress>>,
+) -> Result<(), InferenceError> {
+    let Some(expected) = stored_hash(model_dir) else {
+        log::warn!("No stored hash for {model_id}, loading it unverified");
+        return Ok(());
+    };
+
+    let path = weights_path(model_dir);
+    match verification::verify_integrity_with_progress(
+        &path,
+        &expected,
+        HashAlgorithm::Sha256,
+        false,
+        on_progress,
+        None,
+    ) {
+        Ok(result) if result.verified => {
+            log::info!("Verified {model_id} before loading");
+            Ok(())
+        },
+        Ok(result) => {
+            log::error!("Refusing to load {model_id}: checksum mismatch");
+            Err(InferenceError::integrity_failed(
+                model_id,
+                &format!(
+                    "Checksum mismatch: expected {}, got {}",
+                    result.expected_hash, result.computed_hash
+                ),
+            ))
+        },
+        Err(e) if e.kind == "file_not_found" => Err(InferenceError::model_not_found(model_id)),
+        Err(e) => Err(InferenceError::integrity_failed(model_id, &e.message)),
+    }
+}
+
+/// Load a downloaded model, emitting `inference:status` on each transition
+///
+/// Shared by the `load_model` command and the startup prewarm hook.
+/// Remembers the model as last used so it can be prewarmed next launch.
+/// Ends with `model:ready`, after a warm-up if `warmup` is set.
+pub async fn load_model_from_disk(
+    app: &AppHandle,
+    state: &InferenceState,
+    download_state: &DownloadState,
+    model_id: &str,
+    context_length: Option<u64>,
+    warmup: bool,
+) -> Result<(), InferenceError> {
+    if context_length == Some(0) {
+        return Err(InferenceError::invalid_parameters(
+            "context_length must be at least 1",
+        ));
+    }
+
+    // Checked before anything is unloaded, so the current model stays put
+    let model_dir = download_state.models_dir().join(model_id);
+
+    // Verify model exists before loading (Task 1.6), every shard of a split model
+    if let Some(missing) = weights_paths(&model_dir).iter().find(|path| !path.exists()) {
+        log::error!("Model file not found: {}", missing.display());
+        return Err(InferenceError::model_not_found(model_id));
     }
 
     // Resolve tokenizer path (downloaded alongside model by Story 2.3)
@@ -143,31 +681,246 @@ pub async fn load_model(
 
     // Verify tokenizer exists
     if !tokenizer_path.exists() {
-        state.set_status(ModelStatus::Error).await;
         log::error!("Tokenizer file not found: {}", tokenizer_path.display());
         return Err(InferenceError::model_load_failed(&format!(
             "Tokenizer not found for {model_id}. Please re-download the model."
         )));
     }
 
+    // Refuse overlapping loads; the model lock is never held across build()
+    if !state.begin_loading().await {
+        log::warn!("Ignoring load_model({model_id}): another load is in progress");
+        return Err(InferenceError::model_busy());
+    }
+
+    emit_status(app, &ModelStatus::Loading);
+
+    let shards = weights_paths(&model_dir);
+    if shards.len() > 1 {
+        if let Err(e) = merge_shards(model_id, &model_dir, shards).await {
+            let status = if state.is_loaded().await {
+                ModelStatus::Loaded
+            } else {
+                ModelStatus::Unloaded
+            };
+            set_status(app, state, status).await;
+            return Err(e);
+        }
+    }
+    // Resolve model file path (Task 1.3); the weights filename comes from model.json
+    let model_path = weights_path(&model_dir);
+
+    // Already loaded on standby: switch to it instead of rebuilding
+    if context_length.is_none() && state.activate_standby(model_id).await {
+        log::info!("Activated standby model {model_id}");
+        set_status(app, state, ModelStatus::Loaded).await;
+        remember_last_model(download_state, model_id);
+        announce_ready(app, state, model_id, false).await;
+        return Ok(());
+    }
+    make_room_for(state, model_id).await;
+
+    load_model_files(
+        app,
+        state,
+        model_id,
+        model_path,
+        tokenizer_path,
+        context_length,
+    )
+    .await?;
+
+    remember_last_model(download_state, model_id);
+    announce_ready(app, state, model_id, warmup).await;
+    Ok(())
+}
+
+/// Merge a split model's files into one `model.gguf`, replacing them
+///
+/// Kalosm loads a single GGUF file, so this runs the first time a split model
+/// loads. Each shard was verified when it downloaded; the merged file is
+/// written under a temporary name and only then swapped in.
+async fn merge_shards(
+    model_id: &str,
+    model_dir: &Path,
+    shards: Vec<std::path::PathBuf>,
+) -> Result<(), InferenceError> {
+    log::info!("Merging {} files of {model_id}", shards.len());
+    let merged = model_dir.join(DEFAULT_WEIGHTS_FILE);
+    let temp = model_dir.join(format!("{DEFAULT_WEIGHTS_FILE}.merging"));
+    let model_dir = model_dir.to_path_buf();
+    let result = tokio::task::spawn_blocking(move || {
+        gguf::merge_split(&shards, &temp).map_err(|e| e.to_string())?;
+        std::fs::rename(&temp, &merged).map_err(|e| format!("Rename failed: {e}"))?;
+        ModelMetadata::new(DEFAULT_WEIGHTS_FILE).save(&model_dir)?;
+        for shard in &shards {
+            if let Err(e) = std::fs::remove_file(shard) {
+                log::warn!("Failed to remove merged shard {}: {e}", shard.display());
+            }
+        }
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+
+    result.map_err(|e| {
+        log::error!("Failed to merge {model_id}: {e}");
+        InferenceError::model_load_failed(&format!("Failed to merge split model: {e}"))
+    })
+}
+
+/// Record `model_id` as last used so it can be prewarmed next launch
+fn remember_last_model(download_state: &DownloadState, model_id: &str) {
+    if let Err(e) = AppSettings::update(download_state.app_data_dir(), |s| {
+        s.last_model_id = Some(model_id.to_string());
+    }) {
+        log::warn!("Failed to remember last model: {e}");
+    }
+}
+
+/// Free a slot for `model_id`, which is about to be built
+///
+/// Reloading the active model releases it. Otherwise the active model moves
+/// to standby, and least-recently-used standby models are evicted so the new
+/// one fits under `max_loaded_models` (by default the new one plus the
+/// previous one on standby). Runs before the build so memory is freed first.
+async fn make_room_for(state: &InferenceState, model_id: &str) {
+    if state.current_model_id().await.as_deref() == Some(model_id) {
+        state.release_model().await;
+        log::info!("Unloaded {model_id} before reloading it");
+    } else if let Some(previous) = state.park_active_model().await {
+        log::info!("Moved {previous} to standby before loading {model_id}");
+    }
+    // A standby copy is about to be replaced by the fresh build
+    state.unload(model_id).await;
+    state.evict_standby(1).await;
+}
+
+/// Load a model from GGUF and tokenizer files outside the models directory
+///
+/// For shared model libraries that shouldn't be copied into the app. Both
+/// files are checked before the current model is unloaded; the model is
+/// tracked under its GGUF path as `model_id` and isn't prewarmed on startup.
+///
+/// # Arguments
+/// * `gguf_path` - Path to the GGUF weights
+/// * `tokenizer_path` - Path to the matching tokenizer.json
+/// * `context_length` - Optional context window override (see `load_model`)
+/// * `warmup` - Warm up before `model:ready` (see `load_model`)
+#[tauri::command]
+pub async fn load_model_from_path(
+    app: AppHandle,
+    state: State<'_, Arc<InferenceState>>,
+    gguf_path: String,
+    tokenizer_path: String,
+    context_length: Option<u64>,
+    warmup: Option<bool>,
+) -> Result<(), InferenceError> {
+    if context_length == Some(0) {
+        return Err(InferenceError::invalid_parameters(
+            "context_length must be at least 1",
+        ));
+    }
+
+    let model_path = std::path::PathBuf::from(&gguf_path);
+    let tokenizer_path = std::path::PathBuf::from(tokenizer_path);
+    for path in [&model_path, &tokenizer_path] {
+        if let Err(e) = std::fs::File::open(path) {
+            log::error!("Cannot read {}: {e}", path.display());
+            return Err(InferenceError::model_load_failed(&format!(
+                "Cannot read {}: {e}",
+                path.display()
+            )));
+        }
+    }
+
+    if !state.begin_loading().await {
+        log::warn!("Ignoring load_model_from_path({gguf_path}): another load is in progress");
+        return Err(InferenceError::model_busy());
+    }
+
+    emit_status(&app, &ModelStatus::Loading);
+
+    if context_length.is_none() && state.activate_standby(&gguf_path).await {
+        log::info!("Activated standby model {gguf_path}");
+        set_status(&app, &state, ModelStatus::Loaded).await;
+        announce_ready(&app, &state, &gguf_path, false).await;
+        return Ok(());
+    }
+    make_room_for(&state, &gguf_path).await;
+
+    load_model_files(
+        &app,
+        &state,
+        &gguf_path,
+        model_path.clone(),
+        tokenizer_path.clone(),
+        context_length,
+    )
+    .await?;
+
+    // Reloads must come back to these files, not look in the models directory
+    *state.external_files.write().await = Some((model_path, tokenizer_path));
+    announce_ready(&app, &state, &gguf_path, warmup.unwrap_or(true)).await;
+    Ok(())
+}
+
+/// Build the model from local files and install it in `state`
+///
+/// Expects `begin_loading` to have succeeded. Sets `Loaded` on success and
+/// `Error` on failure, mapping memory failures to `OOM_ERROR`.
+async fn load_model_files(
+    app: &AppHandle,
+    state: &InferenceState,
+    model_id: &str,
+    model_path: std::path::PathBuf,
+    tokenizer_path: std::path::PathBuf,
+    context_length: Option<u64>,
+) -> Result<(), InferenceError> {
     log::info!("Loading model from: {}", model_path.display());
     log::info!("Loading tokenizer from: {}", tokenizer_path.display());
 
+    let header = read_header(model_path.clone()).await;
+    // kalosm reads a single GGUF file, so a first shard alone would load
+    // with most of its tensors missing
+    if let Some(shards) = header
+        .as_ref()
+        .and_then(|h| h.split_count)
+        .filter(|&n| n > 1)
+    {
+        set_status(app, state, ModelStatus::Error).await;
+        log::error!("Refusing to load {model_id}: split into {shards} files");
+        return Err(InferenceError::split_model_unsupported(model_id, shards));
+    }
+    let trained_context = header.and_then(|h| h.context_length);
+    let memory_before = sample_memory().await;
+
     // Load model from local path using FileSource::Local
-    // Both model and tokenizer are local files managed by the app
-    let source = LlamaSource::new(FileSource::Local(model_path.clone()))
-        .with_tokenizer(FileSource::Local(tokenizer_path.clone()));
+    // Both model and tokenizer are local files (managed by the app or user-provided)
+    let source = LlamaSource::new(FileSource::Local(model_path))
+        .with_tokenizer(FileSource::Local(tokenizer_path));
 
     match Llama::builder().with_source(source).build().await {
         Ok(model) => {
+            let memory_usage = MemoryUsage::delta(memory_before, sample_memory().await);
+            log::info!("Model {model_id} memory usage: {memory_usage:?}");
+
             let mut model_guard = state.model.write().await;
             *model_guard = Some(model);
-            state.set_status(ModelStatus::Loaded).await;
+            // A session from the previous model would keep that model alive
+            *state.chat.lock().await = None;
+            *state.model_id.write().await = Some(model_id.to_string());
+            *state.memory_usage.write().await = Some(memory_usage);
+            *state.context_length.write().await =
+                effective_context_length(context_length, trained_context);
+            state.mark_cold().await;
+            set_status(app, state, ModelStatus::Loaded).await;
             log::info!("Model loaded successfully: {model_id}");
             Ok(())
         },
         Err(e) => {
-            state.set_status(ModelStatus::Error).await;
+            set_status(app, state, ModelStatus::Error).await;
             let error_msg = e.to_string();
             log::error!("Failed to load model {model_id}: {error_msg}");
 
@@ -181,71 +934,1107 @@ pub async fn load_model(
     }
 }
 
-/// Generate text with streaming via Tauri events
-/// AC2: First token within 2 seconds (warm)
-/// AC5: Generation rate >= 10 tokens/second
+/// GGUF header of the weights about to load, `None` if unreadable
+async fn read_header(model_path: std::path::PathBuf) -> Option<GgufMetadata> {
+    let result = tokio::task::spawn_blocking(move || gguf::read_metadata(&model_path))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| result.map_err(|e| e.to_string()));
+    result
+        .inspect_err(|e| log::warn!("Couldn't read GGUF metadata: {e}"))
+        .ok()
+}
+
+/// Describe a downloaded model from its GGUF header, without loading it
 ///
-/// Reference: stack-knowledge/kalosm/language-model/docs/completion.md
+/// Reads only the header, so it's quick even for multi-GB files. Returns
+/// `MODEL_NOT_FOUND` if the model isn't downloaded and `MODEL_LOAD_FAILED`
+/// (with the reason in `details`) for a truncated or non-GGUF file.
 #[tauri::command]
-pub async fn generate(
-    app: AppHandle,
-    state: State<'_, Arc<InferenceState>>,
-    prompt: String,
-    _max_tokens: Option<usize>,
-) -> Result<(), InferenceError> {
-    // Reset abort flag
-    state.reset_abort().await;
-    state.set_status(ModelStatus::Generating).await;
-
-    // Get model - need to hold the lock for the entire generation
-    let model_guard = state.model.read().await;
-    let Some(model) = model_guard.as_ref() else {
-        state.set_status(ModelStatus::Unloaded).await;
-        return Err(InferenceError::model_not_loaded());
-    };
-
-    // Use .complete(prompt) which returns a stream
-    // Iterate with while let Some(token) = stream.next().await
-    // The stream yields String tokens directly
-    let mut stream = model.complete(&prompt);
+pub async fn read_gguf_metadata(
+    download_state: State<'_, DownloadState>,
+    model_id: String,
+) -> Result<GgufMetadata, InferenceError> {
+    let model_path = weights_path(&download_state.models_dir().join(&model_id));
+    if !model_path.exists() {
+        return Err(InferenceError::model_not_found(&model_id));
+    }
 
-    while let Some(token) = stream.next().await {
-        // Check abort flag before emitting each token
-        if state.is_abort_requested().await {
-            log::info!("Generation aborted");
-            app.emit("inference:complete", ()).ok();
-            state.set_status(ModelStatus::Loaded).await;
-            return Ok(());
-        }
+    tokio::task::spawn_blocking(move || gguf::read_metadata(&model_path))
+        .await
+        .map_err(|e| InferenceError::unknown_error(&e.to_string()))?
+        .map_err(|e| InferenceError::invalid_model_file(&e))
+}
 
-        // Emit token to frontend via Tauri event
-        let payload = TokenPayload { text: token };
-        if let Err(e) = app.emit("inference:token", payload) {
-            log::error!("Failed to emit token: {e}");
-        }
+/// Context window to enforce: the override, but never beyond the trained length
+fn effective_context_length(requested: Option<u64>, trained: Option<u64>) -> Option<u64> {
+    match (requested, trained) {
+        (Some(requested), Some(trained)) => Some(requested.min(trained)),
+        (requested, trained) => requested.or(trained),
     }
+}
 
-    app.emit("inference:complete", ()).ok();
-    state.set_status(ModelStatus::Loaded).await;
-    log::info!("Generation completed");
+/// Ensure the prompt plus the requested generation fits the context window
+fn check_context_window(
+    prompt_tokens: u64,
+    max_tokens: u64,
+    context_length: u64,
+) -> Result<(), InferenceError> {
+    if prompt_tokens.saturating_add(max_tokens) > context_length {
+        return Err(InferenceError::context_overflow(
+            prompt_tokens,
+            max_tokens,
+            context_length,
+        ));
+    }
     Ok(())
 }
 
-/// Abort ongoing generation by setting flag (checked in generate loop)
-/// AC4: Inference stops immediately on abort
-#[tauri::command]
-pub async fn abort_inference(state: State<'_, Arc<InferenceState>>) -> Result<(), InferenceError> {
-    state.request_abort().await;
-    log::info!("Abort requested");
-    Ok(())
+/// What `generate` does with a prompt too long for the context window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverflowPolicy {
+    /// Fail with `CONTEXT_OVERFLOW`
+    #[default]
+    Error,
+    /// Drop the oldest text and emit `inference:prompt_truncated`
+    Truncate,
 }
 
-/// Check if model is loaded
-/// Uses is_loaded() to verify status accuracy
+/// Tokens kept free for the reply when truncating without `max_tokens`
+const TRUNCATION_RESERVE_TOKENS: u64 = 512;
+
+/// Generation length `fit_prompt` makes room for
+///
+/// Without `max_tokens`, truncation would otherwise fill the whole window
+/// and leave the model nothing to generate into, so up to
+/// `TRUNCATION_RESERVE_TOKENS` (at most half the window) are kept free.
+fn reserved_tokens(max_tokens: u64, context_length: u64, policy: OverflowPolicy) -> u64 {
+    if max_tokens == 0 && policy == OverflowPolicy::Truncate {
+        TRUNCATION_RESERVE_TOKENS.min(context_length / 2)
+    } else {
+        max_tokens
+    }
+}
+
+/// Payload of `inference:prompt_truncated`
+#[derive(Clone, serde::Serialize)]
+pub struct PromptTruncatedPayload {
+    pub original_tokens: u64,
+    pub prompt_tokens: u64,
+}
+
+/// Check `prompt` fits the context window, truncating it if `policy` allows
+fn fit_prompt(
+    app: &AppHandle,
+    model: &Llama,
+    prompt: String,
+    max_tokens: u64,
+    context_length: u64,
+    policy: OverflowPolicy,
+) -> Result<String, InferenceError> {
+    let max_tokens = reserved_tokens(max_tokens, context_length, policy);
+    let prompt_tokens = prompt_token_offsets(model, &prompt)?.len() as u64;
+    let Err(overflow) = check_context_window(prompt_tokens, max_tokens, context_length) else {
+        return Ok(prompt);
+    };
+    // Nothing to truncate to when the generation alone fills the window
+    if policy == OverflowPolicy::Error || max_tokens >= context_length {
+        return Err(overflow);
+    }
+
+    let budget = usize::try_from(context_length - max_tokens).unwrap_or(usize::MAX);
+    let truncated = truncate_front(&prompt, budget, |text| prompt_token_offsets(model, text))
+        .map_err(|e| match e.code {
+            InferenceErrorCode::ContextOverflow => overflow,
+            _ => e,
+        })?;
+    let payload = PromptTruncatedPayload {
+        original_tokens: prompt_tokens,
+        prompt_tokens: prompt_token_offsets(model, truncated)?.len() as u64,
+    };
+    log::warn!(
+        "Truncated prompt from {} to {} tokens to fit the {context_length}-token window",
+        payload.original_tokens,
+        payload.prompt_tokens
+    );
+    if let Err(e) = app.emit("inference:prompt_truncated", payload) {
+        log::error!("Failed to emit prompt_truncated event: {e}");
+    }
+    Ok(truncated.to_string())
+}
+
+/// Count tokens the way the prompt is fed to the model
+///
+/// Shared by `count_tokens` and `generate`, so the UI's count matches the
+/// context-window check.
+fn prompt_token_offsets(model: &Llama, text: &str) -> Result<Vec<(usize, usize)>, InferenceError> {
+    model
+        .tokenizer()
+        .encode(text, true)
+        .map(|encoding| encoding.get_offsets().to_vec())
+        .map_err(|e| InferenceError::unknown_error(&format!("Failed to tokenize prompt: {e}")))
+}
+
+/// Cut text off the front of `prompt` until it encodes to `budget` tokens
+///
+/// `encode` returns each token's byte range in the text. Special tokens such
+/// as BOS come back on every re-encode, so the cut is re-checked until the
+/// prompt fits. Fails if even the last token alone is too long.
+fn truncate_front(
+    prompt: &str,
+    budget: usize,
+    encode: impl Fn(&str) -> Result<Vec<(usize, usize)>, InferenceError>,
+) -> Result<&str, InferenceError> {
+    let mut text = prompt;
+    loop {
+        let offsets = encode(text)?;
+        if offsets.len() <= budget {
+            return Ok(text);
+        }
+        // Start at the first kept token that has text, so every pass shrinks it
+        let cut = offsets[offsets.len() - budget..]
+            .iter()
+            .map(|&(start, _)| start)
+            .find(|&start| start > 0)
+            .and_then(|start| (start..=text.len()).find(|&i| text.is_char_boundary(i)))
+            .filter(|&start| start < text.len());
+        let Some(cut) = cut else {
+            return Err(InferenceError::context_overflow(
+                offsets.len() as u64,
+                0,
+                budget as u64,
+            ));
+        };
+        text = &text[cut..];
+    }
+}
+
+/// Read process RSS and GPU memory without blocking the async runtime
+async fn sample_memory() -> MemoryUsage {
+    tokio::task::spawn_blocking(|| MemoryUsage {
+        ram_mb: crate::hardware::process_rss_mb(),
+        vram_mb: crate::hardware::gpu_memory_used_mb(),
+    })
+    .await
+    .unwrap_or_default()
+}
+
+/// Update the model status and notify the frontend
+async fn set_status(app: &AppHandle, state: &InferenceState, status: ModelStatus) {
+    emit_status(app, &status);
+    state.set_status(status).await;
+}
+
+/// Emit `inference:status` so the UI tracks loads it didn't start itself
+fn emit_status(app: &AppHandle, status: &ModelStatus) {
+    if let Err(e) = app.emit("inference:status", status.clone()) {
+        log::error!("Failed to emit model status: {e}");
+    }
+}
+
+/// Enable or disable loading the last used model when the app starts
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn set_prewarm_on_startup(
+    download_state: State<'_, DownloadState>,
+    enabled: bool,
+) -> Result<(), String> {
+    AppSettings::update(download_state.app_data_dir(), |s| {
+        s.prewarm_on_startup = enabled;
+    })
+    .map(|_| ())
+}
+
+/// Startup hook: load the last used model in the background if enabled
+///
+/// A missing model is logged and skipped; it never fails startup.
+pub fn prewarm_last_model(app: &AppHandle, settings: &AppSettings) {
+    if !settings.prewarm_on_startup {
+        return;
+    }
+    let Some(model_id) = settings.last_model_id.clone() else {
+        log::info!("Prewarm enabled but no model has been used yet");
+        return;
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<Arc<InferenceState>>();
+        let download_state = app.state::<DownloadState>();
+
+        let model_path = weights_path(&download_state.models_dir().join(&model_id));
+        if !model_path.exists() {
+            log::warn!("Skipping prewarm: model '{model_id}' is no longer downloaded");
+            return;
+        }
+
+        log::info!("Prewarming last used model: {model_id}");
+        let loaded =
+            load_model_from_disk(&app, &state, &download_state, &model_id, None, true).await;
+        if let Err(e) = loaded {
+            log::warn!("Prewarm of {model_id} failed: {}", e.message);
+        }
+    });
+}
+
+/// Generate text with streaming via Tauri events
+/// AC2: First token within 2 seconds (warm)
+/// AC5: Generation rate >= 10 tokens/second
+///
+/// While tokens stream, `inference:stats` reports the running throughput about
+/// every 500ms; `inference:complete` carries the final `stats` alongside the
+/// first-token latency.
+///
+/// # Arguments
+/// * `prompt` - Text to complete
+/// * `max_tokens` - Cap on generated tokens; `inference:complete` reports
+///   `max_tokens` when it's reached. The prompt plus this must fit the model's
+///   context window (see `on_overflow`). `None` = no cap.
+/// * `params` - Sampling options (temperature, top_p/top_k or Mirostat, seed,
+///   repetition penalty) and stop sequences; unset fields keep the current
+///   defaults. `repeat_penalty` must be > 0: 1.0 is off and 1.05-1.3 curbs
+///   loops, applied over the last `repeat_last_n` tokens. A matched stop
+///   sequence ends generation with `stop_sequence` and isn't emitted.
+/// * `logprobs` - Attach `logprob`/`top_alternatives` to each `inference:token`
+///   payload, taken from the model's distribution before temperature and
+///   truncation. Costs a softmax over the full vocabulary per token, so leave
+///   it off for normal chat.
+/// * `preset` - Name of a saved preset to start from; fields set in `params`
+///   override it
+/// * `batch_tokens` - Emit `inference:token_batch` events of this many tokens
+///   instead of one `inference:token` per token. Cuts IPC traffic for fast
+///   models at the cost of up to `batch_tokens - 1` tokens of display lag.
+///   The last partial batch is sent before `inference:complete`. Can't be
+///   combined with `logprobs`.
+/// * `detailed_timing` - Add `token_index`, `ms_since_last`, and
+///   `cumulative_ms` to each `inference:token` payload, e.g. to graph
+///   inter-token latency and spot stalls. Can't be combined with
+///   `batch_tokens`.
+/// * `on_overflow` - `error` (default) fails with `CONTEXT_OVERFLOW` when the
+///   prompt doesn't fit; `truncate` drops text from the front until it does
+///   and emits `inference:prompt_truncated`. Without `max_tokens`, truncation
+///   leaves room for a 512-token reply
+/// * `request_id` - Tags every `inference:token`, `inference:token_batch`, and
+///   `inference:complete` event of this generation (a UUID if omitted)
+/// * `queue` - Wait for a running generation to finish first (default), or
+///   fail with `BUSY` when `false`. `abort_inference` only stops the running one.
+///
+/// Reference: stack-knowledge/kalosm/language-model/docs/completion.md
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn generate(
+    app: AppHandle,
+    state: State<'_, Arc<InferenceState>>,
+    download_state: State<'_, DownloadState>,
+    prompt: String,
+    max_tokens: Option<u64>,
+    logprobs: Option<bool>,
+    params: Option<GenerationParams>,
+    preset: Option<String>,
+    batch_tokens: Option<usize>,
+    detailed_timing: Option<bool>,
+    on_overflow: Option<OverflowPolicy>,
+    request_id: Option<String>,
+    queue: Option<bool>,
+) -> Result<(), InferenceError> {
+    let started = Instant::now();
+    let logprobs = logprobs.unwrap_or(false);
+    let mut params = resolve_params(&download_state, params, preset, max_tokens)?;
+    let request_id = request_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let mut batcher = match batch_tokens {
+        Some(0) => {
+            return Err(InferenceError::invalid_parameters(
+                "batch_tokens must be at least 1",
+            ))
+        },
+        Some(_) if logprobs => {
+            return Err(InferenceError::invalid_parameters(
+                "batch_tokens cannot be combined with logprobs",
+            ))
+        },
+        Some(_) if detailed_timing == Some(true) => {
+            return Err(InferenceError::invalid_parameters(
+                "batch_tokens cannot be combined with detailed_timing",
+            ))
+        },
+        Some(size) if size > 1 => Some(TokenBatcher::new(size, &request_id)),
+        _ => None,
+    };
+
+    let _slot = take_generation_slot(&state, queue).await?;
+    // Lets the status watchdog tell this run apart from a stuck status
+    let _generation = state.begin_generation();
+
+    // Reset abort flag
+    state.reset_abort().await;
+    state.set_status(ModelStatus::Generating).await;
+
+    // Get model - need to hold the lock for the entire generation
+    let model_guard = state.model.read().await;
+    let Some(model) = model_guard.as_ref() else {
+        state.set_status(ModelStatus::Unloaded).await;
+        return Err(InferenceError::model_not_loaded());
+    };
+
+    let prompt = match state.context_length().await {
+        Some(context_length) => {
+            match fit_prompt(
+                &app,
+                model,
+                prompt,
+                max_tokens.unwrap_or(0),
+                context_length,
+                on_overflow.unwrap_or_default(),
+            ) {
+                Ok(prompt) => prompt,
+                Err(e) => {
+                    state.set_status(ModelStatus::Loaded).await;
+                    return Err(e);
+                },
+            }
+        },
+        None => prompt,
+    };
+
+    // Use .complete(prompt) which returns a stream
+    // Iterate with while let Some(token) = stream.next().await
+    // The stream yields String tokens directly
+    let recorder = logprobs.then(LogprobRecorder::default);
+    let sampler = LogprobSampler::new(
+        build_sampler(&state, &mut params, max_tokens).await,
+        recorder.clone(),
+    );
+    let stream = model.complete(&prompt).with_sampler(sampler);
+    let tokenizer = model.tokenizer();
+    let decode = |id: u32| tokenizer.decode(&[id], false).unwrap_or_default();
+
+    let cold = state.take_cold().await;
+    let emit_batch = |batch: TokenBatchPayload| {
+        if let Err(e) = app.emit("inference:token_batch", batch) {
+            log::error!("Failed to emit token batch: {e}");
+        }
+    };
+    let mut throughput = ThroughputTracker::new(started);
+    let mut timer = (detailed_timing == Some(true)).then(|| TokenTimer::new(started));
+    let mut complete = stream_tokens(
+        stream,
+        &state,
+        started,
+        max_tokens,
+        &params.stop_sequences,
+        |token| {
+            if let Some(stats) = throughput.record_token(Instant::now()) {
+                if let Err(e) = app.emit("inference:stats", stats) {
+                    log::error!("Failed to emit generation stats: {e}");
+                }
+            }
+            if let Some(batcher) = batcher.as_mut() {
+                if let Some(batch) = batcher.push(token) {
+                    emit_batch(batch);
+                }
+                return;
+            }
+            // Emit token to frontend via Tauri event
+            let mut payload = TokenPayload::new(token, &request_id);
+            payload.logprobs = recorder
+                .as_ref()
+                .and_then(LogprobRecorder::take_chunk)
+                .map(|sampled| TokenLogprobs::decode(sampled, decode));
+            payload.timing = timer.as_mut().map(|timer| timer.tick(Instant::now()));
+            if let Err(e) = app.emit("inference:token", payload) {
+                log::error!("Failed to emit token: {e}");
+            }
+        },
+    )
+    .await;
+    // Deliver the tail so the UI has everything `partial_text` reports
+    if let Some(batch) = batcher.as_mut().and_then(TokenBatcher::flush) {
+        emit_batch(batch);
+    }
+
+    complete.cold = cold;
+    complete.stats = Some(throughput.stats(Instant::now()));
+    complete.request_id = request_id;
+    if let Some(first_token_ms) = complete.first_token_ms {
+        state.record_first_token(cold, first_token_ms).await;
+    }
+
+    match complete.finish_reason {
+        FinishReason::Aborted => log::info!("Generation aborted"),
+        FinishReason::Completed => log::info!("Generation completed"),
+        FinishReason::MaxTokens => log::info!("Generation stopped at max_tokens"),
+        FinishReason::StopSequence => log::info!("Generation stopped at a stop sequence"),
+    }
+    app.emit("inference:complete", complete).ok();
+    state.set_status(ModelStatus::Loaded).await;
+    Ok(())
+}
+
+/// Start a chat session on the loaded model
+///
+/// The session keeps the conversation history and formats it with the
+/// model's chat template, starting with the system prompt if one is given.
+/// Plain completion via `generate` is unaffected.
+///
+/// Calling this while a session is active keeps that session. Passing a
+/// different system prompt then fails: call `reset_chat_session` first.
+///
+/// # Arguments
+/// * `system_prompt` - Instructions kept for every turn (e.g. "You are a
+///   terse assistant"); blank means none
+#[tauri::command]
+pub async fn start_chat_session(
+    state: State<'_, Arc<InferenceState>>,
+    system_prompt: Option<String>,
+) -> Result<(), InferenceError> {
+    let system_prompt = chat::normalize_system_prompt(system_prompt);
+
+    let model_guard = state.model.read().await;
+    let Some(model) = model_guard.as_ref() else {
+        return Err(InferenceError::model_not_loaded());
+    };
+
+    let mut chat_guard = state.chat.lock().await;
+    if let Some(session) = chat_guard.as_ref() {
+        return chat::check_system_prompt_unchanged(
+            session.system_prompt.as_deref(),
+            system_prompt.as_deref(),
+        );
+    }
+
+    // The system prompt is resent with every turn, so it alone must fit
+    let mut history_tokens = 0;
+    if let Some(prompt) = &system_prompt {
+        history_tokens = model
+            .tokenizer()
+            .encode(prompt.as_str(), true)
+            .map_err(|e| {
+                InferenceError::unknown_error(&format!("Failed to tokenize system prompt: {e}"))
+            })?
+            .len() as u64
+            + CHAT_MESSAGE_OVERHEAD_TOKENS;
+        if let Some(context_length) = state.context_length().await {
+            check_context_window(history_tokens, 0, context_length)?;
+        }
+    }
+
+    let mut session = model.chat();
+    if let Some(prompt) = &system_prompt {
+        session = session.with_system_prompt(prompt);
+    }
+    *chat_guard = Some(ChatSession {
+        chat: session,
+        system_prompt,
+        history_tokens,
+    });
+    log::info!("Chat session started");
+    Ok(())
+}
+
+/// Apply `preset` under `params` and validate the result and `max_tokens`
+///
+/// Shared by `generate` and `generate_sync` so both sample identically.
+fn resolve_params(
+    download_state: &DownloadState,
+    params: Option<GenerationParams>,
+    preset: Option<String>,
+    max_tokens: Option<u64>,
+) -> Result<GenerationParams, InferenceError> {
+    let params = params.unwrap_or_default();
+    let params = match preset {
+        Some(name) => presets::load(download_state.app_data_dir())
+            .remove(&name)
+            .ok_or_else(|| {
+                InferenceError::invalid_parameters(&format!("Unknown generation preset '{name}'"))
+            })?
+            .merged_with(&params),
+        None => params,
+    };
+    params.validate()?;
+    if max_tokens == Some(0) {
+        return Err(InferenceError::invalid_parameters(
+            "max_tokens must be at least 1",
+        ));
+    }
+    Ok(params)
+}
+
+/// Build the Kalosm sampler for a generation about to start
+///
+/// Resolves the seed first and records it, so the run can be reported by
+/// `get_last_generation_info` and replayed.
+async fn build_sampler(
+    state: &InferenceState,
+    params: &mut GenerationParams,
+    max_tokens: Option<u64>,
+) -> GenerationParameters {
+    let seed = params.resolve_seed();
+    log::info!("Generating with seed {seed}");
+    *state.last_generation.write().await = Some(GenerationInfo {
+        model_id: state.current_model_id().await,
+        seed,
+    });
+
+    let mut sampler = params.to_sampler();
+    if let Some(max_tokens) = max_tokens {
+        sampler = sampler.with_max_length(u32::try_from(max_tokens).unwrap_or(u32::MAX));
+    }
+    sampler
+}
+
+/// Wait for a turn to generate, or fail with `BUSY` if `queue` is false
+///
+/// Holding the turn for the whole generation keeps two requests' token
+/// events from interleaving.
+async fn take_generation_slot(
+    state: &InferenceState,
+    queue: Option<bool>,
+) -> Result<tokio::sync::MutexGuard<'_, ()>, InferenceError> {
+    state
+        .generation_slot(queue.unwrap_or(true))
+        .await
+        .ok_or_else(InferenceError::generation_busy)
+}
+
+/// Generate text and return it whole instead of streaming events
+///
+/// For scripts and tests. Takes the same `prompt`, `max_tokens`, `params`,
+/// `preset`, and `queue` as `generate` and samples identically; the stop
+/// sequence that ended generation isn't included. `abort_inference` makes
+/// it fail with `GENERATION_ABORTED`, the text so far in `details`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_sync(
+    app: AppHandle,
+    state: State<'_, Arc<InferenceState>>,
+    download_state: State<'_, DownloadState>,
+    prompt: String,
+    max_tokens: Option<u64>,
+    params: Option<GenerationParams>,
+    preset: Option<String>,
+    queue: Option<bool>,
+) -> Result<String, InferenceError> {
+    let started = Instant::now();
+    let mut params = resolve_params(&download_state, params, preset, max_tokens)?;
+
+    let _slot = take_generation_slot(&state, queue).await?;
+    let _generation = state.begin_generation();
+    state.reset_abort().await;
+    state.set_status(ModelStatus::Generating).await;
+
+    let model_guard = state.model.read().await;
+    let Some(model) = model_guard.as_ref() else {
+        state.set_status(ModelStatus::Unloaded).await;
+        return Err(InferenceError::model_not_loaded());
+    };
+    if let Some(context_length) = state.context_length().await {
+        let fitted = fit_prompt(
+            &app,
+            model,
+            prompt.clone(),
+            max_tokens.unwrap_or(0),
+            context_length,
+            OverflowPolicy::Error,
+        );
+        if let Err(e) = fitted {
+            state.set_status(ModelStatus::Loaded).await;
+            return Err(e);
+        }
+    }
+
+    let sampler = build_sampler(&state, &mut params, max_tokens).await;
+    let cold = state.take_cold().await;
+    let mut text = String::new();
+    let complete = stream_tokens(
+        model.complete(&prompt).with_sampler(sampler),
+        &state,
+        started,
+        max_tokens,
+        &params.stop_sequences,
+        |token| text.push_str(&token),
+    )
+    .await;
+    if let Some(first_token_ms) = complete.first_token_ms {
+        state.record_first_token(cold, first_token_ms).await;
+    }
+    state.set_status(ModelStatus::Loaded).await;
+
+    if complete.aborted {
+        let mut error = InferenceError::generation_aborted();
+        error.details = Some(text);
+        return Err(error);
+    }
+    log::info!("Sync generation finished: {:?}", complete.finish_reason);
+    Ok(text)
+}
+
+/// Token budget for `generate_structured` when `max_tokens` is omitted
+const STRUCTURED_MAX_TOKENS: u64 = 512;
+
+/// Generate a JSON value matching `json_schema`, e.g. for tool calls
+///
+/// Tokens stream as `inference:token` events followed by
+/// `inference:complete`, as with `generate`. Sampling is constrained to
+/// JSON of the schema's shape (see `structured`), and the finished reply is
+/// validated against it; a valid value is emitted as `inference:structured`
+/// and returned.
+///
+/// # Arguments
+/// * `prompt` - What to generate
+/// * `json_schema` - JSON Schema the value must match; it must describe an
+///   object or array, and every property needs a `type` or `enum`
+/// * `max_tokens` - Token budget for the reply (default 512); running out
+///   before the value is complete fails with the reply in `details`
+/// * `request_id`, `queue` - As for `generate`
+#[tauri::command]
+pub async fn generate_structured(
+    app: AppHandle,
+    state: State<'_, Arc<InferenceState>>,
+    prompt: String,
+    json_schema: serde_json::Value,
+    max_tokens: Option<u64>,
+    request_id: Option<String>,
+    queue: Option<bool>,
+) -> Result<serde_json::Value, InferenceError> {
+    let started = Instant::now();
+    structured::check_schema(&json_schema).map_err(|e| InferenceError::invalid_parameters(&e))?;
+    let constraints = structured::schema_regex(&json_schema)
+        .and_then(|pattern| RegexParser::new(&pattern).map_err(|e| e.to_string()))
+        .map_err(|e| {
+            InferenceError::invalid_parameters(&format!("Can't enforce json_schema: {e}"))
+        })?;
+    let max_tokens = max_tokens.unwrap_or(STRUCTURED_MAX_TOKENS);
+    if max_tokens == 0 {
+        return Err(InferenceError::invalid_parameters(
+            "max_tokens must be at least 1",
+        ));
+    }
+    let request_id = request_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let _slot = take_generation_slot(&state, queue).await?;
+    let _generation = state.begin_generation();
+    state.reset_abort().await;
+    state.set_status(ModelStatus::Generating).await;
+
+    let model_guard = state.model.read().await;
+    let Some(model) = model_guard.as_ref() else {
+        state.set_status(ModelStatus::Unloaded).await;
+        return Err(InferenceError::model_not_loaded());
+    };
+
+    let prompt = structured::prompt_with_schema(&prompt, &json_schema);
+    if let Some(context_length) = state.context_length().await {
+        let fitted = fit_prompt(
+            &app,
+            model,
+            prompt.clone(),
+            max_tokens,
+            context_length,
+            OverflowPolicy::Error,
+        );
+        if let Err(e) = fitted {
+            state.set_status(ModelStatus::Loaded).await;
+            return Err(e);
+        }
+    }
+
+    let sampler = GenerationParams::default()
+        .to_sampler()
+        .with_max_length(u32::try_from(max_tokens).unwrap_or(u32::MAX));
+    let cold = state.take_cold().await;
+    let mut reply = String::new();
+    let mut complete = stream_tokens(
+        model
+            .complete(&prompt)
+            .with_constraints(constraints)
+            .with_sampler(sampler),
+        &state,
+        started,
+        Some(max_tokens),
+        &[],
+        |token| {
+            reply.push_str(&token);
+            let payload = TokenPayload::new(token, &request_id);
+            if let Err(e) = app.emit("inference:token", payload) {
+                log::error!("Failed to emit token: {e}");
+            }
+        },
+    )
+    .await;
+
+    complete.cold = cold;
+    complete.request_id = request_id;
+    if let Some(first_token_ms) = complete.first_token_ms {
+        state.record_first_token(cold, first_token_ms).await;
+    }
+    let finish_reason = complete.finish_reason;
+    app.emit("inference:complete", complete).ok();
+    state.set_status(ModelStatus::Loaded).await;
+
+    if finish_reason == FinishReason::Aborted {
+        return Err(InferenceError::generation_aborted());
+    }
+    let value = structured::extract_json(&reply)
+        .ok_or_else(|| {
+            if finish_reason == FinishReason::MaxTokens {
+                format!("Ran out of the {max_tokens}-token budget before the JSON was complete: {reply}")
+            } else {
+                format!("No JSON in the reply: {reply}")
+            }
+        })
+        .and_then(|value| structured::validate(&value, &json_schema).map(|()| value))
+        .map_err(|e| {
+            log::warn!("Structured generation failed: {e}");
+            InferenceError::structured_output_invalid(&e)
+        })?;
+
+    if let Err(e) = app.emit("inference:structured", &value) {
+        log::error!("Failed to emit structured event: {e}");
+    }
+    Ok(value)
+}
+
+/// Allowance per chat message for the role markers the chat template adds
+const CHAT_MESSAGE_OVERHEAD_TOKENS: u64 = 8;
+
+/// Send the next user turn to the chat session and stream the reply
+///
+/// Tokens are emitted as `inference:token` events followed by
+/// `inference:complete`, as with `generate`. Both the turn and the reply
+/// stay in the session history for later turns. Fails with
+/// `CONTEXT_OVERFLOW` once the history plus the new turn no longer fit the
+/// context window; `reset_chat_session` then starts over.
+///
+/// # Arguments
+/// * `messages` - The new user message(s); earlier turns must not be resent
+/// * `request_id`, `queue` - As for `generate`
+#[tauri::command]
+pub async fn send_chat_message(
+    app: AppHandle,
+    state: State<'_, Arc<InferenceState>>,
+    messages: Vec<ChatMessage>,
+    request_id: Option<String>,
+    queue: Option<bool>,
+) -> Result<(), InferenceError> {
+    let started = Instant::now();
+    let turn = chat::user_turn(&messages)?;
+    let request_id = request_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let _slot = take_generation_slot(&state, queue).await?;
+    let _generation = state.begin_generation();
+    state.reset_abort().await;
+
+    // Counted before locking the chat, which `start_chat_session` locks second
+    let turn_tokens = {
+        let model_guard = state.model.read().await;
+        let Some(model) = model_guard.as_ref() else {
+            return Err(InferenceError::model_not_loaded());
+        };
+        prompt_token_offsets(model, &turn)?.len() as u64 + CHAT_MESSAGE_OVERHEAD_TOKENS
+    };
+
+    let mut chat_guard = state.chat.lock().await;
+    let Some(session) = chat_guard.as_mut() else {
+        return Err(InferenceError::no_chat_session());
+    };
+    let prompt_tokens = session.history_tokens + turn_tokens;
+    if let Some(context_length) = state.context_length().await {
+        check_context_window(prompt_tokens, 0, context_length)?;
+    }
+    state.set_status(ModelStatus::Generating).await;
+
+    let cold = state.take_cold().await;
+    let mut reply_tokens = 0;
+    let mut complete = stream_tokens(
+        session.chat.add_message(turn),
+        &state,
+        started,
+        None,
+        &[],
+        |token| {
+            reply_tokens += 1;
+            let payload = TokenPayload::new(token, &request_id);
+            if let Err(e) = app.emit("inference:token", payload) {
+                log::error!("Failed to emit token: {e}");
+            }
+        },
+    )
+    .await;
+    session.history_tokens = prompt_tokens + reply_tokens + CHAT_MESSAGE_OVERHEAD_TOKENS;
+
+    complete.cold = cold;
+    complete.request_id = request_id;
+    if let Some(first_token_ms) = complete.first_token_ms {
+        state.record_first_token(cold, first_token_ms).await;
+    }
+    log::info!("Chat reply finished: {:?}", complete.finish_reason);
+    app.emit("inference:complete", complete).ok();
+    state.set_status(ModelStatus::Loaded).await;
+    Ok(())
+}
+
+/// End the chat session, dropping its history so the memory is reclaimed
+///
+/// Also clears the system prompt. A reply still streaming is aborted first.
+/// The model stays loaded.
+/// Returns whether a session was active.
+#[tauri::command]
+pub async fn reset_chat_session(
+    state: State<'_, Arc<InferenceState>>,
+) -> Result<bool, InferenceError> {
+    let mut chat_guard = if let Ok(guard) = state.chat.try_lock() {
+        guard
+    } else {
+        state.request_abort().await;
+        state.chat.lock().await
+    };
+    let existed = chat_guard.take().is_some();
+    if existed {
+        log::info!("Chat session reset");
+    }
+    Ok(existed)
+}
+
+/// Save sampling parameters under a name, replacing any preset with that name
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn save_generation_preset(
+    download_state: State<'_, DownloadState>,
+    name: String,
+    params: GenerationParams,
+) -> Result<(), InferenceError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(InferenceError::invalid_parameters(
+            "preset name must not be empty",
+        ));
+    }
+    params.validate()?;
+
+    presets::update(download_state.app_data_dir(), |p| {
+        p.insert(name, params);
+    })
+    .map(|_| ())
+    .map_err(|e| InferenceError::unknown_error(&e))
+}
+
+/// List saved presets by name
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn list_generation_presets(download_state: State<'_, DownloadState>) -> GenerationPresets {
+    presets::load(download_state.app_data_dir())
+}
+
+/// Delete a saved preset; returns whether it existed
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn delete_generation_preset(
+    download_state: State<'_, DownloadState>,
+    name: String,
+) -> Result<bool, InferenceError> {
+    let mut existed = false;
+    presets::update(download_state.app_data_dir(), |p| {
+        existed = p.remove(&name).is_some();
+    })
+    .map_err(|e| InferenceError::unknown_error(&e))?;
+    Ok(existed)
+}
+
+/// Drive a token stream, handing each token to `emit` until it ends or aborts
+///
+/// Accumulates the emitted text so an abort can report it as `partial_text`,
+/// and measures the first-token latency from `started`. Stops once
+/// `max_tokens` tokens have been generated, or when one of `stop_sequences`
+/// appears; text that may begin a stop sequence is held back until it's
+/// known not to, so the match itself is never emitted.
+///
+/// Each wait for the next token races the state's abort token, so an abort
+/// returns at once even while prompt evaluation or a slow token is pending.
+/// Returning drops the Kalosm stream, which stops the underlying generation.
+/// A token that arrives together with the abort is discarded.
+async fn stream_tokens(
+    stream: impl Stream<Item = String>,
+    state: &InferenceState,
+    started: Instant,
+    max_tokens: Option<u64>,
+    stop_sequences: &[String],
+    mut emit: impl FnMut(String),
+) -> CompletePayload {
+    let mut stream = std::pin::pin!(stream);
+    let mut stop = StopSequences::new(stop_sequences);
+    let mut text = String::new();
+    let mut first_token_ms = None;
+    let mut emitted = 0u64;
+
+    // Cancelled already if the abort came while waiting for the model lock
+    // or tokenizing the prompt, in which case the stream is never polled
+    let abort = state.abort_token().await;
+    loop {
+        let next = tokio::select! {
+            biased;
+            () = abort.cancelled() => None,
+            token = stream.next() => token,
+        };
+        // Also catches an abort raised while this token was being produced
+        if abort.is_cancelled() {
+            return CompletePayload::aborted(text, first_token_ms);
+        }
+        let Some(token) = next else {
+            break;
+        };
+
+        if first_token_ms.is_none() {
+            first_token_ms = Some(u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX));
+        }
+        let (ready, stopped) = match stop.push(&token) {
+            StopScan::Continue(ready) => (ready, false),
+            StopScan::Stopped(ready) => (ready, true),
+        };
+        if !ready.is_empty() {
+            text.push_str(&ready);
+            emit(ready);
+        }
+        if stopped {
+            return CompletePayload::finished(FinishReason::StopSequence, first_token_ms);
+        }
+
+        emitted += 1;
+        if max_tokens.is_some_and(|max| emitted >= max) {
+            emit_held(&mut stop, &mut emit);
+            return CompletePayload::finished(FinishReason::MaxTokens, first_token_ms);
+        }
+    }
+
+    emit_held(&mut stop, &mut emit);
+    CompletePayload::finished(FinishReason::Completed, first_token_ms)
+}
+
+/// Emit text held back as a possible stop sequence once it can't be one
+fn emit_held(stop: &mut StopSequences, emit: &mut impl FnMut(String)) {
+    let held = stop.finish();
+    if !held.is_empty() {
+        emit(held);
+    }
+}
+
+/// Get the seed (and model) used by the most recent `generate` call
+///
+/// Pass the seed back in `params.seed` with the same prompt and parameters
+/// to reproduce the output. `None` before the first generation.
+#[tauri::command]
+pub async fn get_last_generation_info(
+    state: State<'_, Arc<InferenceState>>,
+) -> Result<Option<GenerationInfo>, InferenceError> {
+    Ok(state.last_generation.read().await.clone())
+}
+
+/// Get the most recent cold and warm first-token latencies
+#[tauri::command]
+pub async fn get_latency_stats(
+    state: State<'_, Arc<InferenceState>>,
+) -> Result<LatencyStats, InferenceError> {
+    Ok(state.latency_stats().await)
+}
+
+/// Get the RAM/VRAM the loaded model actually consumed
+///
+/// Measured as the change across `load_model`, so it can be compared with
+/// the file-size estimate. All `None` when no model is loaded.
+#[tauri::command]
+pub async fn get_model_memory_usage(
+    state: State<'_, Arc<InferenceState>>,
+) -> Result<MemoryUsage, String> {
+    Ok(state.memory_usage.read().await.unwrap_or_default())
+}
+
+/// Token count of a prompt, with the window it has to fit in
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TokenCount {
+    pub tokens: u64,
+    /// Context window of the loaded model, `None` if unknown
+    pub context_length: Option<u64>,
+}
+
+/// Count a prompt's tokens with the loaded model's tokenizer
+///
+/// Nothing is generated, so the UI can check the prompt fits before sending.
+#[tauri::command]
+pub async fn count_tokens(
+    state: State<'_, Arc<InferenceState>>,
+    prompt: String,
+) -> Result<TokenCount, InferenceError> {
+    count_prompt_tokens(&state, &prompt).await
+}
+
+/// Body of `count_tokens`
+async fn count_prompt_tokens(
+    state: &InferenceState,
+    prompt: &str,
+) -> Result<TokenCount, InferenceError> {
+    let model_guard = state.model.read().await;
+    let Some(model) = model_guard.as_ref() else {
+        return Err(InferenceError::model_not_loaded());
+    };
+
+    let tokens = prompt_token_offsets(model, prompt)?.len() as u64;
+    Ok(TokenCount {
+        tokens,
+        context_length: state.context_length().await,
+    })
+}
+
+/// Embed texts with the loaded model, one vector per text
+///
+/// Every model is currently loaded through Kalosm's `Llama`, which exposes
+/// text generation only, so this fails with `EMBEDDINGS_UNSUPPORTED` until an
+/// embedding-capable architecture is supported.
+#[tauri::command]
+pub async fn embed(
+    state: State<'_, Arc<InferenceState>>,
+    texts: Vec<String>,
+) -> Result<Vec<Vec<f32>>, InferenceError> {
+    embed_texts(&state, &texts).await
+}
+
+/// Body of `embed`
+async fn embed_texts(
+    state: &InferenceState,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, InferenceError> {
+    if texts.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !state.is_loaded().await {
+        return Err(InferenceError::model_not_loaded());
+    }
+    let model_id = state.current_model_id().await.unwrap_or_default();
+    log::warn!("Rejected embed request: {model_id} has no embedding output");
+    Err(InferenceError::embeddings_unsupported(&model_id))
+}
+
+/// Abort ongoing generation by cancelling its abort token
+/// AC4: Inference stops immediately on abort
+///
+/// The generation stops waiting for its next token at once, even during
+/// prompt evaluation, and emits `inference:complete` with `aborted: true`.
+#[tauri::command]
+pub async fn abort_inference(state: State<'_, Arc<InferenceState>>) -> Result<(), InferenceError> {
+    state.request_abort().await;
+    log::info!("Abort requested");
+    Ok(())
+}
+
+/// Check if model is loaded
+/// Uses is_loaded() to verify status accuracy
 #[tauri::command]
 pub async fn get_model_status(
     state: State<'_, Arc<InferenceState>>,
 ) -> Result<ModelStatus, InferenceError> {
+    if let Some(status) = state.recover_stuck_status(STUCK_GENERATION_THRESHOLD).await {
+        log::warn!("Reset stuck Generating status to {status:?}");
+        return Ok(status);
+    }
     let status = state.get_status().await;
 
     // Verify status matches actual model state
@@ -260,13 +2049,697 @@ pub async fn get_model_status(
     }
 }
 
-/// Unload model and release resources
+/// Manually reset a stuck inference state (escape hatch for a frozen UI)
+///
+/// Aborts any running generation and sets the status from whether a model
+/// is actually loaded. Returns the new status.
+#[tauri::command]
+pub async fn reset_inference_state(
+    app: AppHandle,
+    state: State<'_, Arc<InferenceState>>,
+) -> Result<ModelStatus, InferenceError> {
+    state.request_abort().await;
+    let status = if state.is_loaded().await {
+        ModelStatus::Loaded
+    } else {
+        ModelStatus::Unloaded
+    };
+    set_status(&app, &state, status.clone()).await;
+    log::warn!("Inference state reset to {status:?}");
+    Ok(status)
+}
+
+/// Periodically reset a `Generating` status that outlived its generation
+///
+/// Covers a generation that panicked before restoring the status, which
+/// would otherwise leave the UI stuck until restart.
+pub fn start_status_watchdog(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(STUCK_GENERATION_THRESHOLD / 2);
+        loop {
+            interval.tick().await;
+            let state = app.state::<Arc<InferenceState>>();
+            if let Some(status) = state.recover_stuck_status(STUCK_GENERATION_THRESHOLD).await {
+                log::warn!("Watchdog reset stuck Generating status to {status:?}");
+                emit_status(&app, &status);
+            }
+        }
+    });
+}
+
+/// Unload a model and release its resources
 /// AC4: GPU/RAM released within 30 seconds
+///
+/// # Arguments
+/// * `model_id` - Unload just this model, active or on standby; `None`
+///   unloads every loaded model
 #[tauri::command]
-pub async fn unload_model(state: State<'_, Arc<InferenceState>>) -> Result<(), InferenceError> {
-    let mut model_guard = state.model.write().await;
-    *model_guard = None;
-    state.set_status(ModelStatus::Unloaded).await;
-    log::info!("Model unloaded");
+pub async fn unload_model(
+    state: State<'_, Arc<InferenceState>>,
+    model_id: Option<String>,
+) -> Result<(), InferenceError> {
+    if let Some(model_id) = model_id {
+        if state.unload(&model_id).await {
+            log::info!("Model {model_id} unloaded");
+        }
+    } else {
+        state.unload_all().await;
+        state.set_status(ModelStatus::Unloaded).await;
+        log::info!("All models unloaded");
+    }
     Ok(())
 }
+
+/// Choose which loaded model `generate` and chat sessions use
+///
+/// The previously active model stays loaded on standby. `model_id` must
+/// already be loaded; use `load_model` otherwise.
+#[tauri::command]
+pub async fn set_active_model(
+    app: AppHandle,
+    state: State<'_, Arc<InferenceState>>,
+    model_id: String,
+) -> Result<(), InferenceError> {
+    if state.current_model_id().await.as_deref() == Some(model_id.as_str()) {
+        return Ok(());
+    }
+    // Switching mid-load would let the load overwrite the activated model
+    if !state.begin_loading().await {
+        return Err(InferenceError::model_busy());
+    }
+
+    if !state.activate_standby(&model_id).await {
+        let status = if state.is_loaded().await {
+            ModelStatus::Loaded
+        } else {
+            ModelStatus::Unloaded
+        };
+        set_status(&app, &state, status).await;
+        return Err(InferenceError::model_not_loaded());
+    }
+    set_status(&app, &state, ModelStatus::Loaded).await;
+    log::info!("Active model is now {model_id}");
+    announce_ready(&app, &state, &model_id, false).await;
+    Ok(())
+}
+
+/// Cap how many models stay loaded at once, evicting the least recently used
+///
+/// Counts the active model. Defaults to 2, so the previous model stays on
+/// standby; `None` removes the cap. The cap is saved and applied on the next
+/// launch. Returns the IDs evicted to meet a lower cap.
+#[tauri::command]
+pub async fn set_max_loaded_models(
+    state: State<'_, Arc<InferenceState>>,
+    download_state: State<'_, DownloadState>,
+    limit: Option<usize>,
+) -> Result<Vec<String>, InferenceError> {
+    if limit == Some(0) {
+        return Err(InferenceError::invalid_parameters(
+            "max_loaded_models must be at least 1",
+        ));
+    }
+    if let Err(e) = AppSettings::update(download_state.app_data_dir(), |s| {
+        s.max_loaded_models = Some(limit.unwrap_or(usize::MAX));
+    }) {
+        log::warn!("Failed to save max_loaded_models: {e}");
+    }
+    Ok(state.set_max_loaded_models(limit).await)
+}
+
+/// Reload the currently loaded model from disk
+///
+/// Useful after the model file was replaced (re-download, re-verify).
+/// Aborts any ongoing generation, like `unload_model`.
+#[tauri::command]
+pub async fn reload_model(
+    app: AppHandle,
+    state: State<'_, Arc<InferenceState>>,
+    download_state: State<'_, DownloadState>,
+) -> Result<(), InferenceError> {
+    let Some(model_id) = state.current_model_id().await else {
+        return Err(InferenceError::model_not_loaded());
+    };
+    // Keep any context_length override from the original load
+    let context_length = state.context_length().await;
+
+    log::info!("Reloading model: {model_id}");
+    let external_files = state.external_files.read().await.clone();
+    match external_files {
+        Some((gguf_path, tokenizer_path)) => {
+            load_model_from_path(
+                app,
+                state,
+                gguf_path.to_string_lossy().to_string(),
+                tokenizer_path.to_string_lossy().to_string(),
+                context_length,
+                None,
+            )
+            .await
+        },
+        None => {
+            load_model_from_disk(
+                &app,
+                &state,
+                &download_state,
+                &model_id,
+                context_length,
+                true,
+            )
+            .await
+        },
+    }
+}
+
+/// Measure a model's throughput with a fixed-length, non-streaming generation
+///
+/// Loads `model_id` first if it isn't the current model. Builds a prompt of
+/// about `prompt_tokens` tokens and generates up to `gen_tokens` with a fixed
+/// seed; nothing is emitted to the UI and latency stats are left untouched.
+/// Waits its turn behind other generations like `generate` (see `queue`).
+#[tauri::command]
+pub async fn benchmark_model(
+    app: AppHandle,
+    state: State<'_, Arc<InferenceState>>,
+    download_state: State<'_, DownloadState>,
+    model_id: String,
+    prompt_tokens: usize,
+    gen_tokens: usize,
+    queue: Option<bool>,
+) -> Result<BenchmarkResult, InferenceError> {
+    if prompt_tokens == 0 || gen_tokens == 0 {
+        return Err(InferenceError::invalid_parameters(
+            "prompt_tokens and gen_tokens must be at least 1",
+        ));
+    }
+
+    let _slot = take_generation_slot(&state, queue).await?;
+    if state.current_model_id().await.as_deref() != Some(model_id.as_str()) {
+        // The warm-up would wait on the slot held here; the benchmark warms it
+        load_model_from_disk(&app, &state, &download_state, &model_id, None, false).await?;
+    }
+
+    let _generation = state.begin_generation();
+    state.reset_abort().await;
+    state.set_status(ModelStatus::Generating).await;
+    let result = run_benchmark(&state, &model_id, prompt_tokens, gen_tokens).await;
+    state.set_status(ModelStatus::Loaded).await;
+
+    if let Ok(result) = &result {
+        log::info!("Benchmark of {model_id}: {result:?}");
+    }
+    result
+}
+
+/// Body of `benchmark_model`, run with the status already set to `Generating`
+async fn run_benchmark(
+    state: &InferenceState,
+    model_id: &str,
+    prompt_tokens: usize,
+    gen_tokens: usize,
+) -> Result<BenchmarkResult, InferenceError> {
+    let context_length = state.context_length().await;
+    let to_u64 = |n: usize| u64::try_from(n).unwrap_or(u64::MAX);
+    if let Some(context_length) = context_length {
+        check_context_window(to_u64(prompt_tokens), to_u64(gen_tokens), context_length)?;
+    }
+
+    let model_guard = state.model.read().await;
+    let Some(model) = model_guard.as_ref() else {
+        return Err(InferenceError::model_not_loaded());
+    };
+
+    let count_tokens = |text: &str| {
+        model
+            .tokenizer()
+            .encode(text, true)
+            .map(|encoding| encoding.len())
+            .map_err(|e| InferenceError::unknown_error(&format!("Failed to tokenize prompt: {e}")))
+    };
+    let prompt = benchmark::build_prompt(prompt_tokens, count_tokens)?;
+    let prompt_tokens = count_tokens(&prompt)?;
+    // Whole sentences can overshoot the requested length slightly
+    if let Some(context_length) = context_length {
+        check_context_window(to_u64(prompt_tokens), to_u64(gen_tokens), context_length)?;
+    }
+
+    let sampler = GenerationParameters::default()
+        .with_max_length(u32::try_from(gen_tokens).unwrap_or(u32::MAX))
+        .with_seed(benchmark::BENCHMARK_SEED);
+
+    let started = Instant::now();
+    let stream = model.complete(&prompt).with_sampler(sampler);
+    let mut generated_tokens = 0;
+    let mut first_token_at = None;
+    let complete = stream_tokens(
+        stream,
+        state,
+        started,
+        Some(to_u64(gen_tokens)),
+        &[],
+        |_| {
+            generated_tokens += 1;
+            first_token_at.get_or_insert_with(Instant::now);
+        },
+    )
+    .await;
+    let total = started.elapsed();
+
+    if matches!(complete.finish_reason, FinishReason::Aborted) {
+        return Err(InferenceError::generation_aborted());
+    }
+
+    // Prompt evaluation ends with the first token; generation is the rest
+    let prefill = first_token_at.map_or(total, |at| at.duration_since(started));
+    Ok(BenchmarkResult {
+        model_id: model_id.to_string(),
+        prompt_tokens,
+        generated_tokens,
+        prompt_tokens_per_sec: benchmark::tokens_per_sec(prompt_tokens, prefill),
+        generation_tokens_per_sec: benchmark::tokens_per_sec(
+            generated_tokens.saturating_sub(1),
+            total.saturating_sub(prefill),
+        ),
+        total_ms: u64::try_from(total.as_millis()).unwrap_or(u64::MAX),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_length_override_is_capped() {
+        assert_eq!(effective_context_length(None, Some(4096)), Some(4096));
+        assert_eq!(effective_context_length(Some(2048), Some(4096)), Some(2048));
+        assert_eq!(effective_context_length(Some(8192), Some(4096)), Some(4096));
+        assert_eq!(effective_context_length(Some(2048), None), Some(2048));
+        assert_eq!(effective_context_length(None, None), None);
+    }
+
+    #[test]
+    fn test_verify_model_weights_against_stored_hash() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("model.gguf"), b"test").unwrap();
+
+        // Nothing stored: loaded unverified
+        assert!(verify_model_weights(dir.path(), "phi-3", None).is_ok());
+
+        std::fs::write(
+            dir.path().join("model.sha256"),
+            "9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08\n",
+        )
+        .unwrap();
+        assert!(verify_model_weights(dir.path(), "phi-3", None).is_ok());
+
+        std::fs::write(dir.path().join("model.gguf"), b"tesT").unwrap();
+        let err = verify_model_weights(dir.path(), "phi-3", None).unwrap_err();
+        assert!(matches!(err.code, InferenceErrorCode::IntegrityFailed));
+        assert!(err.details.unwrap().contains("9f86d081"));
+
+        std::fs::remove_file(dir.path().join("model.gguf")).unwrap();
+        let err = verify_model_weights(dir.path(), "phi-3", None).unwrap_err();
+        assert!(matches!(err.code, InferenceErrorCode::ModelNotFound));
+    }
+
+    /// Fake tokenizer: a BOS token, then one token per word
+    fn encode_words(text: &str) -> Vec<(usize, usize)> {
+        let mut offsets = vec![(0, 0)];
+        let mut start = None;
+        for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+            match (start, c.is_whitespace()) {
+                (None, false) => start = Some(i),
+                (Some(s), true) => {
+                    offsets.push((s, i));
+                    start = None;
+                },
+                _ => {},
+            }
+        }
+        offsets
+    }
+
+    #[test]
+    fn test_truncate_front_keeps_the_newest_text() {
+        let words = |text: &str| Ok(encode_words(text));
+        let prompt = "one two three four five";
+        assert_eq!(truncate_front(prompt, 6, words).unwrap(), prompt);
+        // The BOS token takes one slot of the budget
+        assert_eq!(truncate_front(prompt, 3, words).unwrap(), "four five");
+        assert_eq!(
+            truncate_front("ünï cödé wörds", 3, words).unwrap(),
+            "cödé wörds"
+        );
+
+        let err = truncate_front(prompt, 1, words).unwrap_err();
+        assert!(matches!(err.code, InferenceErrorCode::ContextOverflow));
+    }
+
+    #[test]
+    fn test_truncation_without_max_tokens_leaves_room_to_generate() {
+        let truncate = OverflowPolicy::Truncate;
+        assert_eq!(
+            reserved_tokens(0, 4_096, truncate),
+            TRUNCATION_RESERVE_TOKENS
+        );
+        assert_eq!(reserved_tokens(0, 512, truncate), 256);
+        assert_eq!(reserved_tokens(100, 4_096, truncate), 100);
+        // Without truncation, no cap still means no reservation
+        assert_eq!(reserved_tokens(0, 4_096, OverflowPolicy::Error), 0);
+    }
+
+    #[test]
+    fn test_context_overflow_reports_numbers() {
+        assert!(check_context_window(3_000, 1_096, 4_096).is_ok());
+
+        let err = check_context_window(3_500, 1_000, 4_096).unwrap_err();
+        assert!(matches!(err.code, InferenceErrorCode::ContextOverflow));
+        let details = err.details.unwrap();
+        assert!(details.contains("3500") && details.contains("1000") && details.contains("4096"));
+        assert_eq!(
+            err.overflow,
+            Some(ContextOverflowDetails {
+                prompt_tokens: 3_500,
+                max_tokens: 1_000,
+                context_length: 4_096,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_count_tokens_requires_loaded_model() {
+        let state = InferenceState::new();
+        let err = count_prompt_tokens(&state, "Hello").await.unwrap_err();
+        assert!(matches!(err.code, InferenceErrorCode::ModelNotFound));
+        assert!(err.message.contains("not loaded"));
+    }
+
+    #[test]
+    fn test_resolve_params_applies_preset_and_validates() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let download_state = DownloadState::new(dir.path().to_path_buf());
+        presets::update(download_state.app_data_dir(), |p| {
+            p.insert(
+                "focused".to_string(),
+                GenerationParams {
+                    temperature: Some(0.3),
+                    top_k: Some(20),
+                    ..GenerationParams::default()
+                },
+            );
+        })
+        .unwrap();
+
+        let overrides = GenerationParams {
+            temperature: Some(0.9),
+            ..GenerationParams::default()
+        };
+        let params = resolve_params(
+            &download_state,
+            Some(overrides),
+            Some("focused".to_string()),
+            Some(64),
+        )
+        .unwrap();
+        assert_eq!(params.temperature, Some(0.9));
+        assert_eq!(params.top_k, Some(20));
+
+        assert!(resolve_params(&download_state, None, Some("missing".to_string()), None).is_err());
+        assert!(resolve_params(&download_state, None, None, Some(0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_embed_requires_loaded_model() {
+        let state = InferenceState::new();
+        assert!(embed_texts(&state, &[]).await.unwrap().is_empty());
+
+        let err = embed_texts(&state, &["Hello".to_string()])
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code, InferenceErrorCode::ModelNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_abort_reports_partial_text() {
+        let state = InferenceState::new();
+        let tokens = ["Hel", "lo", ", ", "wor", "ld"].map(String::from);
+
+        // Request abort while the fourth token is being produced
+        let mut produced = 0;
+        let stream = futures_util::stream::iter(tokens).then(|token| {
+            produced += 1;
+            let abort = produced == 4;
+            let state = Arc::clone(&state);
+            async move {
+                if abort {
+                    state.request_abort().await;
+                }
+                token
+            }
+        });
+
+        let mut emitted = 0;
+        let complete =
+            stream_tokens(stream, &state, Instant::now(), None, &[], |_| emitted += 1).await;
+
+        assert_eq!(emitted, 3);
+        assert_eq!(complete.finish_reason, FinishReason::Aborted);
+        assert_eq!(complete.partial_text.as_deref(), Some("Hello, "));
+    }
+
+    #[tokio::test]
+    async fn test_abort_before_prefill_never_polls_stream() {
+        let state = InferenceState::new();
+        state.request_abort().await;
+
+        let polled = std::cell::Cell::new(false);
+        let stream = futures_util::stream::poll_fn(|_| {
+            polled.set(true);
+            std::task::Poll::Ready(Some("token".to_string()))
+        });
+
+        let complete = stream_tokens(stream, &state, Instant::now(), None, &[], |_| {}).await;
+
+        assert!(!polled.get());
+        assert_eq!(complete.finish_reason, FinishReason::Aborted);
+        assert_eq!(complete.partial_text.as_deref(), Some(""));
+        assert_eq!(complete.first_token_ms, None);
+    }
+
+    #[tokio::test]
+    async fn test_abort_during_prefill_discards_first_token() {
+        let state = InferenceState::new();
+
+        // Abort lands while the (slow) first token is being computed
+        let stream = futures_util::stream::once({
+            let state = Arc::clone(&state);
+            async move {
+                state.request_abort().await;
+                "first".to_string()
+            }
+        })
+        .chain(futures_util::stream::iter(["second".to_string()]));
+
+        let mut emitted = 0;
+        let complete =
+            stream_tokens(stream, &state, Instant::now(), None, &[], |_| emitted += 1).await;
+
+        assert_eq!(emitted, 0);
+        assert_eq!(complete.finish_reason, FinishReason::Aborted);
+        assert_eq!(complete.partial_text.as_deref(), Some(""));
+    }
+
+    #[tokio::test]
+    async fn test_abort_interrupts_slow_token() {
+        let state = InferenceState::new();
+
+        // Two tokens, then a model call that never finishes
+        let stream = futures_util::stream::iter(["a", "b"].map(String::from))
+            .chain(futures_util::stream::pending());
+        let abort = {
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                state.request_abort().await;
+            })
+        };
+
+        let mut emitted = Vec::new();
+        let complete = tokio::time::timeout(
+            Duration::from_secs(5),
+            stream_tokens(stream, &state, Instant::now(), None, &[], |t| {
+                emitted.push(t);
+            }),
+        )
+        .await
+        .unwrap();
+        abort.await.unwrap();
+
+        assert_eq!(emitted, ["a", "b"]);
+        assert!(complete.aborted);
+        assert_eq!(complete.partial_text.as_deref(), Some("ab"));
+        let json = serde_json::to_string(&complete).unwrap();
+        assert!(json.contains(r#""aborted":true"#));
+    }
+
+    #[tokio::test]
+    async fn test_max_tokens_caps_emitted_tokens() {
+        let state = InferenceState::new();
+        let stream = futures_util::stream::iter(["a", "b", "c", "d"].map(String::from));
+
+        let mut emitted = Vec::new();
+        let complete = stream_tokens(stream, &state, Instant::now(), Some(2), &[], |t| {
+            emitted.push(t);
+        })
+        .await;
+
+        assert_eq!(emitted, ["a", "b"]);
+        assert_eq!(complete.finish_reason, FinishReason::MaxTokens);
+        let json = serde_json::to_string(&complete).unwrap();
+        assert!(json.starts_with(r#"{"finish_reason":"max_tokens""#));
+    }
+
+    #[tokio::test]
+    async fn test_stop_sequence_in_single_token() {
+        let state = InferenceState::new();
+        let stream = futures_util::stream::iter(["fn main()", "```", "more"].map(String::from));
+        let stops = ["```".to_string()];
+
+        let mut emitted = Vec::new();
+        let complete = stream_tokens(stream, &state, Instant::now(), None, &stops, |t| {
+            emitted.push(t);
+        })
+        .await;
+
+        assert_eq!(emitted, ["fn main()"]);
+        assert_eq!(complete.finish_reason, FinishReason::StopSequence);
+        let json = serde_json::to_string(&complete).unwrap();
+        assert!(json.starts_with(r#"{"finish_reason":"stop_sequence""#));
+    }
+
+    #[tokio::test]
+    async fn test_stop_sequence_across_tokens_is_not_emitted() {
+        let state = InferenceState::new();
+        let tokens = ["Sure", ".\n", "\nUs", "er:", " next"].map(String::from);
+        let stops = ["\n\nUser:".to_string()];
+
+        let mut emitted = Vec::new();
+        let complete = stream_tokens(
+            futures_util::stream::iter(tokens),
+            &state,
+            Instant::now(),
+            None,
+            &stops,
+            |t| emitted.push(t),
+        )
+        .await;
+
+        assert_eq!(emitted.concat(), "Sure.");
+        assert_eq!(complete.finish_reason, FinishReason::StopSequence);
+    }
+
+    #[tokio::test]
+    async fn test_complete_payload_without_abort() {
+        let state = InferenceState::new();
+        let stream = futures_util::stream::iter(["a", "b"].map(String::from));
+
+        let complete = stream_tokens(stream, &state, Instant::now(), None, &[], |_| {}).await;
+
+        assert_eq!(complete.finish_reason, FinishReason::Completed);
+        assert!(complete.first_token_ms.is_some());
+        let json = serde_json::to_string(&complete).unwrap();
+        assert!(
+            json.starts_with(r#"{"finish_reason":"completed","aborted":false,"first_token_ms":"#)
+        );
+    }
+
+    #[test]
+    fn test_token_batches_preserve_order_and_content() {
+        let tokens = ["Hel", "lo", ", ", "wor", "ld", "!", " "].map(String::from);
+        let mut batcher = TokenBatcher::new(3, "r1");
+
+        let mut batches: Vec<_> = tokens
+            .iter()
+            .filter_map(|t| batcher.push(t.clone()))
+            .collect();
+        batches.extend(batcher.flush());
+
+        assert!(batches.iter().all(|b| b.request_id == "r1"));
+        let sizes: Vec<_> = batches.iter().map(|b| b.tokens.len()).collect();
+        assert_eq!(sizes, [3, 3, 1]);
+        let rejoined: Vec<_> = batches.into_iter().flat_map(|b| b.tokens).collect();
+        assert_eq!(rejoined, tokens);
+        assert_eq!(batcher.flush(), None);
+    }
+
+    #[test]
+    fn test_throughput_reports_every_interval() {
+        let started = Instant::now();
+        let mut tracker = ThroughputTracker::new(started);
+        let at = |ms| started + Duration::from_millis(ms);
+
+        assert_eq!(tracker.record_token(at(100)), None);
+        assert_eq!(tracker.record_token(at(300)), None);
+        let stats = tracker.record_token(at(500)).unwrap();
+        assert_eq!(stats.tokens_generated, 3);
+        assert_eq!(stats.elapsed_ms, 500);
+        assert!((stats.tokens_per_second - 6.0).abs() < 1e-9);
+
+        // The next report is due a full interval after the last one
+        assert_eq!(tracker.record_token(at(900)), None);
+        assert!(tracker.record_token(at(1_000)).is_some());
+
+        let summary = tracker.stats(at(2_000));
+        assert_eq!(summary.tokens_generated, 5);
+        assert!((summary.tokens_per_second - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_token_payload_includes_logprob_fields_when_requested() {
+        let mut payload = TokenPayload::new("Hi".to_string(), "r1");
+        payload.logprobs = Some(TokenLogprobs::decode(
+            SampledToken {
+                logprob: -0.5,
+                top: vec![(7, -0.5), (8, -1.5)],
+            },
+            |id| format!("t{id}"),
+        ));
+        assert_eq!(
+            serde_json::to_string(&payload).unwrap(),
+            r#"{"text":"Hi","request_id":"r1","logprob":-0.5,"top_alternatives":[["t7",-0.5],["t8",-1.5]]}"#
+        );
+    }
+
+    #[test]
+    fn test_token_payload_is_lean_by_default() {
+        let json = serde_json::to_string(&TokenPayload::new("Hi".to_string(), "r1")).unwrap();
+        assert_eq!(json, r#"{"text":"Hi","request_id":"r1"}"#);
+    }
+
+    #[test]
+    fn test_token_timer_measures_intervals() {
+        let started = Instant::now();
+        let at = |ms| started + Duration::from_millis(ms);
+        let mut timer = TokenTimer::new(started);
+
+        let ticks: Vec<_> = [300, 350, 1_350].map(|ms| timer.tick(at(ms))).into();
+        assert_eq!(
+            ticks.iter().map(|t| t.token_index).collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+        assert_eq!(
+            ticks.iter().map(|t| t.ms_since_last).collect::<Vec<_>>(),
+            [300, 50, 1_000]
+        );
+        assert_eq!(ticks[2].cumulative_ms, 1_350);
+
+        let mut payload = TokenPayload::new("Hi".to_string(), "r1");
+        payload.timing = Some(ticks[1].clone());
+        assert_eq!(
+            serde_json::to_string(&payload).unwrap(),
+            r#"{"text":"Hi","request_id":"r1","token_index":1,"ms_since_last":50,"cumulative_ms":350}"#
+        );
+    }
+}
@@ -0,0 +1,718 @@
+//! Minimal GGUF metadata reader
+//!
+//! Reads the GGUF header (metadata key-values and tensor descriptions) to
+//! describe a model without loading any tensors, so only the first few
+//! megabytes of a multi-GB file are ever read. Also merges the files of a
+//! split model (`{name}-00001-of-00003.gguf`, ...) into one, since Kalosm
+//! loads a single GGUF file.
+//! Format reference: https://github.com/ggml-org/ggml/blob/master/docs/gguf.md
+
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// GGUF value type tags
+const TYPE_U8: u32 = 0;
+const TYPE_I8: u32 = 1;
+const TYPE_U16: u32 = 2;
+const TYPE_I16: u32 = 3;
+const TYPE_U32: u32 = 4;
+const TYPE_I32: u32 = 5;
+const TYPE_F32: u32 = 6;
+const TYPE_BOOL: u32 = 7;
+const TYPE_STRING: u32 = 8;
+const TYPE_ARRAY: u32 = 9;
+const TYPE_U64: u32 = 10;
+const TYPE_I64: u32 = 11;
+const TYPE_F64: u32 = 12;
+
+/// Longest key or architecture name we'll read into memory
+const MAX_KEY_LEN: u64 = 64 * 1024;
+
+/// Most dimensions a GGML tensor can have
+const MAX_TENSOR_DIMS: u32 = 4;
+
+/// Tensor data alignment when `general.alignment` isn't set
+const DEFAULT_ALIGNMENT: u64 = 32;
+
+/// Metadata keys describing the split, dropped from a merged file
+const SPLIT_KEY_PREFIX: &str = "split.";
+
+/// Model facts from a GGUF header; fields the file doesn't declare are `None`
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct GgufMetadata {
+    /// Model family, e.g. "llama" or "phi3"
+    pub architecture: Option<String>,
+    /// Weight format from `general.file_type`, e.g. "Q4_K_M"
+    pub quantization: Option<String>,
+    /// Trained context window in tokens
+    pub context_length: Option<u64>,
+    /// Hidden size of the model
+    pub embedding_length: Option<u64>,
+    /// Parameter count, declared or summed over the tensor shapes
+    pub n_params: Option<u64>,
+    /// Number of files a split model is spread across (`split.count`)
+    pub split_count: Option<u64>,
+}
+
+/// Why a file's GGUF metadata couldn't be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GgufError {
+    /// The file couldn't be opened or read
+    Io(String),
+    /// The file doesn't start with the GGUF magic bytes
+    NotGguf,
+    /// The file ends partway through the header
+    Truncated,
+    /// The header is malformed (unknown types, implausible sizes)
+    Invalid(String),
+}
+
+impl std::fmt::Display for GgufError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read GGUF file: {e}"),
+            Self::NotGguf => write!(f, "Not a GGUF file"),
+            Self::Truncated => write!(f, "Truncated GGUF metadata"),
+            Self::Invalid(e) => write!(f, "Invalid GGUF metadata: {e}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for GgufError {
+    fn from(e: std::io::Error) -> Self {
+        if e.kind() == ErrorKind::UnexpectedEof {
+            Self::Truncated
+        } else {
+            Self::Io(e.to_string())
+        }
+    }
+}
+
+/// Read the metadata of the GGUF file at `path`
+///
+/// Files using the obsolete v1 header layout yield empty metadata.
+pub fn read_metadata(path: &Path) -> Result<GgufMetadata, GgufError> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| GgufError::Io(format!("Failed to open {}: {e}", path.display())))?;
+    parse_metadata(&mut BufReader::new(file))
+}
+
+/// Parse the header from `reader`, positioned at the file start
+fn parse_metadata(reader: &mut (impl Read + Seek)) -> Result<GgufMetadata, GgufError> {
+    let mut magic = [0u8; 4];
+    match reader.read_exact(&mut magic) {
+        Ok(()) if &magic == b"GGUF" => {},
+        Ok(()) => return Err(GgufError::NotGguf),
+        // Too short to even hold the magic
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Err(GgufError::NotGguf),
+        Err(e) => return Err(e.into()),
+    }
+
+    // v1 used 32-bit counts; such files are long obsolete
+    if read_u32(reader)? < 2 {
+        return Ok(GgufMetadata::default());
+    }
+    let tensor_count = read_u64(reader)?;
+    let kv_count = read_u64(reader)?;
+
+    // Per-architecture keys may come before `general.architecture`
+    let mut architecture = None;
+    let mut context_lengths = HashMap::new();
+    let mut embedding_lengths = HashMap::new();
+    let mut file_type = None;
+    let mut parameter_count = None;
+    let mut split_count = None;
+    for _ in 0..kv_count {
+        let key = read_string(reader)?;
+        let value_type = read_u32(reader)?;
+
+        if key == "general.architecture" && value_type == TYPE_STRING {
+            architecture = Some(read_string(reader)?);
+        } else if key == "general.file_type" {
+            file_type = read_integer(reader, value_type)?;
+        } else if key == "general.parameter_count" {
+            parameter_count = read_integer(reader, value_type)?;
+        } else if key == "split.count" {
+            split_count = read_integer(reader, value_type)?;
+        } else if let Some(arch) = key.strip_suffix(".context_length") {
+            if let Some(length) = read_integer(reader, value_type)? {
+                context_lengths.insert(arch.to_string(), length);
+            }
+        } else if let Some(arch) = key.strip_suffix(".embedding_length") {
+            if let Some(length) = read_integer(reader, value_type)? {
+                embedding_lengths.insert(arch.to_string(), length);
+            }
+        } else {
+            skip_value(reader, value_type)?;
+        }
+    }
+
+    let n_params = match parameter_count {
+        Some(count) => Some(count),
+        None => Some(count_tensor_params(reader, tensor_count)?),
+    };
+    Ok(GgufMetadata {
+        context_length: architecture
+            .as_ref()
+            .and_then(|a| context_lengths.get(a).copied()),
+        embedding_length: architecture
+            .as_ref()
+            .and_then(|a| embedding_lengths.get(a).copied()),
+        architecture,
+        quantization: file_type.map(quantization_name),
+        n_params: n_params.filter(|&n| n > 0),
+        split_count,
+    })
+}
+
+/// Sum the element counts of the tensor descriptions after the metadata
+fn count_tensor_params(
+    reader: &mut (impl Read + Seek),
+    tensor_count: u64,
+) -> Result<u64, GgufError> {
+    let overflow = || GgufError::Invalid("tensor sizes overflow".to_string());
+    let mut total = 0u64;
+    for _ in 0..tensor_count {
+        let name_len = read_u64(reader)?;
+        skip(reader, name_len)?;
+        let n_dims = read_u32(reader)?;
+        if n_dims > MAX_TENSOR_DIMS {
+            return Err(GgufError::Invalid(format!(
+                "tensor with {n_dims} dimensions"
+            )));
+        }
+        let mut elements = 1u64;
+        for _ in 0..n_dims {
+            elements = elements
+                .checked_mul(read_u64(reader)?)
+                .ok_or_else(overflow)?;
+        }
+        // Read rather than skip, so a file cut off here is caught
+        let _element_type = read_u32(reader)?;
+        let _data_offset = read_u64(reader)?;
+        total = total.checked_add(elements).ok_or_else(overflow)?;
+    }
+    Ok(total)
+}
+
+/// One tensor description from a GGUF header
+struct TensorInfo {
+    name: String,
+    dims: Vec<u64>,
+    element_type: u32,
+    /// Offset of the data from the start of the file's data section
+    offset: u64,
+}
+
+/// Header of one file of a split model
+struct SplitHeader {
+    version: u32,
+    /// Raw key-value entries, minus the `split.*` ones
+    key_values: Vec<Vec<u8>>,
+    alignment: u64,
+    tensors: Vec<TensorInfo>,
+    /// Where the (aligned) data section starts, and its length
+    data_start: u64,
+    data_len: u64,
+}
+
+/// Merge the files of a split model, in order, into one GGUF file at `out`
+///
+/// Like llama.cpp's `gguf-split --merge`: the first file's metadata minus the
+/// `split.*` keys, then every file's tensors with their offsets moved past the
+/// data of the files before it. Streams the data, so memory use stays small.
+pub fn merge_split(shards: &[PathBuf], out: &Path) -> Result<(), GgufError> {
+    let mut readers = shards
+        .iter()
+        .map(|path| {
+            std::fs::File::open(path)
+                .map(BufReader::new)
+                .map_err(|e| GgufError::Io(format!("Failed to open {}: {e}", path.display())))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let file = std::fs::File::create(out)
+        .map_err(|e| GgufError::Io(format!("Failed to create {}: {e}", out.display())))?;
+    let mut writer = BufWriter::new(file);
+    merge_split_readers(&mut readers, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Body of `merge_split`, over any seekable readers
+fn merge_split_readers(
+    shards: &mut [impl Read + Seek],
+    out: &mut impl Write,
+) -> Result<(), GgufError> {
+    let headers = shards
+        .iter_mut()
+        .map(read_split_header)
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(first) = headers.first() else {
+        return Err(GgufError::Invalid("no files to merge".to_string()));
+    };
+    let alignment = first.alignment;
+
+    out.write_all(b"GGUF")?;
+    out.write_all(&first.version.to_le_bytes())?;
+    let tensor_count: usize = headers.iter().map(|h| h.tensors.len()).sum();
+    out.write_all(&(tensor_count as u64).to_le_bytes())?;
+    out.write_all(&(first.key_values.len() as u64).to_le_bytes())?;
+    for entry in &first.key_values {
+        out.write_all(entry)?;
+    }
+
+    let mut written = 24 + first.key_values.iter().map(|e| e.len() as u64).sum::<u64>();
+    let mut base = 0u64;
+    for header in &headers {
+        for tensor in &header.tensors {
+            let mut info = Vec::new();
+            write_string(&mut info, &tensor.name);
+            let n_dims = u32::try_from(tensor.dims.len()).unwrap_or(MAX_TENSOR_DIMS);
+            info.extend_from_slice(&n_dims.to_le_bytes());
+            for dim in &tensor.dims {
+                info.extend_from_slice(&dim.to_le_bytes());
+            }
+            info.extend_from_slice(&tensor.element_type.to_le_bytes());
+            info.extend_from_slice(&(base + tensor.offset).to_le_bytes());
+            out.write_all(&info)?;
+            written += info.len() as u64;
+        }
+        base += align_up(header.data_len, alignment);
+    }
+    write_padding(out, align_up(written, alignment) - written)?;
+
+    for (reader, header) in shards.iter_mut().zip(&headers) {
+        reader.seek(SeekFrom::Start(header.data_start))?;
+        let copied = std::io::copy(&mut reader.take(header.data_len), out)?;
+        if copied != header.data_len {
+            return Err(GgufError::Truncated);
+        }
+        write_padding(out, align_up(header.data_len, alignment) - header.data_len)?;
+    }
+    Ok(())
+}
+
+/// Read the header of one split file, keeping the key-values as raw bytes
+fn read_split_header(reader: &mut (impl Read + Seek)) -> Result<SplitHeader, GgufError> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != b"GGUF" {
+        return Err(GgufError::NotGguf);
+    }
+    let version = read_u32(reader)?;
+    if version < 2 {
+        return Err(GgufError::Invalid(format!(
+            "unsupported GGUF version {version}"
+        )));
+    }
+    let tensor_count = read_u64(reader)?;
+    let kv_count = read_u64(reader)?;
+
+    let mut key_values = Vec::new();
+    let mut alignment = DEFAULT_ALIGNMENT;
+    for _ in 0..kv_count {
+        let start = reader.stream_position()?;
+        let key = read_string(reader)?;
+        let value_type = read_u32(reader)?;
+        if key == "general.alignment" {
+            alignment = read_integer(reader, value_type)?
+                .filter(|&n| n > 0)
+                .unwrap_or(DEFAULT_ALIGNMENT);
+        } else {
+            skip_value(reader, value_type)?;
+        }
+        if key.starts_with(SPLIT_KEY_PREFIX) {
+            continue;
+        }
+
+        // Go back and keep the whole entry as it is on disk
+        let end = reader.stream_position()?;
+        reader.seek(SeekFrom::Start(start))?;
+        let mut entry = vec![0u8; usize::try_from(end - start).unwrap_or(0)];
+        reader.read_exact(&mut entry)?;
+        key_values.push(entry);
+    }
+
+    let mut tensors = Vec::new();
+    for _ in 0..tensor_count {
+        let name = read_string(reader)?;
+        let n_dims = read_u32(reader)?;
+        if n_dims > MAX_TENSOR_DIMS {
+            return Err(GgufError::Invalid(format!(
+                "tensor with {n_dims} dimensions"
+            )));
+        }
+        let dims = (0..n_dims)
+            .map(|_| read_u64(reader))
+            .collect::<Result<_, _>>()?;
+        tensors.push(TensorInfo {
+            name,
+            dims,
+            element_type: read_u32(reader)?,
+            offset: read_u64(reader)?,
+        });
+    }
+
+    let data_start = align_up(reader.stream_position()?, alignment);
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    Ok(SplitHeader {
+        version,
+        key_values,
+        alignment,
+        tensors,
+        data_start,
+        data_len: file_len.saturating_sub(data_start),
+    })
+}
+
+const fn align_up(n: u64, alignment: u64) -> u64 {
+    n.div_ceil(alignment) * alignment
+}
+
+fn write_padding(out: &mut impl Write, len: u64) -> Result<(), GgufError> {
+    std::io::copy(&mut std::io::repeat(0).take(len), out)?;
+    Ok(())
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u64).to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Name of a llama.cpp `general.file_type` value
+fn quantization_name(file_type: u64) -> String {
+    let name = match file_type {
+        0 => "F32",
+        1 => "F16",
+        2 => "Q4_0",
+        3 => "Q4_1",
+        7 => "Q8_0",
+        8 => "Q5_0",
+        9 => "Q5_1",
+        10 => "Q2_K",
+        11 => "Q3_K_S",
+        12 => "Q3_K_M",
+        13 => "Q3_K_L",
+        14 => "Q4_K_S",
+        15 => "Q4_K_M",
+        16 => "Q5_K_S",
+        17 => "Q5_K_M",
+        18 => "Q6_K",
+        19 => "IQ2_XXS",
+        20 => "IQ2_XS",
+        21 => "Q2_K_S",
+        22 => "IQ3_XS",
+        23 => "IQ3_XXS",
+        24 => "IQ1_S",
+        25 => "IQ4_NL",
+        26 => "IQ3_S",
+        27 => "IQ3_M",
+        28 => "IQ2_S",
+        29 => "IQ2_M",
+        30 => "IQ4_XS",
+        31 => "IQ1_M",
+        32 => "BF16",
+        other => return format!("file type {other}"),
+    };
+    name.to_string()
+}
+
+/// Read an unsigned integer value, skipping (and returning `None`) other types
+fn read_integer(
+    reader: &mut (impl Read + Seek),
+    value_type: u32,
+) -> Result<Option<u64>, GgufError> {
+    Ok(match value_type {
+        TYPE_U16 => Some(u64::from(read_u16(reader)?)),
+        TYPE_U32 => Some(u64::from(read_u32(reader)?)),
+        TYPE_I32 => u64::try_from(read_u32(reader)?.cast_signed()).ok(),
+        TYPE_U64 => Some(read_u64(reader)?),
+        TYPE_I64 => u64::try_from(read_u64(reader)?.cast_signed()).ok(),
+        _ => {
+            skip_value(reader, value_type)?;
+            None
+        },
+    })
+}
+
+/// Skip over a value of the given type without reading it into memory
+fn skip_value(reader: &mut (impl Read + Seek), value_type: u32) -> Result<(), GgufError> {
+    match value_type {
+        TYPE_STRING => {
+            let len = read_u64(reader)?;
+            skip(reader, len)
+        },
+        TYPE_ARRAY => {
+            let element_type = read_u32(reader)?;
+            let count = read_u64(reader)?;
+            match fixed_size(element_type) {
+                Some(size) => skip(
+                    reader,
+                    count
+                        .checked_mul(size)
+                        .ok_or_else(|| GgufError::Invalid("array size overflows".to_string()))?,
+                ),
+                None => (0..count).try_for_each(|_| skip_value(reader, element_type)),
+            }
+        },
+        _ => skip(
+            reader,
+            fixed_size(value_type)
+                .ok_or_else(|| GgufError::Invalid(format!("unknown value type {value_type}")))?,
+        ),
+    }
+}
+
+/// Byte size of fixed-width value types
+const fn fixed_size(value_type: u32) -> Option<u64> {
+    match value_type {
+        TYPE_U8 | TYPE_I8 | TYPE_BOOL => Some(1),
+        TYPE_U16 | TYPE_I16 => Some(2),
+        TYPE_U32 | TYPE_I32 | TYPE_F32 => Some(4),
+        TYPE_U64 | TYPE_I64 | TYPE_F64 => Some(8),
+        _ => None,
+    }
+}
+
+fn skip(reader: &mut impl Seek, len: u64) -> Result<(), GgufError> {
+    let offset =
+        i64::try_from(len).map_err(|_| GgufError::Invalid("value too large".to_string()))?;
+    Ok(reader.seek_relative(offset)?)
+}
+
+fn read_string(reader: &mut impl Read) -> Result<String, GgufError> {
+    let len = read_u64(reader)?;
+    if len > MAX_KEY_LEN {
+        return Err(GgufError::Invalid(format!(
+            "string of {len} bytes is implausibly long"
+        )));
+    }
+    let mut bytes = vec![0u8; usize::try_from(len).unwrap_or(0)];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| GgufError::Invalid("string is not UTF-8".to_string()))
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, GgufError> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u16(reader: &mut impl Read) -> Result<u16, GgufError> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, GgufError> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn string(out: &mut Vec<u8>, s: &str) {
+        out.extend_from_slice(&(s.len() as u64).to_le_bytes());
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    /// Header with a vocab array, an unrelated context length, then the real one
+    fn sample_gguf(arch: &str) -> Vec<u8> {
+        let mut out = b"GGUF".to_vec();
+        out.extend_from_slice(&3u32.to_le_bytes());
+        out.extend_from_slice(&0u64.to_le_bytes());
+        out.extend_from_slice(&4u64.to_le_bytes());
+
+        string(&mut out, "tokenizer.ggml.tokens");
+        out.extend_from_slice(&TYPE_ARRAY.to_le_bytes());
+        out.extend_from_slice(&TYPE_STRING.to_le_bytes());
+        out.extend_from_slice(&2u64.to_le_bytes());
+        string(&mut out, "<s>");
+        string(&mut out, "</s>");
+
+        string(&mut out, "bert.context_length");
+        out.extend_from_slice(&TYPE_U32.to_le_bytes());
+        out.extend_from_slice(&512u32.to_le_bytes());
+
+        string(&mut out, "llama.context_length");
+        out.extend_from_slice(&TYPE_U32.to_le_bytes());
+        out.extend_from_slice(&4096u32.to_le_bytes());
+
+        string(&mut out, "general.architecture");
+        out.extend_from_slice(&TYPE_STRING.to_le_bytes());
+        string(&mut out, arch);
+        out
+    }
+
+    /// Header declaring quantization and embedding size, plus two tensors
+    fn described_gguf() -> Vec<u8> {
+        let mut out = b"GGUF".to_vec();
+        out.extend_from_slice(&3u32.to_le_bytes());
+        out.extend_from_slice(&2u64.to_le_bytes());
+        out.extend_from_slice(&4u64.to_le_bytes());
+
+        string(&mut out, "general.architecture");
+        out.extend_from_slice(&TYPE_STRING.to_le_bytes());
+        string(&mut out, "llama");
+
+        string(&mut out, "general.file_type");
+        out.extend_from_slice(&TYPE_U32.to_le_bytes());
+        out.extend_from_slice(&15u32.to_le_bytes());
+
+        string(&mut out, "llama.context_length");
+        out.extend_from_slice(&TYPE_U32.to_le_bytes());
+        out.extend_from_slice(&8192u32.to_le_bytes());
+
+        string(&mut out, "llama.embedding_length");
+        out.extend_from_slice(&TYPE_U64.to_le_bytes());
+        out.extend_from_slice(&4096u64.to_le_bytes());
+
+        for (name, dims) in [
+            ("token_embd.weight", &[4096u64, 32_000][..]),
+            ("norm", &[4096]),
+        ] {
+            string(&mut out, name);
+            out.extend_from_slice(&u32::try_from(dims.len()).unwrap().to_le_bytes());
+            for dim in dims {
+                out.extend_from_slice(&dim.to_le_bytes());
+            }
+            out.extend_from_slice(&12u32.to_le_bytes());
+            out.extend_from_slice(&0u64.to_le_bytes());
+        }
+        out
+    }
+
+    #[test]
+    fn test_reads_context_length_for_architecture() {
+        let bytes = sample_gguf("llama");
+        let metadata = parse_metadata(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(metadata.context_length, Some(4096));
+        assert_eq!(metadata.architecture.as_deref(), Some("llama"));
+    }
+
+    #[test]
+    fn test_missing_context_length_is_none() {
+        let bytes = sample_gguf("phi3");
+        let metadata = parse_metadata(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(metadata.context_length, None);
+        assert_eq!(metadata.n_params, None);
+    }
+
+    #[test]
+    fn test_reads_model_description() {
+        let metadata = parse_metadata(&mut Cursor::new(described_gguf())).unwrap();
+        assert_eq!(
+            metadata,
+            GgufMetadata {
+                architecture: Some("llama".to_string()),
+                quantization: Some("Q4_K_M".to_string()),
+                context_length: Some(8192),
+                embedding_length: Some(4096),
+                n_params: Some(4096 * 32_000 + 4096),
+                split_count: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reads_split_count_of_first_shard() {
+        let mut bytes = b"GGUF".to_vec();
+        bytes.extend_from_slice(&3u32.to_le_bytes());
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        bytes.extend_from_slice(&1u64.to_le_bytes());
+        string(&mut bytes, "split.count");
+        bytes.extend_from_slice(&TYPE_U16.to_le_bytes());
+        bytes.extend_from_slice(&3u16.to_le_bytes());
+
+        let metadata = parse_metadata(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(metadata.split_count, Some(3));
+        let single = parse_metadata(&mut Cursor::new(described_gguf())).unwrap();
+        assert_eq!(single.split_count, None);
+    }
+
+    /// One file of a split model holding a single 4-byte tensor
+    fn split_gguf(index: u16, tensor: &str, data: [u8; 4]) -> Vec<u8> {
+        let mut out = b"GGUF".to_vec();
+        out.extend_from_slice(&3u32.to_le_bytes());
+        out.extend_from_slice(&1u64.to_le_bytes());
+        let first = index == 0;
+        out.extend_from_slice(&(if first { 3u64 } else { 2 }).to_le_bytes());
+
+        if first {
+            string(&mut out, "general.architecture");
+            out.extend_from_slice(&TYPE_STRING.to_le_bytes());
+            string(&mut out, "llama");
+        }
+        string(&mut out, "split.no");
+        out.extend_from_slice(&TYPE_U16.to_le_bytes());
+        out.extend_from_slice(&index.to_le_bytes());
+        string(&mut out, "split.count");
+        out.extend_from_slice(&TYPE_U16.to_le_bytes());
+        out.extend_from_slice(&2u16.to_le_bytes());
+
+        string(&mut out, tensor);
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.extend_from_slice(&4u64.to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&0u64.to_le_bytes());
+
+        out.resize(out.len().next_multiple_of(32), 0);
+        out.extend_from_slice(&data);
+        out
+    }
+
+    #[test]
+    fn test_merges_split_files_into_one() {
+        let mut shards = [
+            Cursor::new(split_gguf(0, "a", [1, 2, 3, 4])),
+            Cursor::new(split_gguf(1, "b", [5, 6, 7, 8])),
+        ];
+        let mut merged = Vec::new();
+        merge_split_readers(shards.as_mut_slice(), &mut merged).unwrap();
+
+        let metadata = parse_metadata(&mut Cursor::new(merged.clone())).unwrap();
+        assert_eq!(metadata.architecture.as_deref(), Some("llama"));
+        assert_eq!(metadata.split_count, None);
+        assert_eq!(metadata.n_params, Some(8));
+
+        // The second tensor's data sits one aligned block after the first
+        let header = read_split_header(&mut Cursor::new(merged.clone())).unwrap();
+        assert_eq!(header.key_values.len(), 1);
+        let offsets: Vec<u64> = header.tensors.iter().map(|t| t.offset).collect();
+        assert_eq!(offsets, [0, 32]);
+        let data = usize::try_from(header.data_start).unwrap();
+        assert_eq!(merged[data..data + 4], [1, 2, 3, 4]);
+        assert_eq!(merged[data + 32..data + 36], [5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_rejects_non_gguf_and_truncated_files() {
+        let not_gguf = parse_metadata(&mut Cursor::new(b"{\"not\": 1}".to_vec()));
+        assert_eq!(not_gguf, Err(GgufError::NotGguf));
+        assert_eq!(
+            parse_metadata(&mut Cursor::new(b"GG".to_vec())),
+            Err(GgufError::NotGguf)
+        );
+
+        let mut bytes = sample_gguf("llama");
+        bytes.truncate(bytes.len() - 3);
+        assert_eq!(
+            parse_metadata(&mut Cursor::new(bytes)),
+            Err(GgufError::Truncated)
+        );
+
+        // Cut inside the tensor descriptions, after all the key-values
+        let mut bytes = described_gguf();
+        bytes.truncate(bytes.len() - 10);
+        assert_eq!(
+            parse_metadata(&mut Cursor::new(bytes)),
+            Err(GgufError::Truncated)
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Per-token log-probabilities for `generate`'s `logprobs` option
+//!
+//! Kalosm's completion stream yields text only, so the sampler is wrapped to
+//! record the probability of each token it picks. Probabilities come from the
+//! model's raw logits, before temperature or top-p/top-k truncation.
+
+use llm_samplers::prelude::{HasSamplerResources, Logits, Sampler};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Alternatives reported alongside each sampled token
+pub const TOP_ALTERNATIVES: usize = 5;
+
+/// Log-probability of one sampled token and the likeliest candidates
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampledToken {
+    pub logprob: f32,
+    /// Token IDs with their log-probabilities, most likely first
+    pub top: Vec<(u32, f32)>,
+}
+
+/// Tokens sampled but not yet matched to emitted text
+#[derive(Debug, Clone, Default)]
+pub struct LogprobRecorder(Arc<Mutex<VecDeque<SampledToken>>>);
+
+impl LogprobRecorder {
+    fn push(&self, token: SampledToken) {
+        if let Ok(mut pending) = self.0.lock() {
+            pending.push_back(token);
+        }
+    }
+
+    /// Everything sampled since the last call, merged into one entry
+    ///
+    /// A chunk of text can span several tokens (e.g. held back for a stop
+    /// sequence); its log-probability is their sum and the alternatives are
+    /// those of its first token. `None` if nothing was sampled.
+    pub fn take_chunk(&self) -> Option<SampledToken> {
+        let mut pending = self.0.lock().ok()?;
+        let mut tokens = pending.drain(..);
+        let mut chunk = tokens.next()?;
+        chunk.logprob += tokens.map(|token| token.logprob).sum::<f32>();
+        Some(chunk)
+    }
+}
+
+/// Forwards to `inner`, recording each pick when a recorder is attached
+///
+/// Without one it adds nothing, so `generate` always samples through it.
+#[derive(Debug, Clone)]
+pub struct LogprobSampler<S> {
+    inner: S,
+    recorder: Option<LogprobRecorder>,
+}
+
+impl<S> LogprobSampler<S> {
+    pub const fn new(inner: S, recorder: Option<LogprobRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<S: Sampler> Sampler for LogprobSampler<S> {
+    fn sample<'a>(
+        &mut self,
+        res: &mut dyn HasSamplerResources<TokenId = u32>,
+        logits: &'a mut Logits,
+    ) -> anyhow::Result<&'a mut Logits> {
+        let Some(recorder) = &self.recorder else {
+            return self.inner.sample(res, logits);
+        };
+        // The inner sampler rescales and truncates, so read the raw logits first
+        let log_probs = log_softmax(logits.iter().map(|l| (l.token_id, l.logit)).collect());
+        let logits = self.inner.sample(res, logits)?;
+        if let Some(token) = self
+            .inner
+            .sampled_token_id()
+            .and_then(|id| describe_pick(&log_probs, id, TOP_ALTERNATIVES))
+        {
+            recorder.push(token);
+        }
+        Ok(logits)
+    }
+
+    fn sampled_token_id(&self) -> Option<u32> {
+        self.inner.sampled_token_id()
+    }
+}
+
+/// Turn logits into log-probabilities over the whole vocabulary
+fn log_softmax(mut logits: Vec<(u32, f32)>) -> Vec<(u32, f32)> {
+    let max = logits
+        .iter()
+        .map(|(_, logit)| *logit)
+        .fold(f32::NEG_INFINITY, f32::max);
+    let log_sum = logits
+        .iter()
+        .map(|(_, logit)| (logit - max).exp())
+        .sum::<f32>()
+        .ln();
+    for (_, logit) in &mut logits {
+        *logit -= max + log_sum;
+    }
+    logits
+}
+
+/// The picked token's log-probability and the `top_n` likeliest tokens
+fn describe_pick(log_probs: &[(u32, f32)], token_id: u32, top_n: usize) -> Option<SampledToken> {
+    let logprob = log_probs.iter().find(|(id, _)| *id == token_id)?.1;
+    let mut top = log_probs.to_vec();
+    let by_probability = |a: &(u32, f32), b: &(u32, f32)| b.1.total_cmp(&a.1);
+    if top.len() > top_n {
+        // Partition first so only the top `top_n` get sorted
+        top.select_nth_unstable_by(top_n, by_probability);
+        top.truncate(top_n);
+    }
+    top.sort_by(by_probability);
+    Some(SampledToken { logprob, top })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_softmax_is_a_distribution() {
+        let log_probs = log_softmax(vec![(0, 2.0), (1, 1.0), (2, -1.0)]);
+        let total: f32 = log_probs.iter().map(|(_, lp)| lp.exp()).sum();
+        assert!((total - 1.0).abs() < 1e-5);
+        assert!(log_probs[0].1 > log_probs[1].1);
+    }
+
+    #[test]
+    fn test_describe_pick_reports_choice_and_alternatives() {
+        let log_probs = log_softmax(vec![(0, 0.5), (1, 3.0), (2, 1.0), (3, 2.0)]);
+        let pick = describe_pick(&log_probs, 2, 2).unwrap();
+        assert!((pick.logprob - log_probs[2].1).abs() < f32::EPSILON);
+        let ids: Vec<u32> = pick.top.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [1, 3]);
+        assert!(describe_pick(&log_probs, 9, 2).is_none());
+    }
+
+    #[test]
+    fn test_recorder_merges_tokens_into_one_chunk() {
+        let recorder = LogprobRecorder::default();
+        assert!(recorder.take_chunk().is_none());
+
+        let token = |logprob: f32, id: u32| SampledToken {
+            logprob,
+            top: vec![(id, logprob)],
+        };
+        recorder.push(token(-0.5, 1));
+        recorder.push(token(-1.5, 2));
+        let chunk = recorder.take_chunk().unwrap();
+        assert!((chunk.logprob + 2.0).abs() < 1e-6);
+        assert_eq!(chunk.top, vec![(1, -0.5)]);
+        assert!(recorder.take_chunk().is_none());
+    }
+}
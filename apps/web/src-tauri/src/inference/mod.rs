@@ -2,12 +2,29 @@
 //!
 //! This module provides Tauri commands for:
 //! - Loading/unloading models (AC3: cold model loading)
-//! - Streaming text generation (AC2: warm latency, AC5: generation rate)
+//! - Keeping several models loaded, switching between them, LRU eviction
+//! - Streaming text generation (AC2: warm latency, AC5: generation rate),
+//!   or the whole text at once via `generate_sync`
+//! - Multi-turn chat sessions that keep conversation history
 //! - Aborting generation (AC4: inference abort)
 //! - Error handling with user-friendly messages (AC6)
+//! - Sampling parameters, including Mirostat, and named presets of them
+//! - Per-token log-probabilities for confidence display
+//! - Stop sequences that end generation without emitting the match
+//! - JSON output checked against a schema (`generate_structured`)
+//! - Context-window limits, prompt token counts, and GGUF model details
+//! - Throughput benchmarks (`benchmark_model`)
 
+mod benchmark;
+mod chat;
 mod commands;
+mod gguf;
+mod logprobs;
+mod params;
+mod presets;
 mod state;
+mod stop;
+mod structured;
 
 pub use commands::*;
 pub use state::*;
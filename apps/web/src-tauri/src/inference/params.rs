@@ -0,0 +1,305 @@
+//! Sampling parameters for text generation
+//!
+//! Maps frontend-provided sampling options onto Kalosm's sampler.
+//! Reference: stack-knowledge/kalosm/language-model/docs/completion.md
+
+use super::commands::InferenceError;
+use kalosm::language::GenerationParameters;
+use serde::{Deserialize, Serialize};
+
+/// Default Mirostat target entropy (surprise), as in llama.cpp
+pub const DEFAULT_MIROSTAT_TAU: f32 = 5.0;
+/// Default Mirostat learning rate, as in llama.cpp
+pub const DEFAULT_MIROSTAT_ETA: f32 = 0.1;
+
+/// Sampling options for `generate`; unset fields keep Kalosm's defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationParams {
+    /// Softmax temperature (0 = greedy)
+    pub temperature: Option<f32>,
+    /// Nucleus sampling cutoff in (0, 1]
+    pub top_p: Option<f32>,
+    /// Keep only the k most likely tokens
+    pub top_k: Option<u32>,
+    /// Mirostat sampling; mutually exclusive with `top_p`/`top_k`
+    pub mirostat: Option<MirostatConfig>,
+    /// Sampler seed; the same prompt, seed and parameters repeat the output
+    pub seed: Option<u64>,
+    /// Penalty on recently generated tokens; 1.0 is off, 1.05-1.3 curbs loops
+    pub repeat_penalty: Option<f32>,
+    /// How many recent tokens `repeat_penalty` looks back over
+    pub repeat_last_n: Option<usize>,
+    /// Generation stops when any of these appears; the match isn't emitted
+    pub stop_sequences: Vec<String>,
+}
+
+/// Mirostat sampler settings
+///
+/// Defaults: version 2, `tau` 5.0, `eta` 0.1. Kalosm's sampler implements
+/// Mirostat 2.0 only, so version 1 is rejected as invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MirostatConfig {
+    /// Mirostat algorithm version (only 2 is supported)
+    pub version: u8,
+    /// Target entropy; lower is more focused, higher more varied
+    pub tau: f32,
+    /// Learning rate for adjusting towards `tau`
+    pub eta: f32,
+}
+
+impl Default for MirostatConfig {
+    fn default() -> Self {
+        Self {
+            version: 2,
+            tau: DEFAULT_MIROSTAT_TAU,
+            eta: DEFAULT_MIROSTAT_ETA,
+        }
+    }
+}
+
+impl GenerationParams {
+    /// Reject out-of-range values and conflicting sampler choices
+    pub fn validate(&self) -> Result<(), InferenceError> {
+        if self.temperature.is_some_and(|t| !(0.0..).contains(&t)) {
+            return Err(InferenceError::invalid_parameters(
+                "temperature must be >= 0",
+            ));
+        }
+        if self
+            .top_p
+            .is_some_and(|p| p <= 0.0 || !(0.0..=1.0).contains(&p))
+        {
+            return Err(InferenceError::invalid_parameters(
+                "top_p must be in (0, 1]",
+            ));
+        }
+        if self.stop_sequences.iter().any(String::is_empty) {
+            return Err(InferenceError::invalid_parameters(
+                "stop_sequences must not contain empty strings",
+            ));
+        }
+        if self.top_k == Some(0) {
+            return Err(InferenceError::invalid_parameters(
+                "top_k must be at least 1",
+            ));
+        }
+        if self
+            .repeat_penalty
+            .is_some_and(|p| !p.is_finite() || p <= 0.0)
+        {
+            return Err(InferenceError::invalid_parameters(
+                "repeat_penalty must be > 0 (1.0 disables it)",
+            ));
+        }
+
+        if let Some(mirostat) = &self.mirostat {
+            if self.top_p.is_some() || self.top_k.is_some() {
+                return Err(InferenceError::invalid_parameters(
+                    "mirostat cannot be combined with top_p or top_k",
+                ));
+            }
+            if mirostat.version != 2 {
+                return Err(InferenceError::invalid_parameters(&format!(
+                    "mirostat version {} is not supported (only version 2)",
+                    mirostat.version
+                )));
+            }
+            let tau_valid = mirostat.tau.is_finite() && mirostat.tau > 0.0;
+            let eta_valid = mirostat.eta > 0.0 && (0.0..=1.0).contains(&mirostat.eta);
+            if !tau_valid || !eta_valid {
+                return Err(InferenceError::invalid_parameters(
+                    "mirostat requires tau > 0 and eta in (0, 1]",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Layer `overrides` on top of these parameters; set fields win
+    #[must_use]
+    pub fn merged_with(self, overrides: &Self) -> Self {
+        Self {
+            temperature: overrides.temperature.or(self.temperature),
+            top_p: overrides.top_p.or(self.top_p),
+            top_k: overrides.top_k.or(self.top_k),
+            mirostat: overrides.mirostat.or(self.mirostat),
+            seed: overrides.seed.or(self.seed),
+            repeat_penalty: overrides.repeat_penalty.or(self.repeat_penalty),
+            repeat_last_n: overrides.repeat_last_n.or(self.repeat_last_n),
+            stop_sequences: if overrides.stop_sequences.is_empty() {
+                self.stop_sequences
+            } else {
+                overrides.stop_sequences.clone()
+            },
+        }
+    }
+
+    /// The seed to sample with, picking (and keeping) a random one if unset
+    ///
+    /// Resolving up front means every run can be reported and replayed.
+    pub fn resolve_seed(&mut self) -> u64 {
+        *self
+            .seed
+            .get_or_insert_with(|| uuid::Uuid::new_v4().as_u64_pair().0)
+    }
+
+    /// Build the Kalosm sampler for these parameters
+    pub fn to_sampler(&self) -> GenerationParameters {
+        let mut sampler = GenerationParameters::default();
+        if let Some(temperature) = self.temperature {
+            sampler = sampler.with_temperature(temperature);
+        }
+        if let Some(top_p) = self.top_p {
+            sampler = sampler.with_top_p(f64::from(top_p));
+        }
+        if let Some(top_k) = self.top_k {
+            sampler = sampler.with_top_k(top_k);
+        }
+        if let Some(seed) = self.seed {
+            sampler = sampler.with_seed(seed);
+        }
+        if let Some(penalty) = self.repeat_penalty {
+            sampler = sampler.with_repetition_penalty(penalty);
+        }
+        if let Some(last_n) = self.repeat_last_n {
+            sampler =
+                sampler.with_repetition_penalty_range(u32::try_from(last_n).unwrap_or(u32::MAX));
+        }
+        if let Some(mirostat) = self.mirostat {
+            // Mirostat starts with mu at twice the target entropy
+            sampler = sampler
+                .with_tau(mirostat.tau)
+                .with_eta(mirostat.eta)
+                .with_mu(2.0 * mirostat.tau);
+        }
+        sampler
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirostat_defaults() {
+        let params: GenerationParams = serde_json::from_str(r#"{"mirostat":{}}"#).unwrap();
+        assert_eq!(params.mirostat, Some(MirostatConfig::default()));
+        assert!(params.validate().is_ok());
+    }
+
+    #[test]
+    fn test_mirostat_is_exclusive_with_top_p_and_top_k() {
+        let params = GenerationParams {
+            top_k: Some(40),
+            mirostat: Some(MirostatConfig::default()),
+            ..GenerationParams::default()
+        };
+        let err = params.validate().unwrap_err();
+        assert!(matches!(
+            err.code,
+            crate::inference::InferenceErrorCode::InvalidParameters
+        ));
+    }
+
+    #[test]
+    fn test_resolve_seed_keeps_given_seed() {
+        let mut params = GenerationParams {
+            seed: Some(7),
+            ..GenerationParams::default()
+        };
+        assert_eq!(params.resolve_seed(), 7);
+        assert_eq!(params.resolve_seed(), 7);
+        assert_eq!(params.seed, Some(7));
+
+        // An auto-generated seed is stored so it can be reported and reused
+        let mut params = GenerationParams::default();
+        let seed = params.resolve_seed();
+        assert_eq!(params.seed, Some(seed));
+        assert_eq!(params.resolve_seed(), seed);
+    }
+
+    /// Directory holding `model.gguf` and `tokenizer.json` for a small model
+    const FIXTURE_MODEL_DIR: &str = "CONTINUUM_FIXTURE_MODEL_DIR";
+
+    /// Sample `max_length` tokens from the fixture model with `params`
+    async fn sample_fixture(
+        model: &kalosm::language::Llama,
+        params: &GenerationParams,
+        max_length: u32,
+    ) -> String {
+        use futures_util::StreamExt;
+        use kalosm::language::TextCompletionModelExt;
+
+        let sampler = params.to_sampler().with_max_length(max_length);
+        model
+            .complete("Once upon a time")
+            .with_sampler(sampler)
+            .collect::<Vec<String>>()
+            .await
+            .concat()
+    }
+
+    /// Run with `CONTINUUM_FIXTURE_MODEL_DIR=<dir> cargo test
+    /// test_same_seed_repeats_output -- --ignored`; any small GGUF model will do.
+    #[tokio::test]
+    #[ignore = "needs a model fixture in CONTINUUM_FIXTURE_MODEL_DIR"]
+    async fn test_same_seed_repeats_output() {
+        use kalosm::language::{FileSource, Llama, LlamaSource};
+
+        let dir = std::path::PathBuf::from(std::env::var(FIXTURE_MODEL_DIR).unwrap());
+        let source = LlamaSource::new(FileSource::Local(dir.join("model.gguf")))
+            .with_tokenizer(FileSource::Local(dir.join("tokenizer.json")));
+        let model = Llama::builder().with_source(source).build().await.unwrap();
+
+        // Hot enough that an unseeded sampler would wander between runs
+        let params = GenerationParams {
+            temperature: Some(1.5),
+            seed: Some(7),
+            ..GenerationParams::default()
+        };
+        let first = sample_fixture(&model, &params, 32).await;
+        let second = sample_fixture(&model, &params, 32).await;
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+
+        let reseeded = GenerationParams {
+            seed: Some(8),
+            ..params
+        };
+        assert_ne!(first, sample_fixture(&model, &reseeded, 32).await);
+    }
+
+    #[test]
+    fn test_repeat_penalty_must_be_positive() {
+        for penalty in [0.0, -1.1, f32::NAN] {
+            let params = GenerationParams {
+                repeat_penalty: Some(penalty),
+                ..GenerationParams::default()
+            };
+            assert!(params.validate().is_err(), "{penalty} accepted");
+        }
+
+        let params: GenerationParams =
+            serde_json::from_str(r#"{"repeat_penalty":1.1,"repeat_last_n":64}"#).unwrap();
+        assert!(params.validate().is_ok());
+        let merged = GenerationParams::default().merged_with(&params);
+        assert_eq!(merged.repeat_penalty, Some(1.1));
+        assert_eq!(merged.repeat_last_n, Some(64));
+    }
+
+    #[test]
+    fn test_unsupported_mirostat_version_rejected() {
+        let params = GenerationParams {
+            mirostat: Some(MirostatConfig {
+                version: 1,
+                ..MirostatConfig::default()
+            }),
+            ..GenerationParams::default()
+        };
+        assert!(params.validate().is_err());
+    }
+}
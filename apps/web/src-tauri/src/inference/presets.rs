@@ -0,0 +1,111 @@
+//! Named generation presets
+//!
+//! Saved `GenerationParams` so users don't re-enter the same sampling
+//! settings. Stored as `generation_presets.json` in the app data directory.
+
+use super::params::GenerationParams;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Presets file name inside the app data directory
+const PRESETS_FILE: &str = "generation_presets.json";
+
+/// Presets keyed by name (sorted, so listings are stable)
+pub type GenerationPresets = BTreeMap<String, GenerationParams>;
+
+/// Path of the presets file for an app data directory
+fn path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(PRESETS_FILE)
+}
+
+/// Load all presets, empty if the file is missing or unreadable
+pub fn load(app_data_dir: &Path) -> GenerationPresets {
+    let path = path(app_data_dir);
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return GenerationPresets::new();
+    };
+
+    serde_json::from_str(&contents).unwrap_or_else(|e| {
+        log::warn!("Ignoring malformed presets file {}: {e}", path.display());
+        GenerationPresets::new()
+    })
+}
+
+/// Load, modify, and save the presets in one step
+pub fn update(
+    app_data_dir: &Path,
+    apply: impl FnOnce(&mut GenerationPresets),
+) -> Result<GenerationPresets, String> {
+    let mut presets = load(app_data_dir);
+    apply(&mut presets);
+    let json = serde_json::to_string_pretty(&presets)
+        .map_err(|e| format!("Failed to serialize presets: {e}"))?;
+    std::fs::write(path(app_data_dir), json).map_err(|e| format!("Failed to save presets: {e}"))?;
+    Ok(presets)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+    use crate::inference::params::MirostatConfig;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_presets_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let creative = GenerationParams {
+            temperature: Some(1.2),
+            top_p: Some(0.95),
+            ..GenerationParams::default()
+        };
+        let focused = GenerationParams {
+            temperature: Some(0.3),
+            mirostat: Some(MirostatConfig::default()),
+            ..GenerationParams::default()
+        };
+
+        update(dir.path(), |p| {
+            p.insert("creative".to_string(), creative.clone());
+            p.insert("focused".to_string(), focused.clone());
+        })
+        .unwrap();
+
+        let loaded = load(dir.path());
+        assert_eq!(loaded.keys().collect::<Vec<_>>(), ["creative", "focused"]);
+        assert_eq!(loaded["creative"], creative);
+        assert_eq!(loaded["focused"], focused);
+
+        update(dir.path(), |p| {
+            p.remove("creative");
+        })
+        .unwrap();
+        assert_eq!(load(dir.path()).len(), 1);
+    }
+
+    #[test]
+    fn test_missing_or_malformed_presets_file_is_empty() {
+        let dir = TempDir::new().unwrap();
+        assert!(load(dir.path()).is_empty());
+
+        std::fs::write(path(dir.path()), "{not json").unwrap();
+        assert!(load(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_explicit_params_override_preset() {
+        let preset = GenerationParams {
+            temperature: Some(0.3),
+            top_k: Some(40),
+            ..GenerationParams::default()
+        };
+        let explicit = GenerationParams {
+            temperature: Some(0.9),
+            ..GenerationParams::default()
+        };
+
+        let merged = preset.merged_with(&explicit);
+        assert_eq!(merged.temperature, Some(0.9));
+        assert_eq!(merged.top_k, Some(40));
+    }
+}
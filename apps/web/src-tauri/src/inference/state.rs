@@ -3,9 +3,22 @@
 //! Manages model lifecycle and generation state across Tauri commands.
 //! Reference: stack-knowledge/kalosm/language-model/docs/chat.md
 
-use kalosm::language::Llama;
+use kalosm::language::{Chat, Llama};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, MutexGuard, RwLock};
+use tokio_util::sync::CancellationToken;
+
+/// How long `Generating` may outlive every generation before it's reset
+pub const STUCK_GENERATION_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// Models kept in memory by default: the active one plus one on standby
+///
+/// Switching back to the previous model is then instant instead of a reload.
+pub const DEFAULT_MAX_LOADED_MODELS: usize = 2;
 
 /// Model status for UI state management
 #[derive(Debug, Clone, serde::Serialize)]
@@ -23,23 +36,119 @@ pub enum ModelStatus {
     Error,
 }
 
+/// Most recent first-token latencies, split by cold and warm runs
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct LatencyStats {
+    /// First generation after a model load (AC3)
+    pub last_cold_first_token_ms: Option<u64>,
+    /// Any later generation on the same loaded model (AC2)
+    pub last_warm_first_token_ms: Option<u64>,
+}
+
+/// Memory a loaded model added to the process (RAM) and the GPUs (VRAM)
+///
+/// `None` where a measurement isn't possible (e.g. no nvidia-smi for VRAM).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct MemoryUsage {
+    pub ram_mb: Option<u64>,
+    pub vram_mb: Option<u64>,
+}
+
+impl MemoryUsage {
+    /// Growth between two readings; shrinkage is reported as zero
+    pub fn delta(before: Self, after: Self) -> Self {
+        let grown = |before: Option<u64>, after: Option<u64>| Some(after?.saturating_sub(before?));
+        Self {
+            ram_mb: grown(before.ram_mb, after.ram_mb),
+            vram_mb: grown(before.vram_mb, after.vram_mb),
+        }
+    }
+}
+
+/// Details of the most recent `generate` call, for reproducing it
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct GenerationInfo {
+    pub model_id: Option<String>,
+    /// Seed the sampler used (auto-generated when the caller gave none)
+    pub seed: u64,
+}
+
+/// A chat conversation and the system prompt it was started with
+pub struct ChatSession {
+    /// Kalosm session holding the history and applying the chat template
+    pub chat: Chat<Llama>,
+    /// Fixed for the life of the session; `reset_chat_session` to change it
+    pub system_prompt: Option<String>,
+    /// Tokens the history takes up so far, system prompt included
+    pub history_tokens: u64,
+}
+
+/// A loaded model other than the active one, ready for `set_active_model`
+pub struct StandbyModel {
+    model: Llama,
+    memory_usage: Option<MemoryUsage>,
+    context_length: Option<u64>,
+    external_files: Option<(PathBuf, PathBuf)>,
+    /// When it was last active, for least-recently-used eviction
+    last_used: Instant,
+}
+
 /// Inference state managed by Tauri
 /// Uses Arc<RwLock> for safe concurrent access across async commands
 pub struct InferenceState {
     /// The loaded model instance
     pub model: RwLock<Option<Llama>>,
-    /// Flag to signal abort to the generation loop
-    pub abort_flag: RwLock<bool>,
+    /// ID of the loaded model (None when unloaded)
+    pub model_id: RwLock<Option<String>>,
+    /// Cancelled to abort the running generation; replaced when one starts
+    abort_token: RwLock<CancellationToken>,
     /// Current model status
     pub status: RwLock<ModelStatus>,
+    /// When `status` last changed (used to detect a stuck `Generating`)
+    status_changed_at: RwLock<Instant>,
+    /// Number of `generate` calls currently running
+    active_generations: AtomicUsize,
+    /// Held for a whole generation so concurrent ones take turns
+    generation_queue: Mutex<()>,
+    /// True until the first generation after a load has started
+    pub cold: RwLock<bool>,
+    /// First-token latency telemetry
+    pub latency: RwLock<LatencyStats>,
+    /// Memory the loaded model consumed, measured around the load
+    pub memory_usage: RwLock<Option<MemoryUsage>>,
+    /// Context window in tokens for the loaded model (None if unknown)
+    pub context_length: RwLock<Option<u64>>,
+    /// Weights and tokenizer paths when loaded via `load_model_from_path`
+    pub external_files: RwLock<Option<(PathBuf, PathBuf)>>,
+    /// Seed and model of the most recent generation
+    pub last_generation: RwLock<Option<GenerationInfo>>,
+    /// Active chat session and its history (None until `start_chat_session`)
+    pub chat: Mutex<Option<ChatSession>>,
+    /// Loaded models other than the active one, by model ID
+    standby: RwLock<HashMap<String, StandbyModel>>,
+    /// Cap on models in memory, active one included (`usize::MAX` = no cap)
+    max_loaded_models: RwLock<usize>,
 }
 
 impl Default for InferenceState {
     fn default() -> Self {
         Self {
             model: RwLock::new(None),
-            abort_flag: RwLock::new(false),
+            model_id: RwLock::new(None),
+            abort_token: RwLock::new(CancellationToken::new()),
             status: RwLock::new(ModelStatus::Unloaded),
+            status_changed_at: RwLock::new(Instant::now()),
+            active_generations: AtomicUsize::new(0),
+            generation_queue: Mutex::new(()),
+            cold: RwLock::new(true),
+            latency: RwLock::new(LatencyStats::default()),
+            memory_usage: RwLock::new(None),
+            context_length: RwLock::new(None),
+            external_files: RwLock::new(None),
+            last_generation: RwLock::new(None),
+            chat: Mutex::new(None),
+            standby: RwLock::new(HashMap::new()),
+            max_loaded_models: RwLock::new(DEFAULT_MAX_LOADED_MODELS),
         }
     }
 }
@@ -50,29 +159,241 @@ impl InferenceState {
         Arc::new(Self::default())
     }
 
+    /// Create with a persisted cap on loaded models (`usize::MAX` = no cap)
+    pub fn with_max_loaded_models(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            max_loaded_models: RwLock::new(limit),
+            ..Self::default()
+        })
+    }
+
     /// Check if model is loaded
     pub async fn is_loaded(&self) -> bool {
         self.model.read().await.is_some()
     }
 
-    /// Set abort flag to true
+    /// ID of the currently loaded model
+    pub async fn current_model_id(&self) -> Option<String> {
+        self.model_id.read().await.clone()
+    }
+
+    /// Context window of the loaded model, honouring any load-time override
+    pub async fn context_length(&self) -> Option<u64> {
+        *self.context_length.read().await
+    }
+
+    /// Drop the loaded model, returning its ID if one was loaded
+    ///
+    /// A running generation holds the model's read lock, so it is asked to
+    /// abort first rather than making the unload wait for it to finish.
+    pub async fn release_model(&self) -> Option<String> {
+        self.request_abort().await;
+        *self.model.write().await = None;
+        *self.memory_usage.write().await = None;
+        *self.context_length.write().await = None;
+        *self.external_files.write().await = None;
+        *self.chat.lock().await = None;
+        self.model_id.write().await.take()
+    }
+
+    /// Whether `model_id` is loaded, either active or on standby
+    pub async fn is_model_loaded(&self, model_id: &str) -> bool {
+        self.current_model_id().await.as_deref() == Some(model_id)
+            || self.standby.read().await.contains_key(model_id)
+    }
+
+    /// Move the active model to standby, returning its ID
+    ///
+    /// Aborts any running generation, and drops the chat session since it
+    /// belongs to the active model.
+    pub async fn park_active_model(&self) -> Option<String> {
+        self.request_abort().await;
+        let model = self.model.write().await.take();
+        let model_id = self.model_id.write().await.take();
+        let memory_usage = self.memory_usage.write().await.take();
+        let context_length = self.context_length.write().await.take();
+        let external_files = self.external_files.write().await.take();
+        *self.chat.lock().await = None;
+
+        let (model, model_id) = (model?, model_id?);
+        self.standby.write().await.insert(
+            model_id.clone(),
+            StandbyModel {
+                model,
+                memory_usage,
+                context_length,
+                external_files,
+                last_used: Instant::now(),
+            },
+        );
+        Some(model_id)
+    }
+
+    /// Make a standby model active, parking the current one
+    ///
+    /// Returns `false` if `model_id` isn't on standby.
+    pub async fn activate_standby(&self, model_id: &str) -> bool {
+        let Some(standby) = self.standby.write().await.remove(model_id) else {
+            return false;
+        };
+        self.park_active_model().await;
+        *self.model.write().await = Some(standby.model);
+        *self.model_id.write().await = Some(model_id.to_string());
+        *self.memory_usage.write().await = standby.memory_usage;
+        *self.context_length.write().await = standby.context_length;
+        *self.external_files.write().await = standby.external_files;
+        true
+    }
+
+    /// Unload one model, active or on standby; returns whether it was loaded
+    pub async fn unload(&self, model_id: &str) -> bool {
+        if self.current_model_id().await.as_deref() == Some(model_id) {
+            self.release_model().await;
+            self.set_status(ModelStatus::Unloaded).await;
+            return true;
+        }
+        self.standby.write().await.remove(model_id).is_some()
+    }
+
+    /// Unload the active model and everything on standby
+    pub async fn unload_all(&self) {
+        self.release_model().await;
+        self.standby.write().await.clear();
+    }
+
+    /// Set the cap on loaded models (`None` = no cap), evicting any excess
+    pub async fn set_max_loaded_models(&self, limit: Option<usize>) -> Vec<String> {
+        *self.max_loaded_models.write().await = limit.unwrap_or(usize::MAX);
+        self.evict_standby(0).await
+    }
+
+    /// Drop least-recently-used standby models until `incoming` more fit
+    ///
+    /// Counts the active model against the cap. Evicted models are removed
+    /// from the map, which drops their `Llama` and frees the memory.
+    pub async fn evict_standby(&self, incoming: usize) -> Vec<String> {
+        let limit = *self.max_loaded_models.read().await;
+        let active = usize::from(self.is_loaded().await);
+        let mut standby = self.standby.write().await;
+        let excess = (active + standby.len() + incoming).saturating_sub(limit);
+        let evicted = least_recently_used(
+            standby
+                .iter()
+                .map(|(id, model)| (id.as_str(), model.last_used)),
+            excess,
+        );
+        for model_id in &evicted {
+            standby.remove(model_id);
+            log::info!("Evicted model {model_id} (max_loaded_models reached)");
+        }
+        evicted
+    }
+
+    /// Cancel the current abort token, stopping the running generation
     pub async fn request_abort(&self) {
-        *self.abort_flag.write().await = true;
+        self.abort_token.read().await.cancel();
     }
 
-    /// Check if abort was requested
-    pub async fn is_abort_requested(&self) -> bool {
-        *self.abort_flag.read().await
+    /// Token a generation selects against to stop as soon as it's cancelled
+    pub async fn abort_token(&self) -> CancellationToken {
+        self.abort_token.read().await.clone()
     }
 
-    /// Reset abort flag
+    /// Start over with a fresh token (a cancelled one can't be reset)
     pub async fn reset_abort(&self) {
-        *self.abort_flag.write().await = false;
+        *self.abort_token.write().await = CancellationToken::new();
+    }
+
+    /// Atomically claim the `Loading` status for a new model load
+    ///
+    /// Returns `false` if another load is already in progress, so racing
+    /// `load_model` calls (e.g. a double-click) don't build two models.
+    pub async fn begin_loading(&self) -> bool {
+        let mut status = self.status.write().await;
+        if matches!(*status, ModelStatus::Loading) {
+            return false;
+        }
+        *status = ModelStatus::Loading;
+        *self.status_changed_at.write().await = Instant::now();
+        true
+    }
+
+    /// Track a running generation until the returned guard is dropped
+    ///
+    /// The guard is dropped on early returns and panics alike, so the
+    /// watchdog can tell a live generation from a `Generating` left behind.
+    pub fn begin_generation(&self) -> GenerationGuard<'_> {
+        self.active_generations.fetch_add(1, Ordering::SeqCst);
+        GenerationGuard(&self.active_generations)
+    }
+
+    /// Wait for this generation's turn; requests are served in order
+    ///
+    /// With `wait` false, returns `None` at once while another generation
+    /// holds the turn. The turn is released when the guard drops.
+    pub async fn generation_slot(&self, wait: bool) -> Option<MutexGuard<'_, ()>> {
+        if wait {
+            Some(self.generation_queue.lock().await)
+        } else {
+            self.generation_queue.try_lock().ok()
+        }
+    }
+
+    /// Whether any generation is currently running
+    pub fn is_generating(&self) -> bool {
+        self.active_generations.load(Ordering::SeqCst) > 0
+    }
+
+    /// Reset a `Generating` status that no running generation backs
+    ///
+    /// Only acts once the status is older than `threshold`, to leave room
+    /// for a generation that is just starting. Returns the corrected status.
+    pub async fn recover_stuck_status(&self, threshold: Duration) -> Option<ModelStatus> {
+        if !matches!(self.get_status().await, ModelStatus::Generating)
+            || self.is_generating()
+            || self.status_changed_at.read().await.elapsed() < threshold
+        {
+            return None;
+        }
+
+        let status = if self.is_loaded().await {
+            ModelStatus::Loaded
+        } else {
+            ModelStatus::Unloaded
+        };
+        self.set_status(status.clone()).await;
+        Some(status)
+    }
+
+    /// Mark the next generation as cold (call after loading a model)
+    pub async fn mark_cold(&self) {
+        *self.cold.write().await = true;
+    }
+
+    /// Whether this generation is cold; later calls report warm until the next load
+    pub async fn take_cold(&self) -> bool {
+        std::mem::replace(&mut *self.cold.write().await, false)
+    }
+
+    /// Record a first-token latency for a cold or warm generation
+    pub async fn record_first_token(&self, cold: bool, first_token_ms: u64) {
+        let mut latency = self.latency.write().await;
+        if cold {
+            latency.last_cold_first_token_ms = Some(first_token_ms);
+        } else {
+            latency.last_warm_first_token_ms = Some(first_token_ms);
+        }
+    }
+
+    /// Get the latest latency stats
+    pub async fn latency_stats(&self) -> LatencyStats {
+        self.latency.read().await.clone()
     }
 
     /// Update status
     pub async fn set_status(&self, status: ModelStatus) {
         *self.status.write().await = status;
+        *self.status_changed_at.write().await = Instant::now();
     }
 
     /// Get current status
@@ -80,3 +401,181 @@ impl InferenceState {
         self.status.read().await.clone()
     }
 }
+
+/// The `count` least recently used IDs, oldest first
+fn least_recently_used<'a>(
+    entries: impl Iterator<Item = (&'a str, Instant)>,
+    count: usize,
+) -> Vec<String> {
+    let mut entries: Vec<_> = entries.collect();
+    entries.sort_by_key(|&(_, last_used)| last_used);
+    entries
+        .into_iter()
+        .take(count)
+        .map(|(id, _)| id.to_string())
+        .collect()
+}
+
+/// Marks a generation as running for as long as it is alive
+pub struct GenerationGuard<'a>(&'a AtomicUsize);
+
+impl Drop for GenerationGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_concurrent_loads_only_one_wins() {
+        let state = InferenceState::new();
+
+        let (first, second) = tokio::join!(state.begin_loading(), state.begin_loading());
+
+        assert!(
+            first ^ second,
+            "exactly one load should claim the Loading status"
+        );
+        assert!(matches!(state.get_status().await, ModelStatus::Loading));
+    }
+
+    #[tokio::test]
+    async fn test_first_generation_after_load_is_cold() {
+        let state = InferenceState::new();
+        state.mark_cold().await;
+
+        assert!(state.take_cold().await);
+        assert!(!state.take_cold().await);
+
+        state.record_first_token(true, 900).await;
+        state.record_first_token(false, 120).await;
+        let stats = state.latency_stats().await;
+        assert_eq!(stats.last_cold_first_token_ms, Some(900));
+        assert_eq!(stats.last_warm_first_token_ms, Some(120));
+    }
+
+    #[tokio::test]
+    async fn test_release_model_aborts_generation_and_forgets_id() {
+        let state = InferenceState::new();
+        *state.model_id.write().await = Some("phi-3".to_string());
+
+        assert_eq!(state.release_model().await.as_deref(), Some("phi-3"));
+        assert!(state.abort_token().await.is_cancelled());
+        assert_eq!(state.current_model_id().await, None);
+        assert_eq!(state.release_model().await, None);
+    }
+
+    #[test]
+    fn test_least_recently_used_picks_oldest() {
+        let now = Instant::now();
+        let entries = [
+            ("mistral", now + Duration::from_secs(2)),
+            ("phi-3", now),
+            ("llama", now + Duration::from_secs(1)),
+        ];
+
+        assert_eq!(
+            least_recently_used(entries.into_iter(), 2),
+            ["phi-3", "llama"]
+        );
+        assert!(least_recently_used(entries.into_iter(), 0).is_empty());
+        assert_eq!(least_recently_used(entries.into_iter(), 5).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_unload_reports_unknown_model() {
+        let state = InferenceState::new();
+        *state.model_id.write().await = Some("phi-3".to_string());
+
+        assert!(!state.unload("mistral").await);
+        assert_eq!(state.current_model_id().await.as_deref(), Some("phi-3"));
+        assert!(state.unload("phi-3").await);
+        assert_eq!(state.current_model_id().await, None);
+    }
+
+    #[test]
+    fn test_memory_usage_delta() {
+        let before = MemoryUsage {
+            ram_mb: Some(300),
+            vram_mb: None,
+        };
+        let after = MemoryUsage {
+            ram_mb: Some(2_400),
+            vram_mb: Some(4_000),
+        };
+
+        let delta = MemoryUsage::delta(before, after);
+        assert_eq!(delta.ram_mb, Some(2_100));
+        assert_eq!(delta.vram_mb, None);
+
+        // Memory freed elsewhere during the load doesn't go negative
+        assert_eq!(MemoryUsage::delta(after, before).ram_mb, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_stuck_generating_status_is_recovered() {
+        let state = InferenceState::new();
+        state.set_status(ModelStatus::Generating).await;
+
+        // A running generation keeps the status, however old it is
+        let generation = state.begin_generation();
+        assert!(state.recover_stuck_status(Duration::ZERO).await.is_none());
+
+        // Recent status changes get the benefit of the doubt
+        drop(generation);
+        assert!(!state.is_generating());
+        assert!(state
+            .recover_stuck_status(Duration::from_mins(1))
+            .await
+            .is_none());
+
+        // Past the threshold with nothing running: reset (no model, so Unloaded)
+        let recovered = state.recover_stuck_status(Duration::ZERO).await;
+        assert!(matches!(recovered, Some(ModelStatus::Unloaded)));
+        assert!(matches!(state.get_status().await, ModelStatus::Unloaded));
+    }
+
+    #[tokio::test]
+    async fn test_generations_take_turns() {
+        let state = InferenceState::new();
+        let slot = state.generation_slot(true).await;
+        assert!(slot.is_some());
+        assert!(state.generation_slot(false).await.is_none());
+
+        // A waiting request gets the turn as soon as the running one ends
+        let waiting = {
+            let state = Arc::clone(&state);
+            tokio::spawn(async move { state.generation_slot(true).await.is_some() })
+        };
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        drop(slot);
+        assert!(matches!(waiting.await, Ok(true)));
+        assert!(state.generation_slot(false).await.is_some());
+    }
+
+    #[test]
+    #[allow(clippy::panic)] // Simulates a generation that panics
+    fn test_generation_guard_released_on_panic() {
+        let state = InferenceState::new();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _generation = state.begin_generation();
+            panic!("generation crashed");
+        }));
+        assert!(result.is_err());
+        assert!(!state.is_generating());
+    }
+
+    #[tokio::test]
+    async fn test_load_allowed_again_after_previous_finishes() {
+        let state = InferenceState::new();
+        assert!(state.begin_loading().await);
+
+        state.set_status(ModelStatus::Error).await;
+
+        assert!(state.begin_loading().await);
+    }
+}
@@ -0,0 +1,146 @@
+//! Stop sequences for `generate`
+//!
+//! Generated text is held back while it could still be the start of a stop
+//! sequence, so a sequence split across tokens is caught and its text never
+//! reaches the frontend.
+
+/// Outcome of feeding one token to `StopSequences`
+#[derive(Debug, PartialEq, Eq)]
+pub enum StopScan {
+    /// Text that is safe to emit (empty while everything is held back)
+    Continue(String),
+    /// A stop sequence matched; emit the text before it, then stop
+    Stopped(String),
+}
+
+/// Watches generated text for any of a set of stop sequences
+pub struct StopSequences {
+    sequences: Vec<String>,
+    /// Tail of the output that may be the start of a sequence
+    held: String,
+}
+
+impl StopSequences {
+    /// Empty sequences are ignored (`GenerationParams::validate` rejects them)
+    pub fn new(sequences: &[String]) -> Self {
+        Self {
+            sequences: sequences
+                .iter()
+                .filter(|s| !s.is_empty())
+                .cloned()
+                .collect(),
+            held: String::new(),
+        }
+    }
+
+    /// Add a token, returning the text that can be emitted so far
+    pub fn push(&mut self, token: &str) -> StopScan {
+        if self.sequences.is_empty() {
+            return StopScan::Continue(token.to_string());
+        }
+        self.held.push_str(token);
+
+        // Earlier text was already checked, so any match ends in this token
+        let first_match = self
+            .sequences
+            .iter()
+            .filter_map(|s| self.held.find(s.as_str()))
+            .min();
+        if let Some(start) = first_match {
+            self.held.truncate(start);
+            return StopScan::Stopped(std::mem::take(&mut self.held));
+        }
+
+        let keep = self.partial_match_len();
+        let ready = self.held.len() - keep;
+        let rest = self.held.split_off(ready);
+        StopScan::Continue(std::mem::replace(&mut self.held, rest))
+    }
+
+    /// Release the held-back text once generation ends without a match
+    pub fn finish(&mut self) -> String {
+        std::mem::take(&mut self.held)
+    }
+
+    /// Length of the longest tail of `held` that starts some sequence
+    fn partial_match_len(&self) -> usize {
+        self.held
+            .char_indices()
+            .map(|(i, _)| &self.held[i..])
+            .find(|tail| self.sequences.iter().any(|s| s.starts_with(tail)))
+            .map_or(0, str::len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(sequences: &[&str], tokens: &[&str]) -> (String, bool) {
+        let sequences: Vec<String> = sequences.iter().map(ToString::to_string).collect();
+        let mut stop = StopSequences::new(&sequences);
+        let mut out = String::new();
+        for token in tokens {
+            match stop.push(token) {
+                StopScan::Continue(text) => out.push_str(&text),
+                StopScan::Stopped(text) => {
+                    out.push_str(&text);
+                    return (out, true);
+                },
+            }
+        }
+        out.push_str(&stop.finish());
+        (out, false)
+    }
+
+    #[test]
+    fn test_single_token_stop() {
+        assert_eq!(
+            feed(&["```"], &["Here", " it is", "```", "more"]),
+            ("Here it is".to_string(), true)
+        );
+        // Text before the sequence in the same token is kept
+        assert_eq!(
+            feed(&["```"], &["Done.```rust"]),
+            ("Done.".to_string(), true)
+        );
+    }
+
+    #[test]
+    fn test_multi_token_stop() {
+        assert_eq!(
+            feed(&["\n\nUser:"], &["Sure.", "\n", "\nUs", "er", ":", " hi"]),
+            ("Sure.".to_string(), true)
+        );
+    }
+
+    #[test]
+    fn test_held_text_released_when_no_match() {
+        let sequences = vec!["\n\nUser:".to_string()];
+        let mut stop = StopSequences::new(&sequences);
+        assert_eq!(stop.push("Hi\n"), StopScan::Continue("Hi".to_string()));
+        assert_eq!(stop.push("\nUse"), StopScan::Continue(String::new()));
+        // The prefix broke off, so it was ordinary output after all
+        assert_eq!(
+            stop.push("d it"),
+            StopScan::Continue("\n\nUsed it".to_string())
+        );
+        assert_eq!(
+            feed(&["\n\nUser:"], &["a\n", "\nUs"]),
+            ("a\n\nUs".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn test_earliest_of_several_sequences_wins() {
+        assert_eq!(
+            feed(&["END", "\n"], &["one\ntwo END"]),
+            ("one".to_string(), true)
+        );
+    }
+
+    #[test]
+    fn test_no_sequences_passes_tokens_through() {
+        assert_eq!(feed(&[], &["a", "b"]), ("ab".to_string(), false));
+    }
+}
@@ -0,0 +1,353 @@
+//! JSON output for `generate_structured`
+//!
+//! Kalosm's constrained sampling takes parsers, not a JSON Schema, so a
+//! schema that arrives at runtime is compiled to a regular expression and
+//! sampled through a `RegexParser`: the model can only produce JSON of the
+//! schema's shape. The reply is still validated afterwards, which catches a
+//! value cut short by the token budget. Both cover the JSON Schema subset
+//! used for tool calls: `type`, `properties`, `required`,
+//! `additionalProperties: false`, `items`, `enum`.
+
+use serde_json::{Map, Value};
+
+/// A JSON string, with the escapes JSON allows
+const STRING_PATTERN: &str = r#""(?:[^"\\\x00-\x1F]|\\["\\/bfnrt]|\\u[0-9a-fA-F]{4})*""#;
+
+/// A JSON integer
+const INTEGER_PATTERN: &str = r"-?(?:0|[1-9][0-9]*)";
+
+/// A JSON number, integer or not
+const NUMBER_PATTERN: &str = r"-?(?:0|[1-9][0-9]*)(?:\.[0-9]+)?(?:[eE][+-]?[0-9]+)?";
+
+/// Append the schema and output rules to the user's prompt
+pub fn prompt_with_schema(prompt: &str, schema: &Value) -> String {
+    format!(
+        "{prompt}\n\nRespond with a single JSON value matching this JSON Schema, \
+         and nothing else:\n{schema}\n"
+    )
+}
+
+/// Ensure `schema` is something `validate` can check
+///
+/// The value itself must be an object or array, which is what
+/// `extract_json` looks for in the reply.
+pub fn check_schema(schema: &Value) -> Result<(), String> {
+    let Some(object) = schema.as_object() else {
+        return Err("json_schema must be a JSON object".to_string());
+    };
+    let top_level: Vec<&str> = schema_types(object).collect();
+    if top_level.is_empty()
+        || top_level
+            .iter()
+            .any(|name| !matches!(*name, "object" | "array"))
+    {
+        return Err("json_schema must describe an object or an array".to_string());
+    }
+    check_subschema(schema)
+}
+
+fn check_subschema(schema: &Value) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Err("json_schema must be a JSON object".to_string());
+    };
+    for name in schema_types(schema) {
+        if !matches!(
+            name,
+            "object" | "array" | "string" | "number" | "integer" | "boolean" | "null"
+        ) {
+            return Err(format!("Unsupported schema type '{name}'"));
+        }
+    }
+    let children = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(Map::values)
+        .chain(schema.get("items"));
+    for child in children {
+        check_subschema(child)?;
+    }
+    Ok(())
+}
+
+/// Regular expression matching exactly the compact JSON `schema` allows
+///
+/// Objects list every declared property, in order; optional ones are
+/// always produced, which still matches the schema. Fails for parts of a
+/// schema a regex can't pin down, such as a property with no `type`.
+pub fn schema_regex(schema: &Value) -> Result<String, String> {
+    regex_at(schema, "$")
+}
+
+fn regex_at(schema: &Value, path: &str) -> Result<String, String> {
+    let Some(schema) = schema.as_object() else {
+        return Err(format!("{path} must be a JSON object"));
+    };
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        let literals: Vec<String> = allowed
+            .iter()
+            .map(|value| escape_regex(&value.to_string()))
+            .collect();
+        return Ok(format!("(?:{})", literals.join("|")));
+    }
+
+    let types: Vec<&str> = schema_types(schema).collect();
+    if types.is_empty() {
+        return Err(format!("{path} needs a type or enum to be enforced"));
+    }
+    let alternatives = types
+        .iter()
+        .map(|name| match *name {
+            "object" => object_regex(schema, path),
+            "array" => {
+                let Some(items) = schema.get("items") else {
+                    // Without an item schema only the empty array is certain to match
+                    return Ok(r"\[\]".to_string());
+                };
+                let item = regex_at(items, &format!("{path}[]"))?;
+                Ok(format!(r"\[(?:{item}(?:,{item})*)?\]"))
+            },
+            "string" => Ok(STRING_PATTERN.to_string()),
+            "integer" => Ok(INTEGER_PATTERN.to_string()),
+            "number" => Ok(NUMBER_PATTERN.to_string()),
+            "boolean" => Ok("(?:true|false)".to_string()),
+            "null" => Ok("null".to_string()),
+            other => Err(format!("Unsupported schema type '{other}'")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!("(?:{})", alternatives.join("|")))
+}
+
+/// `{"name":value,...}` with every declared property
+fn object_regex(schema: &Map<String, Value>, path: &str) -> Result<String, String> {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let required = schema.get("required").and_then(Value::as_array);
+    for name in required.into_iter().flatten().filter_map(Value::as_str) {
+        if !properties.is_some_and(|p| p.contains_key(name)) {
+            return Err(format!("{path}.{name} is required but has no schema"));
+        }
+    }
+
+    let fields = properties
+        .into_iter()
+        .flatten()
+        .map(|(name, field)| {
+            let value = regex_at(field, &format!("{path}.{name}"))?;
+            Ok(format!(
+                "{}:{value}",
+                escape_regex(&Value::String(name.clone()).to_string())
+            ))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(format!(r"\{{{}\}}", fields.join(",")))
+}
+
+/// Escape regex metacharacters so `text` matches literally
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if r"\.+*?()|[]{}^$#&-~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Parse the first JSON object or array in the model's reply
+///
+/// Models often wrap the value in prose or a Markdown code fence, so
+/// anything before and after it is ignored.
+pub fn extract_json(text: &str) -> Option<Value> {
+    text.match_indices(['{', '[']).find_map(|(start, _)| {
+        serde_json::Deserializer::from_str(&text[start..])
+            .into_iter::<Value>()
+            .next()?
+            .ok()
+    })
+}
+
+/// Check `value` against `schema`, naming the first mismatch by its path
+pub fn validate(value: &Value, schema: &Value) -> Result<(), String> {
+    validate_at(value, schema, "$")
+}
+
+fn validate_at(value: &Value, schema: &Value, path: &str) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    let types: Vec<&str> = schema_types(schema).collect();
+    if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+        return Err(format!("{path} should be {}", types.join(" or ")));
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            return Err(format!("{path} is not one of the allowed values"));
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let required = schema.get("required").and_then(Value::as_array);
+        for name in required.into_iter().flatten().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                return Err(format!("{path}.{name} is required"));
+            }
+        }
+        for (name, field) in object {
+            match properties.and_then(|p| p.get(name)) {
+                Some(field_schema) => validate_at(field, field_schema, &format!("{path}.{name}"))?,
+                None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                    return Err(format!("{path}.{name} is not allowed"));
+                },
+                None => {},
+            }
+        }
+    }
+    if let (Some(items), Some(item_schema)) = (value.as_array(), schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate_at(item, item_schema, &format!("{path}[{i}]"))?;
+        }
+    }
+    Ok(())
+}
+
+/// The `type` of a schema, which may be one name or a list of them
+fn schema_types(schema: &Map<String, Value>) -> impl Iterator<Item = &str> {
+    match schema.get("type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+    .into_iter()
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn person_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "age": { "type": "number" }
+            },
+            "required": ["name", "age"]
+        })
+    }
+
+    #[test]
+    fn test_person_schema_accepts_matching_reply() {
+        let schema = person_schema();
+        check_schema(&schema).unwrap();
+
+        let reply = "Sure! Here you go:\n```json\n{\"name\": \"Ada\", \"age\": 36}\n```";
+        let value = extract_json(reply).unwrap();
+        assert_eq!(value, json!({ "name": "Ada", "age": 36 }));
+        assert!(validate(&value, &schema).is_ok());
+    }
+
+    #[test]
+    fn test_person_schema_rejects_mismatches() {
+        let schema = person_schema();
+        assert_eq!(
+            validate(&json!({ "name": "Ada" }), &schema).unwrap_err(),
+            "$.age is required"
+        );
+        assert_eq!(
+            validate(&json!({ "name": "Ada", "age": "36" }), &schema).unwrap_err(),
+            "$.age should be number"
+        );
+        assert!(validate(&json!(["Ada", 36]), &schema).is_err());
+        assert!(extract_json("I don't know").is_none());
+    }
+
+    #[test]
+    fn test_nested_arrays_and_strict_objects() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "tags": { "type": "array", "items": { "enum": ["a", "b"] } }
+            },
+            "additionalProperties": false
+        });
+        assert!(validate(&json!({ "tags": ["a", "b"] }), &schema).is_ok());
+        assert_eq!(
+            validate(&json!({ "tags": ["a", "c"] }), &schema).unwrap_err(),
+            "$.tags[1] is not one of the allowed values"
+        );
+        assert_eq!(
+            validate(&json!({ "extra": 1 }), &schema).unwrap_err(),
+            "$.extra is not allowed"
+        );
+        assert!(check_schema(&json!({ "type": "date" })).is_err());
+        assert!(check_schema(&json!("object")).is_err());
+    }
+
+    #[test]
+    fn test_scalar_top_level_schemas_are_rejected() {
+        for schema in [
+            json!({ "type": "string" }),
+            json!({ "enum": [1, 2] }),
+            json!({}),
+        ] {
+            assert_eq!(
+                check_schema(&schema).unwrap_err(),
+                "json_schema must describe an object or an array"
+            );
+        }
+        assert!(check_schema(&json!({ "type": ["object", "null"] })).is_err());
+        assert!(check_schema(&json!({ "type": "array", "items": { "type": "string" } })).is_ok());
+    }
+
+    #[test]
+    fn test_schema_regex_pins_down_the_shape() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "ok": { "type": "boolean" },
+                "tags": { "type": "array", "items": { "enum": ["a", "b.c"] } }
+            }
+        });
+        let tags = r#"(?:\[(?:(?:"a"|"b\.c")(?:,(?:"a"|"b\.c"))*)?\])"#;
+        assert_eq!(
+            schema_regex(&schema).unwrap(),
+            format!(r#"(?:\{{"ok":(?:(?:true|false)),"tags":{tags}\}})"#)
+        );
+        let scores = json!({ "type": "array", "items": { "type": ["number", "null"] } });
+        let item = format!("(?:{NUMBER_PATTERN}|null)");
+        assert_eq!(
+            schema_regex(&scores).unwrap(),
+            format!(r"(?:\[(?:{item}(?:,{item})*)?\])")
+        );
+
+        let untyped = json!({ "type": "object", "properties": { "x": {} } });
+        assert_eq!(
+            schema_regex(&untyped).unwrap_err(),
+            "$.x needs a type or enum to be enforced"
+        );
+        let undeclared = json!({ "type": "object", "required": ["id"] });
+        assert_eq!(
+            schema_regex(&undeclared).unwrap_err(),
+            "$.id is required but has no schema"
+        );
+    }
+}
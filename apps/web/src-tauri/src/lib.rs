@@ -16,11 +16,13 @@
 mod downloads;
 mod hardware;
 mod inference;
+mod settings;
 mod verification;
 
 use downloads::DownloadState;
 use hardware::HardwareState;
 use inference::InferenceState;
+use settings::AppSettings;
 use tauri::Manager;
 use verification::commands::VerificationState;
 
@@ -28,33 +30,85 @@ use verification::commands::VerificationState;
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        .manage(InferenceState::new())
         .manage(HardwareState::new())
         .invoke_handler(tauri::generate_handler![
             // Inference commands (Story 1.4)
             inference::load_model,
+            inference::load_model_from_path,
+            inference::read_gguf_metadata,
             inference::generate,
+            inference::generate_structured,
+            inference::generate_sync,
+            inference::start_chat_session,
+            inference::send_chat_message,
+            inference::reset_chat_session,
             inference::abort_inference,
             inference::get_model_status,
+            inference::get_latency_stats,
+            inference::get_last_generation_info,
+            inference::get_model_memory_usage,
+            inference::count_tokens,
+            inference::embed,
             inference::unload_model,
+            inference::set_active_model,
+            inference::set_max_loaded_models,
+            inference::reset_inference_state,
+            inference::reload_model,
+            inference::benchmark_model,
+            inference::set_prewarm_on_startup,
+            inference::save_generation_preset,
+            inference::list_generation_presets,
+            inference::delete_generation_preset,
             // Hardware commands (Story 2.1)
             hardware::get_system_info,
             hardware::get_gpu_info,
+            hardware::get_all_gpus,
+            hardware::get_gpu_stats,
+            hardware::get_gpu_utilization,
+            hardware::get_models_disk_info,
+            hardware::max_loadable_model_mb,
+            hardware::recommend_models,
+            hardware::start_hardware_monitoring,
+            hardware::stop_hardware_monitoring,
+            hardware::set_hardware_cache_duration,
+            hardware::refresh_hardware,
             // Download commands (Story 2.3)
             downloads::start_download,
+            downloads::fetch_expected_hash,
+            downloads::fetch_text,
             downloads::pause_download,
             downloads::resume_download,
+            downloads::resume_from_disk,
+            downloads::redownload_corrupted,
+            downloads::requarantine_retry,
+            downloads::pause_all_downloads,
+            downloads::resume_all_downloads,
             downloads::cancel_download,
             downloads::get_download_progress,
             downloads::check_storage_space,
+            downloads::set_proxy,
+            downloads::set_max_concurrent_downloads,
+            downloads::set_download_max_retries,
+            downloads::set_progress_interval_ms,
+            downloads::set_quarantine_size_limit,
             downloads::get_model_path,
+            downloads::list_downloaded_models,
+            downloads::get_models_storage_usage,
+            downloads::cleanup_orphaned_downloads,
+            downloads::is_model_ready,
             downloads::get_partial_download_size,
+            downloads::delete_partial_download,
+            downloads::import_local_model,
             downloads::delete_model,
             // Verification commands (Story 2.5)
             verification::commands::verify_model_integrity,
             verification::commands::compute_model_checksum,
+            verification::commands::compute_tokenizer_checksum,
+            verification::commands::cancel_verification,
             verification::commands::list_quarantined_files,
             verification::commands::delete_quarantined_file,
+            verification::commands::prune_quarantine,
+            verification::commands::export_quarantine_report,
         ])
         .setup(|app| {
             // Initialize download state with app data directory
@@ -62,8 +116,22 @@ pub fn run() {
                 .path()
                 .app_data_dir()
                 .expect("Failed to get app data directory");
-            app.manage(DownloadState::new(app_data_dir.clone()));
+            let settings = AppSettings::load(&app_data_dir);
+            let mut download_state = DownloadState::new(app_data_dir.clone());
+            if let Some(buffer_mb) = settings.download_buffer_mb {
+                download_state = download_state.with_buffer_size(buffer_mb * 1024 * 1024);
+            }
+            app.manage(download_state);
             app.manage(VerificationState::new(app_data_dir));
+            app.manage(InferenceState::with_max_loaded_models(
+                settings
+                    .max_loaded_models
+                    .unwrap_or(inference::DEFAULT_MAX_LOADED_MODELS),
+            ));
+
+            // Load the last used model in the background if the user opted in
+            inference::prewarm_last_model(app.handle(), &settings);
+            inference::start_status_watchdog(app.handle());
 
             // Notification plugin (Story 2.3)
             app.handle().plugin(tauri_plugin_notification::init())?;
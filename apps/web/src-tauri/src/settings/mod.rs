@@ -0,0 +1,117 @@
+//! Persisted application settings
+//!
+//! Small user preferences that must survive app restarts.
+//! Stored as `settings.json` in the app data directory.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Settings file name inside the app data directory
+const SETTINGS_FILE: &str = "settings.json";
+
+/// User settings persisted across restarts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Proxy URL for model downloads (None = direct connection)
+    pub proxy_url: Option<String>,
+    /// Hosts that bypass `proxy_url`, in `NO_PROXY` syntax (None = `NO_PROXY`)
+    pub no_proxy: Option<String>,
+    /// Download write/hash buffer size in MB (None = 8MB default)
+    pub download_buffer_mb: Option<usize>,
+    /// Load `last_model_id` in the background when the app starts
+    pub prewarm_on_startup: bool,
+    /// Most recently loaded model, recorded on every successful load
+    pub last_model_id: Option<String>,
+    /// Cap on models kept in memory (None = default, `usize::MAX` = no cap)
+    pub max_loaded_models: Option<usize>,
+}
+
+impl AppSettings {
+    /// Path of the settings file for an app data directory
+    pub fn path(app_data_dir: &Path) -> PathBuf {
+        app_data_dir.join(SETTINGS_FILE)
+    }
+
+    /// Load settings, falling back to defaults if missing or unreadable
+    pub fn load(app_data_dir: &Path) -> Self {
+        let path = Self::path(app_data_dir);
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("Ignoring malformed settings file {}: {e}", path.display());
+            Self::default()
+        })
+    }
+
+    /// Write settings to disk
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize settings: {e}"))?;
+        std::fs::write(Self::path(app_data_dir), json)
+            .map_err(|e| format!("Failed to save settings: {e}"))
+    }
+
+    /// Load, modify, and save settings in one step
+    pub fn update(app_data_dir: &Path, apply: impl FnOnce(&mut Self)) -> Result<Self, String> {
+        let mut settings = Self::load(app_data_dir);
+        apply(&mut settings);
+        settings.save(app_data_dir)?;
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // Tests use unwrap for clarity
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_settings_file_uses_defaults() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(AppSettings::load(dir.path()), AppSettings::default());
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let dir = TempDir::new().unwrap();
+        AppSettings::update(dir.path(), |s| {
+            s.proxy_url = Some("http://proxy.local:8080".to_string());
+        })
+        .unwrap();
+
+        let loaded = AppSettings::load(dir.path());
+        assert_eq!(loaded.proxy_url.as_deref(), Some("http://proxy.local:8080"));
+    }
+
+    #[test]
+    fn test_malformed_settings_file_uses_defaults() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(AppSettings::path(dir.path()), "{not json").unwrap();
+        assert_eq!(AppSettings::load(dir.path()), AppSettings::default());
+    }
+
+    #[test]
+    fn test_older_settings_file_gets_new_defaults() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(AppSettings::path(dir.path()), r#"{"proxy_url":null}"#).unwrap();
+
+        let loaded = AppSettings::load(dir.path());
+        assert!(!loaded.prewarm_on_startup);
+        assert_eq!(loaded.last_model_id, None);
+        assert_eq!(loaded.max_loaded_models, None);
+    }
+
+    #[test]
+    fn test_uncapped_max_loaded_models_round_trips() {
+        let dir = TempDir::new().unwrap();
+        AppSettings::update(dir.path(), |s| s.max_loaded_models = Some(usize::MAX)).unwrap();
+        assert_eq!(
+            AppSettings::load(dir.path()).max_loaded_models,
+            Some(usize::MAX)
+        );
+    }
+}
@@ -3,8 +3,12 @@
 // File sizes displayed in MB don't need full f64 precision
 #![allow(clippy::cast_precision_loss)]
 
-use super::{VerificationProgress, VerificationResult};
-use std::path::PathBuf;
+use super::{HashAlgorithm, VerificationError, VerificationProgress, VerificationResult};
+use crate::downloads::{weights_path, QuarantineRecord};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tauri::ipc::Channel;
 use tauri::State;
 
@@ -23,11 +27,52 @@ pub struct QuarantinedFile {
 /// State for verification module
 pub struct VerificationState {
     pub app_data_dir: PathBuf,
+    /// Cancel flags for running verifications, keyed by model ID
+    cancel_flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
 }
 
 impl VerificationState {
-    pub const fn new(app_data_dir: PathBuf) -> Self {
-        Self { app_data_dir }
+    pub fn new(app_data_dir: PathBuf) -> Self {
+        Self {
+            app_data_dir,
+            cancel_flags: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Register a running verification, returning its cancel flag
+    ///
+    /// Only one verification per model may run at a time.
+    pub fn begin_verification(&self, model_id: &str) -> Result<Arc<AtomicBool>, VerificationError> {
+        let mut flags = self
+            .cancel_flags
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if flags.contains_key(model_id) {
+            return Err(VerificationError::already_running(model_id));
+        }
+        let flag = Arc::new(AtomicBool::new(false));
+        flags.insert(model_id.to_string(), Arc::clone(&flag));
+        Ok(flag)
+    }
+
+    /// Unregister a finished (or cancelled) verification
+    pub fn finish_verification(&self, model_id: &str) {
+        self.cancel_flags
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(model_id);
+    }
+
+    /// Signal a running verification to stop, returning whether one was running
+    pub fn cancel_verification(&self, model_id: &str) -> bool {
+        self.cancel_flags
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(model_id)
+            .is_some_and(|flag| {
+                flag.store(true, Ordering::Relaxed);
+                true
+            })
     }
 
     /// Get the models directory
@@ -42,53 +87,196 @@ impl VerificationState {
 }
 
 /// Verify a downloaded model's integrity
+///
+/// `algorithm` is what `expected_hash` was made with (SHA-256 if omitted);
+/// `parallel` hashes on several threads, which is fastest with BLAKE3.
+/// Can be stopped with `cancel_verification`, which fails with kind `cancelled`.
 #[tauri::command]
 pub async fn verify_model_integrity(
     model_id: String,
     expected_hash: String,
+    algorithm: Option<HashAlgorithm>,
+    parallel: Option<bool>,
     state: State<'_, VerificationState>,
     on_progress: Channel<VerificationProgress>,
-) -> Result<VerificationResult, String> {
-    let model_path = state.models_dir().join(&model_id).join("model.gguf");
+) -> Result<VerificationResult, VerificationError> {
+    let model_path = weights_path(&state.models_dir().join(&model_id));
 
     if !model_path.exists() {
-        return Err(format!("Model file not found: {}", model_path.display()));
+        return Err(VerificationError::file_not_found(&model_path));
     }
 
-    super::verify_integrity_with_progress(&model_path, &expected_hash, Some(&on_progress))
-        .map_err(|e| e.message)
+    run_cancellable(&state, &model_id, move |cancel| {
+        super::verify_integrity_with_progress(
+            &model_path,
+            &expected_hash,
+            algorithm.unwrap_or_default(),
+            parallel.unwrap_or(false),
+            Some(&on_progress),
+            Some(cancel),
+        )
+    })
+    .await
 }
 
-/// Compute checksum of a model file
+/// Compute checksum of a model file (SHA-256 unless `algorithm` says otherwise)
+///
+/// `parallel` hashes on several threads, as for `verify_model_integrity`.
+/// Can be stopped with `cancel_verification`, which fails with kind `cancelled`.
 #[tauri::command]
 pub async fn compute_model_checksum(
     model_id: String,
+    algorithm: Option<HashAlgorithm>,
+    parallel: Option<bool>,
     state: State<'_, VerificationState>,
     on_progress: Channel<VerificationProgress>,
-) -> Result<String, String> {
-    let model_path = state.models_dir().join(&model_id).join("model.gguf");
+) -> Result<String, VerificationError> {
+    let model_path = weights_path(&state.models_dir().join(&model_id));
 
     if !model_path.exists() {
-        return Err(format!("Model file not found: {}", model_path.display()));
+        return Err(VerificationError::file_not_found(&model_path));
     }
 
-    super::compute_checksum_with_progress(&model_path, Some(&on_progress)).map_err(|e| e.message)
+    run_cancellable(&state, &model_id, move |cancel| {
+        super::compute_checksum_with_progress(
+            &model_path,
+            algorithm.unwrap_or_default(),
+            parallel.unwrap_or(false),
+            Some(&on_progress),
+            Some(cancel),
+        )
+    })
+    .await
 }
 
-/// List all quarantined files
+/// Compute checksum of a model's `tokenizer.json` (SHA-256 unless `algorithm` says otherwise)
+///
+/// The tokenizer is small, so no progress is reported.
+#[tauri::command]
+pub async fn compute_tokenizer_checksum(
+    model_id: String,
+    algorithm: Option<HashAlgorithm>,
+    state: State<'_, VerificationState>,
+) -> Result<String, VerificationError> {
+    tokenizer_checksum(
+        &state.models_dir().join(&model_id),
+        algorithm.unwrap_or_default(),
+    )
+}
+
+/// Hash the tokenizer in a model directory. Made public for testing.
+pub fn tokenizer_checksum(
+    model_dir: &Path,
+    algorithm: HashAlgorithm,
+) -> Result<String, VerificationError> {
+    super::compute_checksum(&model_dir.join("tokenizer.json"), algorithm)
+}
+
+/// Stop a running `verify_model_integrity` or `compute_model_checksum`
+///
+/// # Returns
+/// * `true` if a verification was running for the model
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)] // Tauri State is designed to be passed by value
+pub fn cancel_verification(model_id: String, state: State<'_, VerificationState>) -> bool {
+    let cancelled = state.cancel_verification(&model_id);
+    if cancelled {
+        log::info!("Verification cancel requested for {model_id}");
+    }
+    cancelled
+}
+
+/// Run blocking hash work off the async runtime with a registered cancel flag
+async fn run_cancellable<T: Send + 'static>(
+    state: &VerificationState,
+    model_id: &str,
+    work: impl FnOnce(&AtomicBool) -> Result<T, VerificationError> + Send + 'static,
+) -> Result<T, VerificationError> {
+    let cancel = state.begin_verification(model_id)?;
+    let result = tokio::task::spawn_blocking(move || work(&cancel)).await;
+    state.finish_verification(model_id);
+
+    result.map_err(|e| VerificationError {
+        kind: "io_error".to_string(),
+        message: format!("Verification task failed: {e}"),
+    })?
+}
+
+/// Ordering for `list_quarantined_files`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuarantineSort {
+    /// Most recently quarantined first
+    #[default]
+    Newest,
+    /// Biggest files first
+    Largest,
+}
+
+/// List quarantined files, optionally sorted and paged
+///
+/// # Arguments
+/// * `sort_by` - `newest` (default) or `largest`
+/// * `limit` - Maximum number of entries to return (all if omitted)
+/// * `offset` - Entries to skip after sorting
 #[tauri::command]
 pub async fn list_quarantined_files(
     state: State<'_, VerificationState>,
+    sort_by: Option<QuarantineSort>,
+    limit: Option<usize>,
+    offset: Option<usize>,
 ) -> Result<Vec<QuarantinedFile>, String> {
-    let quarantine_dir = state.quarantine_dir();
+    list_quarantine_dir(
+        &state.quarantine_dir(),
+        sort_by.unwrap_or_default(),
+        offset.unwrap_or(0),
+        limit,
+    )
+}
+
+/// Read, sort, then page the quarantine directory
+///
+/// Sorting happens before truncation so pages are stable. Made public for testing.
+pub fn list_quarantine_dir(
+    quarantine_dir: &Path,
+    sort: QuarantineSort,
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<Vec<QuarantinedFile>, String> {
+    let mut files = read_quarantine_dir(quarantine_dir)?;
+    sort_quarantined_files(&mut files, sort);
+
+    Ok(files
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect())
+}
 
+/// Order quarantined files in place; ties fall back to the file ID
+fn sort_quarantined_files(files: &mut [QuarantinedFile], sort: QuarantineSort) {
+    match sort {
+        // Timestamps are zero-padded YYYYMMDD_HHMMSS, so they sort as strings
+        QuarantineSort::Newest => {
+            files.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| a.id.cmp(&b.id)));
+        },
+        QuarantineSort::Largest => files.sort_by(|a, b| {
+            b.file_size_mb
+                .total_cmp(&a.file_size_mb)
+                .then_with(|| a.id.cmp(&b.id))
+        }),
+    }
+}
+
+/// Read every `.corrupted` file in the quarantine directory (unsorted)
+fn read_quarantine_dir(quarantine_dir: &Path) -> Result<Vec<QuarantinedFile>, String> {
     if !quarantine_dir.exists() {
         return Ok(vec![]);
     }
 
     let mut files = Vec::new();
 
-    let entries = std::fs::read_dir(&quarantine_dir)
+    let entries = std::fs::read_dir(quarantine_dir)
         .map_err(|e| format!("Failed to read quarantine directory: {e}"))?;
 
     for entry in entries.flatten() {
@@ -150,6 +338,201 @@ pub async fn delete_quarantined_file(
     Err(format!("Quarantined file not found: {file_id}"))
 }
 
+/// One quarantined download in an exported report (metadata only)
+///
+/// Hashes and URLs come from the quarantine sidecar and are `None` for files
+/// quarantined before sidecars were written.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct QuarantineReportEntry {
+    pub id: String,
+    pub model_id: String,
+    pub timestamp: String,
+    pub file_size_bytes: u64,
+    pub expected_hash: Option<String>,
+    pub actual_hash: Option<String>,
+    pub url: Option<String>,
+    pub tokenizer_url: Option<String>,
+}
+
+/// Report of all quarantined downloads, for attaching to bug reports
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct QuarantineReport {
+    pub generated_at: String,
+    pub entries: Vec<QuarantineReportEntry>,
+}
+
+/// Write a JSON report of all quarantined downloads to the app data dir
+///
+/// Contains metadata only (no file contents). Returns the report's path.
+#[tauri::command]
+pub async fn export_quarantine_report(
+    state: State<'_, VerificationState>,
+) -> Result<String, String> {
+    let report = build_quarantine_report(&state.quarantine_dir())?;
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("Failed to serialize quarantine report: {e}"))?;
+
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+    let path = state
+        .app_data_dir
+        .join(format!("quarantine_report_{timestamp}.json"));
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write quarantine report: {e}"))?;
+
+    log::info!(
+        "Exported quarantine report with {} entries to {}",
+        report.entries.len(),
+        path.display()
+    );
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Collect report entries for every `.corrupted` file in `quarantine_dir`
+///
+/// Made public for testing.
+pub fn build_quarantine_report(quarantine_dir: &Path) -> Result<QuarantineReport, String> {
+    let mut entries = Vec::new();
+    if quarantine_dir.exists() {
+        let dir_entries = std::fs::read_dir(quarantine_dir)
+            .map_err(|e| format!("Failed to read quarantine directory: {e}"))?;
+
+        for path in dir_entries.flatten().map(|entry| entry.path()) {
+            if path.extension().and_then(|s| s.to_str()) != Some("corrupted") {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let Some((model_id, timestamp)) = parse_quarantine_filename(id) else {
+                continue;
+            };
+
+            // Sidecar: {model_id}_{timestamp}.json next to {model_id}_{timestamp}.gguf.corrupted
+            let sidecar =
+                quarantine_dir.join(format!("{}.json", id.strip_suffix(".gguf").unwrap_or(id)));
+            let record: Option<QuarantineRecord> = std::fs::read_to_string(sidecar)
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok());
+
+            entries.push(QuarantineReportEntry {
+                id: id.to_string(),
+                model_id,
+                timestamp,
+                file_size_bytes: std::fs::metadata(&path).map_or(0, |m| m.len()),
+                expected_hash: record.as_ref().map(|r| r.expected_hash.clone()),
+                actual_hash: record.as_ref().map(|r| r.actual_hash.clone()),
+                url: record.as_ref().map(|r| r.url.clone()),
+                tokenizer_url: record.map(|r| r.tokenizer_url),
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(QuarantineReport {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        entries,
+    })
+}
+
+/// Outcome of pruning old quarantined files
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct PruneResult {
+    pub files_removed: u64,
+    pub bytes_freed: u64,
+}
+
+/// Delete quarantined files (and their sidecars) older than `older_than_days`
+///
+/// Only ever touches the quarantine directory, never the models directory.
+#[tauri::command]
+pub async fn prune_quarantine(
+    older_than_days: u64,
+    state: State<'_, VerificationState>,
+) -> Result<PruneResult, String> {
+    let max_age = i64::try_from(older_than_days)
+        .ok()
+        .and_then(chrono::TimeDelta::try_days);
+
+    // A threshold beyond chrono's range can't match any file
+    let Some(cutoff) = max_age.and_then(|age| chrono::Utc::now().checked_sub_signed(age)) else {
+        return Ok(PruneResult::default());
+    };
+
+    prune_quarantine_dir(&state.quarantine_dir(), cutoff)
+}
+
+/// Remove `.corrupted` files quarantined before `cutoff`
+///
+/// Age comes from the timestamp in the filename, falling back to the file's
+/// modification time. Sidecars are files sharing the `{model_id}_{timestamp}.`
+/// prefix. Made public for testing.
+pub fn prune_quarantine_dir(
+    quarantine_dir: &Path,
+    cutoff: chrono::DateTime<chrono::Utc>,
+) -> Result<PruneResult, String> {
+    let mut result = PruneResult::default();
+    if !quarantine_dir.exists() {
+        return Ok(result);
+    }
+
+    // Snapshot entries first so deletions don't disturb the iteration
+    let entries: Vec<_> = std::fs::read_dir(quarantine_dir)
+        .map_err(|e| format!("Failed to read quarantine directory: {e}"))?
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .map(|entry| entry.path())
+        .collect();
+
+    for path in entries
+        .iter()
+        .filter(|p| p.extension().is_some_and(|e| e == "corrupted"))
+    {
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if quarantined_at(path, stem).is_none_or(|at| at >= cutoff) {
+            continue;
+        }
+
+        // {model_id}_{timestamp}.gguf.corrupted and e.g. {model_id}_{timestamp}.json
+        let prefix = format!("{}.", stem.strip_suffix(".gguf").unwrap_or(stem));
+        for victim in entries.iter().filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(&prefix))
+        }) {
+            let size = std::fs::metadata(victim).map_or(0, |m| m.len());
+            match std::fs::remove_file(victim) {
+                Ok(()) => result.bytes_freed += size,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+                Err(e) => return Err(format!("Failed to delete {}: {e}", victim.display())),
+            }
+        }
+        result.files_removed += 1;
+    }
+
+    if result.files_removed > 0 {
+        log::info!(
+            "Pruned {} quarantined files ({} bytes)",
+            result.files_removed,
+            result.bytes_freed
+        );
+    }
+    Ok(result)
+}
+
+/// When a file was quarantined, from its name or else its modification time
+fn quarantined_at(path: &Path, stem: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    parse_quarantine_filename(stem)
+        .and_then(|(_, timestamp)| {
+            chrono::NaiveDateTime::parse_from_str(&timestamp, "%Y%m%d_%H%M%S").ok()
+        })
+        .map(|naive| naive.and_utc())
+        .or_else(|| {
+            let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+            Some(chrono::DateTime::<chrono::Utc>::from(modified))
+        })
+}
+
 /// Parse quarantine filename: {model_id}_{timestamp}.gguf -> (model_id, timestamp)
 /// Made public for testing
 pub fn parse_quarantine_filename(filename: &str) -> Option<(String, String)> {
@@ -1,11 +1,9 @@
 //! Model integrity verification module (Story 2.5)
 //!
-//! Provides SHA-256 checksum computation and verification for downloaded models.
-//! Uses streaming approach for memory-efficient hashing of large files (2-10GB).
+//! Provides SHA-256 (or BLAKE3) checksum computation and verification for
+//! downloaded models. Uses streaming approach for memory-efficient hashing of
+//! large files (2-10GB).
 
-// Large buffers are intentional for I/O performance on multi-GB model files
-#![allow(clippy::large_stack_arrays)]
-#![allow(clippy::large_stack_frames)]
 // Progress percentages don't need full f64 precision
 #![allow(clippy::cast_precision_loss)]
 
@@ -13,10 +11,75 @@ pub mod commands;
 
 use sha2::{Digest, Sha256};
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, Read, Seek};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::ipc::Channel;
 
+/// Hash function used for a checksum
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    /// What most model hosts publish
+    #[default]
+    Sha256,
+    /// Several times faster on large files
+    Blake3,
+}
+
+/// Streaming hasher for either algorithm
+enum FileHasher {
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl FileHasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+            HashAlgorithm::Blake3 => Self::Blake3(Box::default()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Blake3(hasher) => {
+                hasher.update(data);
+            },
+        }
+    }
+
+    /// Like `update`, but BLAKE3 hashes large inputs across all cores
+    fn update_parallel(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Blake3(hasher) => {
+                hasher.update_rayon(data);
+            },
+        }
+    }
+
+    /// Lowercase hex digest
+    fn finalize(self) -> String {
+        match self {
+            Self::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+            Self::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+impl io::Write for FileHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 /// Result of a file integrity verification
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct VerificationResult {
@@ -24,6 +87,8 @@ pub struct VerificationResult {
     pub computed_hash: String,
     pub expected_hash: String,
     pub file_size: u64,
+    /// Algorithm both hashes are in
+    pub algorithm: HashAlgorithm,
 }
 
 /// Error types for verification operations
@@ -55,6 +120,20 @@ impl VerificationError {
         }
     }
 
+    pub fn cancelled(path: &Path) -> Self {
+        Self {
+            kind: "cancelled".to_string(),
+            message: format!("Verification cancelled: {}", path.display()),
+        }
+    }
+
+    pub fn already_running(model_id: &str) -> Self {
+        Self {
+            kind: "already_running".to_string(),
+            message: format!("Verification already running for {model_id}"),
+        }
+    }
+
     pub fn from_io_error(error: &io::Error, path: &Path) -> Self {
         match error.kind() {
             io::ErrorKind::NotFound => Self::file_not_found(path),
@@ -72,36 +151,56 @@ pub struct VerificationProgress {
     pub percentage: f32,
 }
 
-/// Compute SHA-256 checksum of a file using streaming (constant memory)
+/// Compute the checksum of a file using streaming (constant memory)
 ///
 /// # Arguments
 /// * `path` - Path to the file to hash
+/// * `algorithm` - Hash function to use
 ///
 /// # Returns
-/// * `Ok(String)` - Lowercase hex-encoded SHA-256 hash
+/// * `Ok(String)` - Lowercase hex-encoded hash
 /// * `Err(VerificationError)` - Error with clear message
-pub fn compute_checksum(path: &Path) -> Result<String, VerificationError> {
+pub fn compute_checksum(
+    path: &Path,
+    algorithm: HashAlgorithm,
+) -> Result<String, VerificationError> {
     let mut file = File::open(path).map_err(|e| VerificationError::from_io_error(&e, path))?;
 
-    let mut hasher = Sha256::new();
+    let mut hasher = FileHasher::new(algorithm);
     io::copy(&mut file, &mut hasher).map_err(|e| VerificationError::from_io_error(&e, path))?;
 
-    let hash = hasher.finalize();
-    Ok(format!("{hash:x}")) // lowercase hex
+    Ok(hasher.finalize())
 }
 
-/// Compute SHA-256 checksum with progress reporting for large files
+/// Bytes of a file each reader thread hands over at a time in parallel mode
+///
+/// Compare sizes with the ignored `bench_parallel_segment_sizes` test:
+/// smaller segments spend more time on hand-offs, larger ones leave readers
+/// idle at the end of the file.
+const PARALLEL_SEGMENT_BYTES: usize = 4 * 1024 * 1024;
+
+/// Compute the checksum with progress reporting for large files
+///
+/// With `parallel`, several threads read ahead while the hash runs, and
+/// BLAKE3 also hashes each segment across all cores. SHA-256 can't be split,
+/// so it only gains the overlapped reads. The hash is the same either way.
 ///
 /// # Arguments
 /// * `path` - Path to the file to hash
+/// * `algorithm` - Hash function to use
+/// * `parallel` - Read (and for BLAKE3, hash) on multiple threads
 /// * `progress_channel` - Optional channel for progress events
+/// * `cancel` - Optional flag checked between chunks; set it to stop early
 ///
 /// # Returns
-/// * `Ok(String)` - Lowercase hex-encoded SHA-256 hash
-/// * `Err(VerificationError)` - Error with clear message
+/// * `Ok(String)` - Lowercase hex-encoded hash
+/// * `Err(VerificationError)` - Error with clear message (`cancelled` if stopped)
 pub fn compute_checksum_with_progress(
     path: &Path,
+    algorithm: HashAlgorithm,
+    parallel: bool,
     progress_channel: Option<&Channel<VerificationProgress>>,
+    cancel: Option<&AtomicBool>,
 ) -> Result<String, VerificationError> {
     let file = File::open(path).map_err(|e| VerificationError::from_io_error(&e, path))?;
     let total_bytes = file
@@ -109,26 +208,24 @@ pub fn compute_checksum_with_progress(
         .map_err(|e| VerificationError::from_io_error(&e, path))?
         .len();
 
-    let mut reader = io::BufReader::with_capacity(8 * 1024 * 1024, file); // 8MB buffer
-    let mut hasher = Sha256::new();
+    let mut hasher = FileHasher::new(algorithm);
     let mut bytes_processed: u64 = 0;
-    let mut buffer = [0u8; 8 * 1024 * 1024]; // 8MB chunks
 
     // Only report progress for files >500MB
     let should_report_progress = total_bytes > 500 * 1024 * 1024;
     let mut last_percentage: f32 = 0.0;
 
-    loop {
-        let bytes_read = reader
-            .read(&mut buffer)
-            .map_err(|e| VerificationError::from_io_error(&e, path))?;
-
-        if bytes_read == 0 {
-            break;
+    let mut consume = |chunk: &[u8]| {
+        if cancel.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+            return Err(VerificationError::cancelled(path));
         }
 
-        hasher.update(&buffer[..bytes_read]);
-        bytes_processed += bytes_read as u64;
+        if parallel {
+            hasher.update_parallel(chunk);
+        } else {
+            hasher.update(chunk);
+        }
+        bytes_processed += chunk.len() as u64;
 
         // Report progress every 5% for large files
         if should_report_progress {
@@ -144,17 +241,117 @@ pub fn compute_checksum_with_progress(
                 last_percentage = percentage;
             }
         }
+        Ok(())
+    };
+
+    if parallel {
+        read_parallel(path, total_bytes, PARALLEL_SEGMENT_BYTES, &mut consume)?;
+    } else {
+        read_sequential(file, path, &mut consume)?;
+    }
+
+    Ok(hasher.finalize())
+}
+
+/// Read `file` front to back, passing each chunk to `consume`
+fn read_sequential(
+    file: File,
+    path: &Path,
+    consume: &mut impl FnMut(&[u8]) -> Result<(), VerificationError>,
+) -> Result<(), VerificationError> {
+    // 8MB buffer
+    let mut reader = io::BufReader::with_capacity(8 * 1024 * 1024, file);
+    // 8MB chunks, on the heap: worker and blocking-pool threads only have 2MB stacks
+    let mut buffer = vec![0u8; 8 * 1024 * 1024];
+
+    loop {
+        let bytes_read = reader
+            .read(&mut buffer)
+            .map_err(|e| VerificationError::from_io_error(&e, path))?;
+
+        if bytes_read == 0 {
+            return Ok(());
+        }
+        consume(&buffer[..bytes_read])?;
     }
+}
 
-    let hash = hasher.finalize();
-    Ok(format!("{hash:x}"))
+/// Read the first `total_bytes` of `path` on several threads, in order
+///
+/// Reader `i` owns segments `i`, `i + n`, ... and reads one ahead of the
+/// hash, so the disk stays busy while `consume` still sees the bytes in file
+/// order. Returning early (cancel, error) closes the channels, which stops
+/// the readers.
+fn read_parallel(
+    path: &Path,
+    total_bytes: u64,
+    segment_len: usize,
+    consume: &mut impl FnMut(&[u8]) -> Result<(), VerificationError>,
+) -> Result<(), VerificationError> {
+    let readers = std::thread::available_parallelism()
+        .map_or(2, usize::from)
+        .clamp(2, 4);
+    let segment_bytes = segment_len as u64;
+    let segments = total_bytes.div_ceil(segment_bytes);
+
+    std::thread::scope(|scope| {
+        let receivers: Vec<_> = (0..readers)
+            .map(|reader| {
+                let (tx, rx) = std::sync::mpsc::sync_channel(1);
+                scope.spawn(move || {
+                    let mut file = match File::open(path) {
+                        Ok(file) => file,
+                        Err(e) => {
+                            let _ = tx.send(Err(VerificationError::from_io_error(&e, path)));
+                            return;
+                        },
+                    };
+                    for segment in (0..segments).skip(reader).step_by(readers) {
+                        let offset = segment * segment_bytes;
+                        let len = (total_bytes - offset).min(segment_bytes);
+                        let result = read_segment(&mut file, offset, len)
+                            .map_err(|e| VerificationError::from_io_error(&e, path));
+                        let failed = result.is_err();
+                        // A closed channel means hashing already stopped
+                        if tx.send(result).is_err() || failed {
+                            return;
+                        }
+                    }
+                });
+                rx
+            })
+            .collect();
+
+        for (_, rx) in (0..segments).zip(receivers.iter().cycle()) {
+            let chunk = rx.recv().map_err(|_| {
+                VerificationError::io_error(path, &io::Error::other("reader thread stopped"))
+            })??;
+            consume(&chunk)?;
+        }
+        Ok(())
+    })
+}
+
+/// Read exactly `len` bytes at `offset`
+fn read_segment(file: &mut File, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    file.seek(io::SeekFrom::Start(offset))?;
+    let mut buffer = Vec::new();
+    file.take(len).read_to_end(&mut buffer)?;
+    if (buffer.len() as u64) < len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "file shrank while being hashed",
+        ));
+    }
+    Ok(buffer)
 }
 
 /// Verify file integrity against expected hash
 ///
 /// # Arguments
 /// * `path` - Path to the file to verify
-/// * `expected_hash` - Expected SHA-256 hash (hex string)
+/// * `expected_hash` - Expected hash (hex string)
+/// * `algorithm` - Hash function `expected_hash` was made with
 ///
 /// # Returns
 /// * `Ok(VerificationResult)` - Result with verification status and hashes
@@ -162,12 +359,13 @@ pub fn compute_checksum_with_progress(
 pub fn verify_integrity(
     path: &Path,
     expected_hash: &str,
+    algorithm: HashAlgorithm,
 ) -> Result<VerificationResult, VerificationError> {
     let file_size = std::fs::metadata(path)
         .map_err(|e| VerificationError::from_io_error(&e, path))?
         .len();
 
-    let computed_hash = compute_checksum(path)?;
+    let computed_hash = compute_checksum(path, algorithm)?;
     let expected_lower = expected_hash.to_lowercase();
     let verified = computed_hash == expected_lower;
 
@@ -176,20 +374,27 @@ pub fn verify_integrity(
         computed_hash,
         expected_hash: expected_lower,
         file_size,
+        algorithm,
     })
 }
 
-/// Verify file integrity with progress reporting
+/// Verify file integrity with progress reporting and optional cancellation
+///
+/// `parallel` is as for `compute_checksum_with_progress`.
 pub fn verify_integrity_with_progress(
     path: &Path,
     expected_hash: &str,
+    algorithm: HashAlgorithm,
+    parallel: bool,
     progress_channel: Option<&Channel<VerificationProgress>>,
+    cancel: Option<&AtomicBool>,
 ) -> Result<VerificationResult, VerificationError> {
     let file_size = std::fs::metadata(path)
         .map_err(|e| VerificationError::from_io_error(&e, path))?
         .len();
 
-    let computed_hash = compute_checksum_with_progress(path, progress_channel)?;
+    let computed_hash =
+        compute_checksum_with_progress(path, algorithm, parallel, progress_channel, cancel)?;
     let expected_lower = expected_hash.to_lowercase();
     let verified = computed_hash == expected_lower;
 
@@ -198,6 +403,7 @@ pub fn verify_integrity_with_progress(
         computed_hash,
         expected_hash: expected_lower,
         file_size,
+        algorithm,
     })
 }
 
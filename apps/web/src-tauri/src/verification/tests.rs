@@ -20,6 +20,10 @@ const TEST_CONTENT_HASH: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822
 /// SHA-256("") = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
 const EMPTY_CONTENT_HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
 
+/// BLAKE3("abc") and BLAKE3(""), from the BLAKE3 specification
+const BLAKE3_ABC_HASH: &str = "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85";
+const BLAKE3_EMPTY_HASH: &str = "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262";
+
 #[test]
 fn test_compute_checksum_known_vector() {
     // Create a temp file with known content
@@ -28,7 +32,7 @@ fn test_compute_checksum_known_vector() {
         .expect("Failed to write to temp file");
     file.flush().expect("Failed to flush temp file");
 
-    let result = compute_checksum(file.path());
+    let result = compute_checksum(file.path(), HashAlgorithm::Sha256);
     assert!(result.is_ok(), "Checksum computation should succeed");
 
     let hash = result.unwrap();
@@ -43,7 +47,7 @@ fn test_compute_checksum_empty_file() {
     let file = NamedTempFile::new().expect("Failed to create temp file");
     // Don't write anything - empty file
 
-    let result = compute_checksum(file.path());
+    let result = compute_checksum(file.path(), HashAlgorithm::Sha256);
     assert!(result.is_ok(), "Checksum of empty file should succeed");
 
     let hash = result.unwrap();
@@ -55,7 +59,10 @@ fn test_compute_checksum_empty_file() {
 
 #[test]
 fn test_compute_checksum_file_not_found() {
-    let result = compute_checksum(std::path::Path::new("/nonexistent/path/to/file.gguf"));
+    let result = compute_checksum(
+        std::path::Path::new("/nonexistent/path/to/file.gguf"),
+        HashAlgorithm::Sha256,
+    );
 
     assert!(result.is_err(), "Should fail for nonexistent file");
     let error = result.unwrap_err();
@@ -69,7 +76,7 @@ fn test_verify_integrity_pass() {
         .expect("Failed to write to temp file");
     file.flush().expect("Failed to flush temp file");
 
-    let result = verify_integrity(file.path(), TEST_CONTENT_HASH);
+    let result = verify_integrity(file.path(), TEST_CONTENT_HASH, HashAlgorithm::Sha256);
     assert!(result.is_ok(), "Verification should succeed");
 
     let verification = result.unwrap();
@@ -86,7 +93,7 @@ fn test_verify_integrity_fail_mismatch() {
         .expect("Failed to write to temp file");
     file.flush().expect("Failed to flush temp file");
 
-    let result = verify_integrity(file.path(), TEST_CONTENT_HASH);
+    let result = verify_integrity(file.path(), TEST_CONTENT_HASH, HashAlgorithm::Sha256);
     assert!(result.is_ok(), "Verification should complete (not error)");
 
     let verification = result.unwrap();
@@ -106,7 +113,7 @@ fn test_verify_integrity_case_insensitive() {
 
     // Test with uppercase hash
     let uppercase_hash = TEST_CONTENT_HASH.to_uppercase();
-    let result = verify_integrity(file.path(), &uppercase_hash);
+    let result = verify_integrity(file.path(), &uppercase_hash, HashAlgorithm::Sha256);
 
     assert!(result.is_ok(), "Verification should succeed");
     let verification = result.unwrap();
@@ -121,6 +128,7 @@ fn test_verify_integrity_file_not_found() {
     let result = verify_integrity(
         std::path::Path::new("/nonexistent/path/to/file.gguf"),
         TEST_CONTENT_HASH,
+        HashAlgorithm::Sha256,
     );
 
     assert!(result.is_err(), "Should fail for nonexistent file");
@@ -128,6 +136,154 @@ fn test_verify_integrity_file_not_found() {
     assert_eq!(error.kind, "file_not_found");
 }
 
+#[test]
+fn test_compute_checksum_blake3_known_vectors() {
+    let mut file = NamedTempFile::new().expect("Failed to create temp file");
+    assert_eq!(
+        compute_checksum(file.path(), HashAlgorithm::Blake3).unwrap(),
+        BLAKE3_EMPTY_HASH
+    );
+
+    file.write_all(b"abc")
+        .expect("Failed to write to temp file");
+    file.flush().expect("Failed to flush temp file");
+    assert_eq!(
+        compute_checksum(file.path(), HashAlgorithm::Blake3).unwrap(),
+        BLAKE3_ABC_HASH
+    );
+
+    let result = verify_integrity(file.path(), BLAKE3_ABC_HASH, HashAlgorithm::Blake3).unwrap();
+    assert!(result.verified);
+    assert_eq!(result.algorithm, HashAlgorithm::Blake3);
+    // The SHA-256 of the same file is a different hash
+    assert!(
+        !verify_integrity(file.path(), BLAKE3_ABC_HASH, HashAlgorithm::Sha256)
+            .unwrap()
+            .verified
+    );
+}
+
+#[test]
+fn test_streaming_and_progress_hashes_agree_for_each_algorithm() {
+    // Larger than one BLAKE3 chunk and one io::copy buffer
+    let mut file = NamedTempFile::new().expect("Failed to create temp file");
+    let content: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    file.write_all(&content)
+        .expect("Failed to write to temp file");
+    file.flush().expect("Failed to flush temp file");
+
+    for algorithm in [HashAlgorithm::Sha256, HashAlgorithm::Blake3] {
+        assert_eq!(
+            compute_checksum(file.path(), algorithm).unwrap(),
+            compute_checksum_with_progress(file.path(), algorithm, false, None, None).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_parallel_checksum_matches_known_vectors() {
+    let mut file = NamedTempFile::new().expect("Failed to create temp file");
+    let empty =
+        compute_checksum_with_progress(file.path(), HashAlgorithm::Sha256, true, None, None);
+    assert_eq!(empty.unwrap(), EMPTY_CONTENT_HASH);
+
+    file.write_all(TEST_CONTENT.as_bytes())
+        .expect("Failed to write to temp file");
+    file.flush().expect("Failed to flush temp file");
+    let sha = compute_checksum_with_progress(file.path(), HashAlgorithm::Sha256, true, None, None);
+    assert_eq!(sha.unwrap(), TEST_CONTENT_HASH);
+
+    let mut file = NamedTempFile::new().expect("Failed to create temp file");
+    file.write_all(b"abc")
+        .expect("Failed to write to temp file");
+    file.flush().expect("Failed to flush temp file");
+    let blake =
+        compute_checksum_with_progress(file.path(), HashAlgorithm::Blake3, true, None, None);
+    assert_eq!(blake.unwrap(), BLAKE3_ABC_HASH);
+
+    let result = verify_integrity_with_progress(
+        file.path(),
+        BLAKE3_ABC_HASH,
+        HashAlgorithm::Blake3,
+        true,
+        None,
+        None,
+    )
+    .unwrap();
+    assert!(result.verified);
+}
+
+#[test]
+fn test_parallel_reads_deliver_segments_in_order() {
+    // Many small, uneven segments spread over every reader thread
+    let mut file = NamedTempFile::new().expect("Failed to create temp file");
+    let content: Vec<u8> = (0..100_003u32).map(|i| (i % 251) as u8).collect();
+    file.write_all(&content)
+        .expect("Failed to write to temp file");
+    file.flush().expect("Failed to flush temp file");
+
+    let mut read_back = Vec::new();
+    read_parallel(file.path(), content.len() as u64, 997, &mut |chunk| {
+        read_back.extend_from_slice(chunk);
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(read_back, content);
+
+    // Stopping early (as a cancel does) ends the readers without hanging
+    let mut seen = 0;
+    let err = read_parallel(file.path(), content.len() as u64, 997, &mut |_| {
+        seen += 1;
+        if seen == 3 {
+            Err(VerificationError::cancelled(file.path()))
+        } else {
+            Ok(())
+        }
+    })
+    .unwrap_err();
+    assert_eq!(err.kind, "cancelled");
+}
+
+/// Times sequential and parallel hashing of a 1GB file per segment size
+///
+/// Run with `cargo test --release bench_parallel_segment_sizes -- --ignored
+/// --nocapture`. The file is freshly written, so reads come from the page
+/// cache and the numbers show hashing and hand-off cost, not the disk.
+#[test]
+#[ignore = "benchmark; run manually in release mode"]
+fn bench_parallel_segment_sizes() {
+    const FILE_BYTES: u64 = 1024 * 1024 * 1024;
+    let mut file = NamedTempFile::new().expect("Failed to create temp file");
+    let block: Vec<u8> = (0..1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+    for _ in 0..FILE_BYTES / block.len() as u64 {
+        file.write_all(&block)
+            .expect("Failed to write to temp file");
+    }
+    file.flush().expect("Failed to flush temp file");
+
+    for algorithm in [HashAlgorithm::Sha256, HashAlgorithm::Blake3] {
+        let start = std::time::Instant::now();
+        let sequential =
+            compute_checksum_with_progress(file.path(), algorithm, false, None, None).unwrap();
+        println!("{algorithm:?} sequential: {:?}", start.elapsed());
+
+        for segment_mb in [1, 4, 16, 64] {
+            let mut hasher = FileHasher::new(algorithm);
+            let start = std::time::Instant::now();
+            read_parallel(file.path(), FILE_BYTES, segment_mb << 20, &mut |chunk| {
+                hasher.update_parallel(chunk);
+                Ok(())
+            })
+            .unwrap();
+            println!(
+                "{algorithm:?} parallel, {segment_mb}MB segments: {:?}",
+                start.elapsed()
+            );
+            assert_eq!(hasher.finalize(), sequential);
+        }
+    }
+}
+
 #[test]
 fn test_verification_result_serialization() {
     let result = VerificationResult {
@@ -135,11 +291,13 @@ fn test_verification_result_serialization() {
         computed_hash: TEST_CONTENT_HASH.to_string(),
         expected_hash: TEST_CONTENT_HASH.to_string(),
         file_size: 1024,
+        algorithm: HashAlgorithm::Blake3,
     };
 
     let json = serde_json::to_string(&result).expect("Should serialize");
     assert!(json.contains("\"verified\":true"));
     assert!(json.contains(&format!("\"computed_hash\":\"{TEST_CONTENT_HASH}\"")));
+    assert!(json.contains("\"algorithm\":\"blake3\""));
 }
 
 #[test]
@@ -210,6 +368,196 @@ mod quarantine_tests {
     }
 }
 
+#[test]
+fn test_checksum_with_progress_honors_cancel() {
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    temp_file.write_all(b"test").expect("Failed to write");
+
+    let cancel = std::sync::atomic::AtomicBool::new(true);
+    for parallel in [false, true] {
+        let err = compute_checksum_with_progress(
+            temp_file.path(),
+            HashAlgorithm::Sha256,
+            parallel,
+            None,
+            Some(&cancel),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, "cancelled");
+    }
+
+    // Without the flag set the hash completes normally
+    cancel.store(false, std::sync::atomic::Ordering::Relaxed);
+    assert!(compute_checksum_with_progress(
+        temp_file.path(),
+        HashAlgorithm::Sha256,
+        false,
+        None,
+        Some(&cancel)
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_cancel_verification_registry() {
+    let state = commands::VerificationState::new(std::path::PathBuf::from("/tmp"));
+    assert!(!state.cancel_verification("phi-3"));
+
+    let flag = state.begin_verification("phi-3").unwrap();
+    assert!(state.begin_verification("phi-3").is_err());
+    assert!(state.cancel_verification("phi-3"));
+    assert!(flag.load(std::sync::atomic::Ordering::Relaxed));
+
+    state.finish_verification("phi-3");
+    assert!(state.begin_verification("phi-3").is_ok());
+}
+
+#[test]
+fn test_tokenizer_checksum() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let err = commands::tokenizer_checksum(dir.path(), HashAlgorithm::Sha256).unwrap_err();
+    assert_eq!(err.kind, "file_not_found");
+
+    std::fs::write(dir.path().join("tokenizer.json"), b"test").unwrap();
+    assert_eq!(
+        commands::tokenizer_checksum(dir.path(), HashAlgorithm::Sha256).unwrap(),
+        "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+    );
+}
+
+// Quarantine pruning tests
+mod prune_tests {
+    use super::super::commands::{prune_quarantine_dir, PruneResult};
+    use tempfile::TempDir;
+
+    fn cutoff() -> chrono::DateTime<chrono::Utc> {
+        chrono::NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+    }
+
+    #[test]
+    fn test_prune_removes_old_files_and_sidecars() {
+        let dir = TempDir::new().unwrap();
+        let quarantine = dir.path().join("quarantine");
+        std::fs::create_dir_all(&quarantine).unwrap();
+        std::fs::write(
+            quarantine.join("phi-3_20250101_120000.gguf.corrupted"),
+            b"old",
+        )
+        .unwrap();
+        std::fs::write(quarantine.join("phi-3_20250101_120000.json"), b"{}").unwrap();
+        std::fs::write(
+            quarantine.join("phi-3_20251229_103000.gguf.corrupted"),
+            b"new",
+        )
+        .unwrap();
+
+        let result = prune_quarantine_dir(&quarantine, cutoff()).unwrap();
+
+        assert_eq!(
+            result,
+            PruneResult {
+                files_removed: 1,
+                bytes_freed: 5
+            }
+        );
+        assert!(!quarantine.join("phi-3_20250101_120000.json").exists());
+        assert!(quarantine
+            .join("phi-3_20251229_103000.gguf.corrupted")
+            .exists());
+    }
+
+    #[test]
+    fn test_prune_missing_directory_is_noop() {
+        let dir = TempDir::new().unwrap();
+        let result = prune_quarantine_dir(&dir.path().join("quarantine"), cutoff()).unwrap();
+        assert_eq!(result, PruneResult::default());
+    }
+}
+
+// Quarantine listing tests
+mod listing_tests {
+    use super::super::commands::{list_quarantine_dir, QuarantineSort};
+    use tempfile::TempDir;
+
+    fn ids(files: &[super::super::commands::QuarantinedFile]) -> Vec<&str> {
+        files.iter().map(|f| f.model_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_list_sorts_before_paging() {
+        let dir = TempDir::new().unwrap();
+        let mb = |n: usize| vec![0u8; n * 1024 * 1024];
+        std::fs::write(dir.path().join("a_20250101_120000.gguf.corrupted"), mb(3)).unwrap();
+        std::fs::write(dir.path().join("b_20250301_120000.gguf.corrupted"), mb(1)).unwrap();
+        std::fs::write(dir.path().join("c_20250201_120000.gguf.corrupted"), mb(2)).unwrap();
+        std::fs::write(dir.path().join("c_20250201_120000.json"), b"{}").unwrap();
+
+        let newest = list_quarantine_dir(dir.path(), QuarantineSort::Newest, 0, None).unwrap();
+        assert_eq!(ids(&newest), ["b", "c", "a"]);
+
+        let largest = list_quarantine_dir(dir.path(), QuarantineSort::Largest, 0, None).unwrap();
+        assert_eq!(ids(&largest), ["a", "c", "b"]);
+
+        let page = list_quarantine_dir(dir.path(), QuarantineSort::Largest, 1, Some(1)).unwrap();
+        assert_eq!(ids(&page), ["c"]);
+
+        let past_end =
+            list_quarantine_dir(dir.path(), QuarantineSort::Newest, 5, Some(10)).unwrap();
+        assert!(past_end.is_empty());
+    }
+}
+
+// Quarantine report tests
+mod report_tests {
+    use super::super::commands::build_quarantine_report;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_report_includes_sidecar_metadata_without_contents() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("phi-3_20250101_120000.gguf.corrupted"),
+            b"corrupted weights",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("phi-3_20250101_120000.json"),
+            r#"{"model_id":"phi-3","url":"https://example.com/phi-3.gguf",
+                "tokenizer_url":"https://example.com/tokenizer.json",
+                "expected_hash":"aaa","actual_hash":"bbb"}"#,
+        )
+        .unwrap();
+        // Quarantined before sidecars existed
+        std::fs::write(
+            dir.path().join("llama_20240101_120000.gguf.corrupted"),
+            b"x",
+        )
+        .unwrap();
+
+        let report = build_quarantine_report(dir.path()).unwrap();
+
+        assert_eq!(report.entries.len(), 2);
+        let legacy = &report.entries[0];
+        assert_eq!(legacy.model_id, "llama");
+        assert_eq!(legacy.expected_hash, None);
+
+        let entry = &report.entries[1];
+        assert_eq!(entry.model_id, "phi-3");
+        assert_eq!(entry.timestamp, "20250101_120000");
+        assert_eq!(entry.file_size_bytes, 17);
+        assert_eq!(entry.expected_hash.as_deref(), Some("aaa"));
+        assert_eq!(entry.actual_hash.as_deref(), Some("bbb"));
+        assert_eq!(entry.url.as_deref(), Some("https://example.com/phi-3.gguf"));
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(!json.contains("corrupted weights"));
+    }
+}
+
 // Integration test for full verification flow
 mod integration_tests {
     use super::*;
@@ -230,14 +578,16 @@ mod integration_tests {
         file.sync_all().expect("Failed to sync file");
 
         // 2. Compute checksum
-        let checksum = compute_checksum(&model_path).expect("Checksum computation failed");
+        let checksum = compute_checksum(&model_path, HashAlgorithm::Sha256)
+            .expect("Checksum computation failed");
 
         // Verify checksum is 64 hex characters (SHA-256)
         assert_eq!(checksum.len(), 64);
         assert!(checksum.chars().all(|c| c.is_ascii_hexdigit()));
 
         // 3. Verify with correct hash
-        let result = verify_integrity(&model_path, &checksum).expect("Verification failed");
+        let result = verify_integrity(&model_path, &checksum, HashAlgorithm::Sha256)
+            .expect("Verification failed");
         assert!(
             result.verified,
             "Verification should pass with correct hash"
@@ -246,8 +596,8 @@ mod integration_tests {
 
         // 4. Verify with incorrect hash
         let wrong_hash = "0".repeat(64);
-        let result =
-            verify_integrity(&model_path, &wrong_hash).expect("Verification should complete");
+        let result = verify_integrity(&model_path, &wrong_hash, HashAlgorithm::Sha256)
+            .expect("Verification should complete");
         assert!(!result.verified, "Verification should fail with wrong hash");
         assert_ne!(result.computed_hash, result.expected_hash);
 
@@ -268,10 +618,12 @@ mod integration_tests {
         std::fs::write(&model_path, &content).expect("Failed to write file");
 
         // Compute checksum
-        let checksum = compute_checksum(&model_path).expect("Checksum failed");
+        let checksum =
+            compute_checksum(&model_path, HashAlgorithm::Sha256).expect("Checksum failed");
 
         // Verify without progress (should work)
-        let result = verify_integrity(&model_path, &checksum).expect("Verification failed");
+        let result = verify_integrity(&model_path, &checksum, HashAlgorithm::Sha256)
+            .expect("Verification failed");
         assert!(result.verified);
         assert_eq!(result.file_size, size as u64);
     }
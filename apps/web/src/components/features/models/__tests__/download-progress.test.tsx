@@ -128,6 +128,13 @@ describe("DownloadProgress", () => {
       expect(screen.getByText(REMAINING_PATTERN)).toBeInTheDocument();
     });
 
+    it("should warn when a download has stalled", () => {
+      const progress = createProgress({ stalled: true, speedBps: 0 });
+      render(<DownloadProgress progress={progress} />);
+
+      expect(screen.getByText(/Stalled/)).toBeInTheDocument();
+    });
+
     it("should display 'Paused' when status is paused", () => {
       const progress = createProgress({ status: "paused" });
       render(<DownloadProgress progress={progress} />);
@@ -188,6 +195,23 @@ describe("DownloadProgress", () => {
       const progressBar = screen.getByRole("progressbar");
       expect(progressBar).toHaveAttribute("aria-valuenow", "0");
     });
+
+    it("should show an indeterminate bar when the size is unknown", () => {
+      const progress = createProgress({
+        bytesDownloaded: 5 * 1024 * 1024,
+        totalBytes: 0,
+        etaSeconds: 0,
+      });
+      render(<DownloadProgress progress={progress} />);
+
+      const progressBar = screen.getByRole("progressbar");
+      expect(progressBar).not.toHaveAttribute("aria-valuenow");
+      expect(progressBar).toHaveAttribute(
+        "aria-label",
+        "Downloading phi-3-mini"
+      );
+      expect(screen.getByText(/unknown/)).toBeInTheDocument();
+    });
   });
 
   describe("CVA Variants", () => {
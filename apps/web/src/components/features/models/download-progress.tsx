@@ -98,7 +98,14 @@ export function DownloadProgress({
 }: DownloadProgressProps) {
   const percent = calculateProgressPercent(progress);
   const displayName = modelName ?? progress.modelId;
-  const ariaLabel = getAriaLabel(progress.status, displayName, percent);
+  // Data is arriving but the server never said how much to expect
+  const indeterminate =
+    progress.status === "downloading" &&
+    progress.totalBytes === 0 &&
+    progress.bytesDownloaded > 0;
+  const ariaLabel = indeterminate
+    ? `Downloading ${displayName}`
+    : getAriaLabel(progress.status, displayName, percent);
 
   return (
     <div className={cn("space-y-1", className)} data-slot="download-progress">
@@ -109,10 +116,13 @@ export function DownloadProgress({
           aria-label={ariaLabel}
           aria-valuemax={100}
           aria-valuemin={0}
-          aria-valuenow={percent}
-          className={cn(progressVariants({ status: progress.status }))}
+          aria-valuenow={indeterminate ? undefined : percent}
+          className={cn(
+            progressVariants({ status: progress.status }),
+            indeterminate && "animate-pulse"
+          )}
           role="progressbar"
-          style={{ width: `${percent}%` }}
+          style={{ width: indeterminate ? "100%" : `${percent}%` }}
         />
       </div>
 
@@ -139,7 +149,7 @@ function ProgressDetails({ progress }: ProgressDetailsProps) {
       {/* Bytes downloaded / total */}
       <span>
         {formatBytes(progress.bytesDownloaded)} /{" "}
-        {formatBytes(progress.totalBytes)}
+        {progress.totalBytes > 0 ? formatBytes(progress.totalBytes) : "unknown"}
       </span>
 
       {/* Speed and ETA */}
@@ -158,6 +168,14 @@ interface StatusIndicatorProps {
 function StatusIndicator({ progress }: StatusIndicatorProps) {
   const { status, speedBps, etaSeconds } = progress;
 
+  if (status === "downloading" && progress.stalled) {
+    return (
+      <span className="text-yellow-600 dark:text-yellow-400">
+        Stalled - no data received
+      </span>
+    );
+  }
+
   if (status === "downloading") {
     return (
       <div className="flex items-center gap-2">
@@ -127,7 +127,8 @@ export function ModelDownloadButton({
       downloadUrl,
       model.tokenizerUrl,
       model.requirements.storageMb,
-      model.sha256
+      model.sha256,
+      model.tokenizerSha256
     );
   };
 
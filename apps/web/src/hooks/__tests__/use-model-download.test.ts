@@ -16,6 +16,20 @@ const mockStartModelDownload = vi.fn();
 vi.mock("@continuum/platform", () => ({
   checkStorageSpace: (...args: unknown[]) => mockCheckStorageSpace(...args),
   startModelDownload: (...args: unknown[]) => mockStartModelDownload(...args),
+  DownloadStartError: class extends Error {
+    readonly offline: boolean;
+    readonly tokenizerCorrupted: boolean;
+
+    constructor(
+      message: string,
+      offline: boolean,
+      tokenizerCorrupted: boolean
+    ) {
+      super(message);
+      this.offline = offline;
+      this.tokenizerCorrupted = tokenizerCorrupted;
+    }
+  },
 }));
 
 // Mock download store
@@ -31,6 +45,7 @@ vi.mock("@/stores/downloads", () => ({
 }));
 
 // Import after mocks are set up
+import { DownloadStartError } from "@continuum/platform";
 import { useModelDownload } from "../use-model-download";
 
 describe("useModelDownload", () => {
@@ -154,7 +169,9 @@ describe("useModelDownload", () => {
         "phi-3-mini",
         "https://example.com/model.gguf",
         "https://example.com/tokenizer.json",
-        undefined // sha256 is optional
+        undefined, // sha256 is optional
+        undefined,
+        undefined
       );
     });
   });
@@ -240,6 +257,34 @@ describe("useModelDownload", () => {
         expect(result.current.state.error).toBe("Network error");
       }
     });
+
+    it("should ask for a re-download when the tokenizer is corrupted", async () => {
+      mockCheckStorageSpace.mockResolvedValue({
+        hasSpace: true,
+        availableMb: 10_000,
+        requiredMb: 4000,
+        shortfallMb: 0,
+      });
+      mockStartModelDownload.mockRejectedValue(
+        new DownloadStartError("Tokenizer checksum mismatch", false, true)
+      );
+
+      const { result } = renderHook(() => useModelDownload());
+
+      await act(async () => {
+        await result.current.initiateDownload(
+          "phi-3-mini",
+          "https://example.com/model.gguf",
+          "https://example.com/tokenizer.json",
+          4000
+        );
+      });
+
+      expect(result.current.state.status).toBe("error");
+      if (result.current.state.status === "error") {
+        expect(result.current.state.error).toContain("re-download");
+      }
+    });
   });
 
   describe("Retry and Dismiss", () => {
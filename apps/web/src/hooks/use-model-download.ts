@@ -11,7 +11,11 @@
  */
 
 import type { StorageCheckResult } from "@continuum/inference";
-import { checkStorageSpace, startModelDownload } from "@continuum/platform";
+import {
+  checkStorageSpace,
+  DownloadStartError,
+  startModelDownload,
+} from "@continuum/platform";
 import { useCallback, useState } from "react";
 import { useDownloadStore } from "@/stores/downloads";
 
@@ -32,7 +36,8 @@ interface UseModelDownloadResult {
     url: string,
     tokenizerUrl: string,
     requiredMb: number,
-    sha256?: string
+    sha256?: string,
+    tokenizerSha256?: string
   ) => Promise<void>;
   /** Retry after storage warning */
   retryDownload: () => Promise<void>;
@@ -57,6 +62,7 @@ export function useModelDownload(): UseModelDownloadResult {
     tokenizerUrl: string;
     requiredMb: number;
     sha256?: string;
+    tokenizerSha256?: string;
   } | null>(null);
 
   const addDownload = useDownloadStore((s) => s.addDownload);
@@ -67,10 +73,18 @@ export function useModelDownload(): UseModelDownloadResult {
       url: string,
       tokenizerUrl: string,
       requiredMb: number,
-      sha256?: string
+      sha256?: string,
+      tokenizerSha256?: string
     ) => {
       // Save for potential retry
-      setPendingDownload({ modelId, url, tokenizerUrl, requiredMb, sha256 });
+      setPendingDownload({
+        modelId,
+        url,
+        tokenizerUrl,
+        requiredMb,
+        sha256,
+        tokenizerSha256,
+      });
 
       // Check storage first
       setState({ status: "checking-storage" });
@@ -91,7 +105,9 @@ export function useModelDownload(): UseModelDownloadResult {
           modelId,
           url,
           tokenizerUrl,
-          sha256
+          sha256,
+          undefined,
+          tokenizerSha256
         );
 
         // Add to store
@@ -108,8 +124,11 @@ export function useModelDownload(): UseModelDownloadResult {
 
         setState({ status: "downloading", downloadId });
       } catch (error) {
-        const message =
-          error instanceof Error ? error.message : "Download failed";
+        let message = error instanceof Error ? error.message : "Download failed";
+        if (error instanceof DownloadStartError && error.tokenizerCorrupted) {
+          // The bad tokenizer.json was deleted, so a retry fetches it again
+          message = "The tokenizer file was corrupted. Retry to re-download it.";
+        }
         setState({ status: "error", error: message });
       }
     },
@@ -123,7 +142,8 @@ export function useModelDownload(): UseModelDownloadResult {
         pendingDownload.url,
         pendingDownload.tokenizerUrl,
         pendingDownload.requiredMb,
-        pendingDownload.sha256
+        pendingDownload.sha256,
+        pendingDownload.tokenizerSha256
       );
     }
   }, [pendingDownload, initiateDownload]);
@@ -179,5 +179,36 @@ describe("KalosmAdapter", () => {
         maxTokens: undefined,
       });
     });
+
+    it("should pass stop sequences as generation params", async () => {
+      mockListen.mockImplementation(
+        async (
+          eventName: string,
+          callback: (event: { payload: unknown }) => void
+        ) => {
+          if (eventName === "inference:complete") {
+            setTimeout(() => callback({ payload: null }), 5);
+          }
+          return vi.fn();
+        }
+      );
+      mockInvoke.mockResolvedValueOnce(undefined);
+
+      const { KalosmAdapter } = await import("../adapters/kalosm");
+      const adapter = new KalosmAdapter();
+
+      for await (const _ of adapter.generate({
+        prompt: "Hello",
+        stopSequences: ["\n\nUser:"],
+      })) {
+        // consume
+      }
+
+      expect(mockInvoke).toHaveBeenCalledWith("generate", {
+        prompt: "Hello",
+        maxTokens: undefined,
+        params: { stop_sequences: ["\n\nUser:"] },
+      });
+    });
   });
 });
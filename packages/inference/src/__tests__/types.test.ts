@@ -83,6 +83,11 @@ describe("InferenceError", () => {
       "INFERENCE_TIMEOUT",
       "GENERATION_ABORTED",
       "MODEL_LOAD_FAILED",
+      "INVALID_PARAMETERS",
+      "CONTEXT_OVERFLOW",
+      "INTEGRITY_FAILED",
+      "BUSY",
+      "EMBEDDINGS_UNSUPPORTED",
       "UNKNOWN_ERROR",
     ];
     for (const code of codes) {
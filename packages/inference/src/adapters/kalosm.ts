@@ -73,6 +73,9 @@ export class KalosmAdapter implements InferenceAdapter {
     invoke("generate", {
       prompt: request.prompt,
       maxTokens: request.maxTokens,
+      params: request.stopSequences?.length
+        ? { stop_sequences: request.stopSequences }
+        : undefined,
     }).catch((e) => {
       error = e instanceof Error ? e : new Error(String(e));
       isComplete = true;
@@ -15,12 +15,17 @@
 export type DownloadStatus =
   | "queued"
   | "downloading"
+  | "retrying" // Network error; waiting to resume the transfer
+  | "restarting" // Server ignored the Range request; starting over from 0
+  | "switching_mirror" // Moving to the next fallback URL, keeping progress
+  | "rehashing" // Re-reading a resumed .part to continue its hash
   | "paused"
   | "verifying" // Story 2.5: hash verification in progress
   | "verified" // Story 2.5: hash verification succeeded
   | "corrupted" // Story 2.5: hash verification failed
   | "completed"
   | "failed"
+  | "offline" // Connectivity lost mid-download; resumable from the .part file
   | "cancelled";
 
 /**
@@ -60,12 +65,22 @@ export interface DownloadProgress {
   status: DownloadStatus;
   /** Bytes downloaded so far */
   bytesDownloaded: number;
-  /** Total file size in bytes */
+  /** Total file size in bytes (0 when the server doesn't report it) */
   totalBytes: number;
   /** Current download speed in bytes per second */
   speedBps: number;
-  /** Estimated time remaining in seconds */
+  /** Speed over the last few seconds (responsive, but jumpy) */
+  instantSpeedBps?: number;
+  /** Speed since the download (re)started (stable) */
+  averageSpeedBps?: number;
+  /** Estimated time remaining in seconds (download phase only) */
   etaSeconds: number;
+  /** Progress of a non-download phase like 'verifying' or 'rehashing' (0-100) */
+  phasePercent?: number;
+  /** Pending retry number while status is 'retrying' (1 = first) */
+  retryAttempt?: number;
+  /** No data has arrived for 30s; the connection may be dead */
+  stalled?: boolean;
   /** Timestamp when download started */
   startedAt: Date;
   /** Error info if status is 'failed' */
@@ -85,6 +100,11 @@ export interface StorageCheckResult {
   requiredMb: number;
   /** Shortfall in MB (0 if hasSpace is true) */
   shortfallMb: number;
+  /**
+   * False when the models disk couldn't be identified and availableMb is the
+   * total across all disks, which may overstate it. Unset on web.
+   */
+  diskResolved?: boolean;
 }
 
 /**
@@ -49,6 +49,7 @@ export type {
   InferenceStatus,
   InferenceToken,
   // Story 2.5: Verification types
+  HashAlgorithm,
   QuarantinedFile,
   VerificationInfo,
   VerificationResult,
@@ -96,4 +96,6 @@ export interface ModelMetadata {
    * Required because GGUF files often don't embed tokenizers in a format Kalosm can use.
    */
   tokenizerUrl: string;
+  /** Expected SHA-256 hash of tokenizer.json. Optional - if not provided, verification is skipped. */
+  tokenizerSha256?: string;
 }
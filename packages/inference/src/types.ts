@@ -40,6 +40,11 @@ export type InferenceErrorCode =
   | "INFERENCE_TIMEOUT"
   | "GENERATION_ABORTED"
   | "MODEL_LOAD_FAILED"
+  | "INVALID_PARAMETERS"
+  | "CONTEXT_OVERFLOW"
+  | "INTEGRITY_FAILED"
+  | "BUSY"
+  | "EMBEDDINGS_UNSUPPORTED"
   | "UNKNOWN_ERROR";
 
 /**
@@ -149,6 +154,29 @@ export const INFERENCE_ERROR_MESSAGES: Record<
     recoveryHint:
       "Check if the model file is corrupted and re-download if needed.",
   },
+  INVALID_PARAMETERS: {
+    userMessage: "Invalid generation settings. Please adjust them and try again.",
+    recoveryHint: "Mirostat can't be combined with top-p or top-k sampling.",
+  },
+  CONTEXT_OVERFLOW: {
+    userMessage:
+      "This conversation is too long for the model. Shorten it and try again.",
+    recoveryHint:
+      "Start a new conversation or lower the maximum response length.",
+  },
+  INTEGRITY_FAILED: {
+    userMessage: "The model files are damaged. Please re-download the model.",
+    recoveryHint: "Delete the model in Models > Models and download it again.",
+  },
+  BUSY: {
+    userMessage:
+      "Another response is still being generated. Please wait for it to finish.",
+    recoveryHint: "Stop the current response or try again once it's done.",
+  },
+  EMBEDDINGS_UNSUPPORTED: {
+    userMessage: "The loaded model can't produce embeddings.",
+    recoveryHint: "Load a model with embedding support to use this feature.",
+  },
   UNKNOWN_ERROR: {
     userMessage: "Something went wrong. Please try again.",
     recoveryHint: "If this persists, check the logs for more details.",
@@ -174,12 +202,16 @@ export function createInferenceError(
 // Story 2.5: Model Integrity Verification Types
 // ============================================================================
 
+/** Hash function used for a checksum (SHA-256 unless a host publishes BLAKE3) */
+export type HashAlgorithm = "sha256" | "blake3";
+
 /** Result from Rust verification command */
 export interface VerificationResult {
   verified: boolean;
   computedHash: string;
   expectedHash: string;
   fileSize: number;
+  algorithm: HashAlgorithm;
 }
 
 /** Verification status stored in model store */
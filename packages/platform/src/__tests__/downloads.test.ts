@@ -25,6 +25,7 @@ import { isDesktop } from "../capabilities";
 import {
   cancelModelDownload,
   checkStorageSpace,
+  DownloadStartError,
   deleteModel,
   getModelPath,
   isOnline,
@@ -87,6 +88,68 @@ describe("Download Adapter", () => {
       expect(result).toBe("download-456");
     });
 
+    it("should pass auth headers for gated downloads", async () => {
+      mockInvoke.mockResolvedValue("download-789");
+
+      await startModelDownload(
+        "llama-3-8b",
+        "https://example.com/model.gguf",
+        "https://example.com/tokenizer.json",
+        undefined,
+        { Authorization: "Bearer hf_token" }
+      );
+
+      expect(mockInvoke).toHaveBeenCalledWith("start_download", {
+        modelId: "llama-3-8b",
+        url: "https://example.com/model.gguf",
+        tokenizerUrl: "https://example.com/tokenizer.json",
+        expectedHash: null,
+        headers: { Authorization: "Bearer hf_token" },
+      });
+    });
+
+    it("should pass tokenizerHash when provided", async () => {
+      mockInvoke.mockResolvedValue("download-321");
+
+      await startModelDownload(
+        "phi-3-mini",
+        "https://example.com/model.gguf",
+        "https://example.com/tokenizer.json",
+        "abc123def456",
+        undefined,
+        "fed654cba321"
+      );
+
+      expect(mockInvoke).toHaveBeenCalledWith("start_download", {
+        modelId: "phi-3-mini",
+        url: "https://example.com/model.gguf",
+        tokenizerUrl: "https://example.com/tokenizer.json",
+        expectedHash: "abc123def456",
+        tokenizerHash: "fed654cba321",
+      });
+    });
+
+    it("should keep the tokenizer_corrupted flag on failure", async () => {
+      mockInvoke.mockRejectedValue({
+        offline: false,
+        tokenizer_corrupted: true,
+        message: "Tokenizer checksum mismatch",
+      });
+
+      const error = await startModelDownload(
+        "phi-3-mini",
+        "https://example.com/model.gguf",
+        "https://example.com/tokenizer.json"
+      ).catch((e: unknown) => e);
+
+      expect(error).toBeInstanceOf(DownloadStartError);
+      expect(error).toMatchObject({
+        message: "Tokenizer checksum mismatch",
+        offline: false,
+        tokenizerCorrupted: true,
+      });
+    });
+
     it("should throw error on non-desktop platform", async () => {
       mockIsDesktop.mockReturnValue(false);
 
@@ -151,6 +214,7 @@ describe("Download Adapter", () => {
         available_mb: 100_000,
         required_mb: 4000,
         shortfall_mb: 0,
+        disk_resolved: false,
       });
 
       const result = await checkStorageSpace(4000);
@@ -163,6 +227,7 @@ describe("Download Adapter", () => {
         availableMb: 100_000,
         requiredMb: 4000,
         shortfallMb: 0,
+        diskResolved: false,
       });
     });
 
@@ -225,6 +290,18 @@ describe("Download Adapter", () => {
 
       expect(mockInvoke).toHaveBeenCalledWith("delete_model", {
         modelId: "phi-3-mini",
+        force: false,
+      });
+    });
+
+    it("should pass force to unload a loaded model", async () => {
+      mockInvoke.mockResolvedValue(undefined);
+
+      await deleteModel("phi-3-mini", true);
+
+      expect(mockInvoke).toHaveBeenCalledWith("delete_model", {
+        modelId: "phi-3-mini",
+        force: true,
       });
     });
   });
@@ -505,6 +505,47 @@ describe("Hardware Capability Detection", () => {
       expect(capabilities.gpu?.computeCapable).toBe(true);
     });
 
+    it("should report used and free VRAM when available", async () => {
+      mockInvokeResponses.set("get_system_info", {
+        ram_mb: 32_768,
+        cpu_cores: 16,
+        storage_available_mb: 500_000,
+      });
+      mockInvokeResponses.set("get_gpu_info", {
+        name: "NVIDIA RTX 4090",
+        vram_mb: 24_576,
+        compute_capable: true,
+        vendor: "nvidia",
+        vram_used_mb: 6144,
+        vram_free_mb: 18_432,
+      });
+
+      const capabilities = await getHardwareCapabilities();
+
+      expect(capabilities.gpu?.vramUsed).toBe(6144);
+      expect(capabilities.gpu?.vramFree).toBe(18_432);
+    });
+
+    it("should report Apple Silicon GPUs with their vendor", async () => {
+      mockInvokeResponses.set("get_system_info", {
+        ram_mb: 32_768,
+        cpu_cores: 12,
+        storage_available_mb: 500_000,
+      });
+      mockInvokeResponses.set("get_gpu_info", {
+        name: "Apple M2 Pro",
+        vram_mb: 32_768,
+        compute_capable: true,
+        vendor: "apple",
+      });
+
+      const capabilities = await getHardwareCapabilities();
+
+      expect(capabilities.gpu?.name).toBe("Apple M2 Pro");
+      expect(capabilities.gpu?.vendor).toBe("apple");
+      expect(capabilities.gpu?.vram).toBe(32_768);
+    });
+
     it("should handle no GPU gracefully (AC2 fallback)", async () => {
       // Arrange - nvidia-smi not available or no GPU
       mockInvokeResponses.set("get_system_info", {
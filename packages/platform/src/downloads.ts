@@ -84,7 +84,37 @@ interface TauriDownloadProgressEvent {
   bytes_downloaded: number;
   total_bytes: number;
   speed_bps: number;
+  instant_speed_bps: number;
+  average_speed_bps: number;
   eta_seconds: number;
+  phase_percent: number | null;
+  retry_attempt: number | null;
+  stalled: boolean;
+}
+
+/** Tauri error payload from start_download (offline = host unreachable) */
+interface TauriNetworkError {
+  offline: boolean;
+  /** tokenizer.json failed its expected hash and was deleted */
+  tokenizer_corrupted: boolean;
+  message: string;
+}
+
+/**
+ * Error thrown when start_download fails.
+ * `tokenizerCorrupted` means tokenizer.json failed its hash and was deleted,
+ * so retrying the download fetches a fresh copy.
+ */
+export class DownloadStartError extends Error {
+  readonly offline: boolean;
+  readonly tokenizerCorrupted: boolean;
+
+  constructor(message: string, offline: boolean, tokenizerCorrupted: boolean) {
+    super(message);
+    this.name = "DownloadStartError";
+    this.offline = offline;
+    this.tokenizerCorrupted = tokenizerCorrupted;
+  }
 }
 
 /** Tauri storage check result */
@@ -93,6 +123,7 @@ interface TauriStorageCheckResult {
   available_mb: number;
   required_mb: number;
   shortfall_mb: number;
+  disk_resolved: boolean;
 }
 
 // ============================================================================
@@ -106,27 +137,44 @@ interface TauriStorageCheckResult {
  * @param url - The download URL for the GGUF model
  * @param tokenizerUrl - The download URL for the tokenizer.json
  * @param expectedHash - Optional SHA-256 hash for integrity verification (Story 2.5)
+ * @param headers - Optional extra HTTP headers, e.g. `Authorization: Bearer <token>`
+ *   for gated or private repositories
+ * @param tokenizerHash - Optional SHA-256 hash of tokenizer.json
  * @returns Promise<string> - The download ID for tracking
- * @throws Error if not on desktop or if download fails to start
+ * @throws DownloadStartError if the download fails to start
+ *   (`offline` / `tokenizerCorrupted` say why)
+ * @throws Error if not on desktop
  */
-// biome-ignore lint/suspicious/useAwait: Returns awaited promise from Tauri invoke
 export async function startModelDownload(
   modelId: string,
   url: string,
   tokenizerUrl: string,
-  expectedHash?: string
+  expectedHash?: string,
+  headers?: Record<string, string>,
+  tokenizerHash?: string
 ): Promise<string> {
   if (!isDesktop()) {
     throw new Error("Model downloads are only supported on desktop");
   }
 
   const invoke = getTauriInvoke();
-  return invoke<string>("start_download", {
-    modelId,
-    url,
-    tokenizerUrl,
-    expectedHash: expectedHash ?? null,
-  });
+  try {
+    return await invoke<string>("start_download", {
+      modelId,
+      url,
+      tokenizerUrl,
+      expectedHash: expectedHash ?? null,
+      ...(headers && { headers }),
+      ...(tokenizerHash && { tokenizerHash }),
+    });
+  } catch (error) {
+    const networkError = error as Partial<TauriNetworkError>;
+    throw new DownloadStartError(
+      networkError.message ?? String(error),
+      networkError.offline ?? false,
+      networkError.tokenizer_corrupted ?? false
+    );
+  }
 }
 
 /**
@@ -201,6 +249,7 @@ export async function checkStorageSpace(
     availableMb: result.available_mb,
     requiredMb: result.required_mb,
     shortfallMb: result.shortfall_mb,
+    diskResolved: result.disk_resolved,
   };
 }
 
@@ -220,6 +269,122 @@ export async function getModelPath(modelId: string): Promise<string | null> {
   return invoke<string | null>("get_model_path", { modelId });
 }
 
+/** A model directory on disk, finished or not */
+export interface InstalledModel {
+  modelId: string;
+  sizeBytes: number;
+  hasTokenizer: boolean;
+  /** False while only a partial download exists */
+  complete: boolean;
+  /** Last modification, in milliseconds since the epoch */
+  modifiedMs: number | null;
+}
+
+/** Tauri list_downloaded_models entry */
+interface TauriInstalledModel {
+  model_id: string;
+  size_bytes: number;
+  has_tokenizer: boolean;
+  complete: boolean;
+  modified_ms: number | null;
+}
+
+/**
+ * List every model in the models directory, including unfinished downloads.
+ * Backs the "Manage Models" screen.
+ *
+ * @returns Promise<InstalledModel[]> - Models sorted by id (empty on web)
+ */
+export async function listDownloadedModels(): Promise<InstalledModel[]> {
+  if (!isDesktop()) {
+    return [];
+  }
+
+  const invoke = getTauriInvoke();
+  const models = await invoke<TauriInstalledModel[]>(
+    "list_downloaded_models"
+  );
+  return models.map((model) => ({
+    modelId: model.model_id,
+    sizeBytes: model.size_bytes,
+    hasTokenizer: model.has_tokenizer,
+    complete: model.complete,
+    modifiedMs: model.modified_ms,
+  }));
+}
+
+/** Disk space used by downloaded models */
+export interface ModelsStorageUsage {
+  /** Everything in the models directory, partial downloads included */
+  totalBytes: number;
+  perModel: Array<{ modelId: string; bytes: number }>;
+  /** Share of totalBytes held by unfinished downloads */
+  partialBytes: number;
+  /** Quarantined files, not included in totalBytes */
+  quarantineBytes: number;
+}
+
+/** Tauri get_models_storage_usage result */
+interface TauriStorageUsage {
+  total_bytes: number;
+  per_model: Array<[string, number]>;
+  partial_bytes: number;
+  quarantine_bytes: number;
+}
+
+/**
+ * Get how much disk space downloaded models use.
+ * Partial and quarantined bytes are reported separately as reclaimable space.
+ *
+ * @returns Promise<ModelsStorageUsage | null> - Usage, or null on web
+ */
+export async function getModelsStorageUsage(): Promise<ModelsStorageUsage | null> {
+  if (!isDesktop()) {
+    return null;
+  }
+
+  const invoke = getTauriInvoke();
+  const usage = await invoke<TauriStorageUsage>("get_models_storage_usage");
+  return {
+    totalBytes: usage.total_bytes,
+    perModel: usage.per_model.map(([modelId, bytes]) => ({ modelId, bytes })),
+    partialBytes: usage.partial_bytes,
+    quarantineBytes: usage.quarantine_bytes,
+  };
+}
+
+/** A model directory left behind by a cancelled or crashed download */
+export interface OrphanedDownload {
+  modelId: string;
+  files: string[];
+  bytes: number;
+}
+
+/**
+ * Find leftovers of cancelled or crashed downloads, deleting them only when
+ * `confirm` is set. Quarantined files are never touched.
+ *
+ * @param confirm - Actually delete; otherwise a dry run
+ * @returns Promise<OrphanedDownload[]> - What was (or would be) removed
+ */
+export async function cleanupOrphanedDownloads(
+  confirm = false
+): Promise<OrphanedDownload[]> {
+  if (!isDesktop()) {
+    return [];
+  }
+
+  const invoke = getTauriInvoke();
+  const orphans = await invoke<
+    Array<{ model_id: string; files: string[]; bytes: number }>
+  >("cleanup_orphaned_downloads", { confirm });
+  return orphans.map((orphan) => ({
+    modelId: orphan.model_id,
+    files: orphan.files,
+    bytes: orphan.bytes,
+  }));
+}
+
 /**
  * Get the size of a partial download if it exists.
  * Used to show "Resume" instead of "Download" in the UI.
@@ -241,16 +406,21 @@ export async function getPartialDownloadSize(
 
 /**
  * Delete a downloaded model.
+ * Rejected while the model is downloading, or loaded unless `force` is set.
  *
  * @param modelId - The model identifier to delete
+ * @param force - Unload the model first if it is currently loaded
  */
-export async function deleteModel(modelId: string): Promise<void> {
+export async function deleteModel(
+  modelId: string,
+  force = false
+): Promise<void> {
   if (!isDesktop()) {
     throw new Error("Model deletion is only supported on desktop");
   }
 
   const invoke = getTauriInvoke();
-  await invoke<void>("delete_model", { modelId });
+  await invoke<void>("delete_model", { modelId, force });
 }
 
 // ============================================================================
@@ -290,7 +460,12 @@ export function subscribeToDownloadProgress(
       bytesDownloaded: payload.bytes_downloaded,
       totalBytes: payload.total_bytes,
       speedBps: payload.speed_bps,
+      instantSpeedBps: payload.instant_speed_bps,
+      averageSpeedBps: payload.average_speed_bps,
       etaSeconds: payload.eta_seconds,
+      phasePercent: payload.phase_percent ?? undefined,
+      retryAttempt: payload.retry_attempt ?? undefined,
+      stalled: payload.stalled,
       startedAt: new Date(), // Approximate - Tauri doesn't send this
     };
 
@@ -347,6 +522,48 @@ export function subscribeToCorruptionEvents(
   });
 }
 
+/** How the installed model library changed */
+export type ModelChangeAction = "downloaded" | "imported" | "deleted";
+
+/** Library change event data from Tauri */
+export interface ModelsChangedEvent {
+  modelId: string;
+  action: ModelChangeAction;
+}
+
+/** Tauri models:changed event payload */
+interface TauriModelsChangedEvent {
+  model_id: string;
+  action: ModelChangeAction;
+}
+
+/**
+ * Subscribe to model library changes.
+ * Emitted when a download finishes, or a model is imported or deleted,
+ * so the library can refresh without polling.
+ *
+ * @param callback - Function to call with the affected model and action
+ * @returns Promise<UnlistenFn> - Function to unsubscribe from events
+ */
+export function subscribeToModelsChanged(
+  callback: (event: ModelsChangedEvent) => void
+): Promise<UnlistenFn> {
+  if (!isDesktop()) {
+    return Promise.resolve(() => {
+      // No-op unlisten function for non-desktop platforms
+    });
+  }
+
+  const listen = getTauriListen();
+
+  return listen<TauriModelsChangedEvent>("models:changed", (event) => {
+    callback({
+      modelId: event.payload.model_id,
+      action: event.payload.action,
+    });
+  });
+}
+
 // ============================================================================
 // Network Connectivity
 // ============================================================================
@@ -25,8 +25,17 @@ export interface GpuInfo {
   vram: number;
   /** Whether GPU supports CUDA (NVIDIA) or Metal (Apple) */
   computeCapable: boolean;
+  /** GPU maker; on Apple Silicon `vram` is unified memory shared with the CPU */
+  vendor?: GpuVendor;
+  /** VRAM currently in use by all apps in MB (NVIDIA only) */
+  vramUsed?: number;
+  /** VRAM still free for a model in MB (NVIDIA only) */
+  vramFree?: number;
 }
 
+/** GPU maker reported by the desktop backend */
+export type GpuVendor = "nvidia" | "apple";
+
 /** Complete hardware capability profile */
 export interface HardwareCapabilities {
   /** Available RAM in MB */
@@ -75,6 +84,9 @@ interface TauriGpuInfo {
   name: string;
   vram_mb: number;
   compute_capable: boolean;
+  vendor: GpuVendor;
+  vram_used_mb: number | null;
+  vram_free_mb: number | null;
 }
 
 // ============================================================================
@@ -119,6 +131,9 @@ async function getDesktopCapabilities(): Promise<HardwareCapabilities> {
           name: gpuInfo.name,
           vram: gpuInfo.vram_mb,
           computeCapable: gpuInfo.compute_capable,
+          vendor: gpuInfo.vendor,
+          vramUsed: gpuInfo.vram_used_mb ?? undefined,
+          vramFree: gpuInfo.vram_free_mb ?? undefined,
         }
       : null,
     detectedBy: "desktop" as const,
@@ -15,24 +15,35 @@ export type {
   CorruptionEvent,
   CorruptionEventCallback,
   DownloadProgressCallback,
+  InstalledModel,
+  ModelChangeAction,
+  ModelsChangedEvent,
+  ModelsStorageUsage,
+  OrphanedDownload,
 } from "./downloads";
 export {
   cancelModelDownload,
   checkStorageSpace,
+  DownloadStartError,
+  cleanupOrphanedDownloads,
   deleteModel,
   getModelPath,
+  getModelsStorageUsage,
   getPartialDownloadSize,
   isOnline,
+  listDownloadedModels,
   pauseModelDownload,
   resumeModelDownload,
   startModelDownload,
   subscribeToCorruptionEvents,
   subscribeToDownloadProgress,
+  subscribeToModelsChanged,
   subscribeToNetworkStatus,
 } from "./downloads";
 // Hardware capability detection (Story 2.1)
 export type {
   GpuInfo,
+  GpuVendor,
   HardwareCapabilities,
   ModelRecommendation,
   ModelRequirements,